@@ -0,0 +1,51 @@
+use calyx_frontend as frontend;
+use calyx_ir as ir;
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::path::Path;
+
+/// The guards of all the assignments in the gemm8 benchmark.
+fn guards() -> Vec<ir::Guard<ir::Nothing>> {
+    let bench = Path::new("benches/component-sharing/gemm8.futil");
+    let ws =
+        frontend::Workspace::construct(&Some(bench.into()), Path::new("."))
+            .unwrap();
+    let rep = ir::from_ast::ast_to_ir(ws).unwrap();
+    let mut guards = vec![];
+    for comp in &rep.components {
+        comp.iter_assignments(|assign| guards.push(*assign.guard.clone()));
+    }
+    guards
+}
+
+fn guard_ports_bench(c: &mut Criterion) {
+    let guards = guards();
+    let mut group = c.benchmark_group("guard-ports");
+    group.bench_function("all_ports", |b| {
+        b.iter(|| {
+            guards
+                .iter()
+                .map(|guard| guard.all_ports().len())
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("collect_ports_into", |b| {
+        let mut buf = vec![];
+        b.iter(|| {
+            let mut total = 0;
+            for guard in &guards {
+                buf.clear();
+                guard.collect_ports_into(&mut buf);
+                total += buf.len();
+            }
+            total
+        })
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = guard_ports;
+    config = Criterion::default().sample_size(20);
+    targets = guard_ports_bench
+}
+criterion_main!(guard_ports);
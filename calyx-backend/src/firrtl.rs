@@ -258,6 +258,11 @@ fn get_guard_string(guard: &ir::Guard<ir::Nothing>) -> String {
             let r_str = get_guard_string(r.as_ref());
             format!("and({}, {})", l_str, r_str)
         }
+        ir::Guard::Implies(l, r) => {
+            let l_str = get_guard_string(l.as_ref());
+            let r_str = get_guard_string(r.as_ref());
+            format!("or(not({}), {})", l_str, r_str)
+        }
         ir::Guard::Not(g) => {
             let g_str = get_guard_string(g);
             format!("not({})", g_str)
@@ -9,7 +9,7 @@ pub use backend_opt::BackendOpt;
 pub use firrtl::FirrtlBackend;
 pub use primitive_uses::PrimitiveUsesBackend;
 pub use traits::Backend;
-pub use verilog::VerilogBackend;
+pub use verilog::{ToVerilogExpr, VerilogBackend, VerilogCtx};
 
 #[cfg(feature = "mlir")]
 mod mlir;
@@ -37,7 +37,9 @@ fn get_mem_str(mem_type: &str) -> &str {
 /// used in a guard.
 fn validate_guard(guard: &ir::Guard<Nothing>) -> bool {
     match guard {
-        Guard::Or(left, right) | Guard::And(left, right) => {
+        Guard::Or(left, right)
+        | Guard::And(left, right)
+        | Guard::Implies(left, right) => {
             validate_guard(left) && validate_guard(right)
         }
         Guard::CompOp(_, left, right) => {
@@ -78,6 +80,42 @@ where
     Ok(())
 }
 
+/// Returns `Ok` if the library declares the comparison primitive for every
+/// comparison in the guards of `comp` that is instantiated as a primitive.
+fn validate_comparisons(
+    comp: &ir::Component,
+    lib: &ir::LibrarySignatures,
+) -> CalyxResult<()> {
+    fn missing(
+        guard: &ir::Guard<Nothing>,
+        lib: &ir::LibrarySignatures,
+    ) -> Option<&'static str> {
+        match guard {
+            Guard::Or(l, r) | Guard::And(l, r) | Guard::Implies(l, r) => {
+                missing(l, lib).or_else(|| missing(r, lib))
+            }
+            Guard::Not(g) => missing(g, lib),
+            Guard::CompOp(op, l, r) => {
+                let prim = comparison_prim(op);
+                (bit_test(op, l, r).is_none()
+                    && lib.find_primitive(prim).is_none())
+                .then_some(prim)
+            }
+            Guard::Port(_) | Guard::True | Guard::Info(_) => None,
+        }
+    }
+
+    for asgn in &comp.continuous_assignments {
+        if let Some(prim) = missing(&asgn.guard, lib) {
+            return Err(Error::malformed_structure(format!(
+                "Guard comparisons are emitted as instances of `{prim}`, which is not defined. Import `primitives/core.futil` or remove `--comparison-primitives`."
+            ))
+            .with_pos(&asgn.attributes));
+        }
+    }
+    Ok(())
+}
+
 /// Returns `Ok` if the control for `comp` is either a single `enable`
 /// or `empty`.
 fn validate_control(ctrl: &ir::Control) -> CalyxResult<()> {
@@ -99,6 +137,9 @@ impl Backend for VerilogBackend {
         for component in &ctx.components {
             validate_structure(component.get_groups().iter())?;
             validate_control(&component.control.borrow())?;
+            if ctx.bc.comparison_primitives {
+                validate_comparisons(component, &ctx.lib)?;
+            }
         }
         Ok(())
     }
@@ -140,6 +181,8 @@ impl Backend for VerilogBackend {
                 ctx.bc.synthesis_mode,
                 ctx.bc.enable_verification,
                 ctx.bc.flat_assign,
+                ctx.bc.guard_default,
+                ctx.bc.comparison_primitives,
                 out,
             );
             log::info!("Generated `{}` in {:?}", comp.name, time.elapsed());
@@ -228,6 +271,8 @@ fn emit_component<F: io::Write>(
     synthesis_mode: bool,
     enable_verification: bool,
     flat_assign: bool,
+    guard_default: ir::GuardDefault,
+    comparison_primitives: bool,
     f: &mut F,
 ) -> io::Result<()> {
     writeln!(f, "module {}(", comp.name)?;
@@ -312,22 +357,44 @@ fn emit_component<F: io::Write>(
         })
         .collect();
 
+    // Comparison primitives used by the guards.
+    let cmps = if comparison_primitives {
+        Comparators::new(&pool)
+    } else {
+        Comparators::default()
+    };
+    cmps.instances.iter().try_for_each(|(name, _)| {
+        let decl = v::Decl::new_logic(&format!("{name}_out"), 1);
+        writeln!(f, "{};", decl)
+    })?;
+    cmps.instances
+        .iter()
+        .try_for_each(|(_, instance)| writeln!(f, "{instance}"))?;
+
     if flat_assign {
         // Emit "flattened" assignments as ANF statements.
         // Emit Verilog for the flattened guards.
+        let ctx = VerilogCtx::with_wires(&pool);
         for (idx, guard) in pool.iter() {
-            write!(f, "wire {} = ", VerilogGuardRef(idx))?;
-            emit_guard(guard, f)?;
-            writeln!(f, ";")?;
+            if let Some(name) = cmps.get(idx) {
+                writeln!(f, "wire {} = {name}_out;", VerilogGuardRef(idx))?;
+                continue;
+            }
+            writeln!(
+                f,
+                "wire {} = {};",
+                VerilogGuardRef(idx),
+                guard.to_verilog_expr(&ctx)
+            )?;
         }
 
         // Emit assignments using these guards.
         for (dst, asgns) in &grouped_asgns {
-            emit_assignment_flat(dst, asgns, f)?;
+            emit_assignment_flat(dst, asgns, guard_default, f)?;
 
             if enable_verification {
                 if let Some(check) =
-                    emit_guard_disjoint_check(dst, asgns, &pool, true)
+                    emit_guard_disjoint_check(dst, asgns, &pool, &cmps, true)
                 {
                     writeln!(f, "always_comb begin")?;
                     writeln!(f, "  {check}")?;
@@ -341,13 +408,18 @@ fn emit_component<F: io::Write>(
 
         // Emit nested assignments.
         for (dst, asgns) in grouped_asgns {
-            let stmt =
-                v::Stmt::new_parallel(emit_assignment(dst, &asgns, &pool));
+            let stmt = v::Stmt::new_parallel(emit_assignment(
+                dst,
+                &asgns,
+                &pool,
+                &cmps,
+                guard_default,
+            ));
             writeln!(f, "{stmt}")?;
 
             if enable_verification {
                 if let Some(check) =
-                    emit_guard_disjoint_check(dst, &asgns, &pool, false)
+                    emit_guard_disjoint_check(dst, &asgns, &pool, &cmps, false)
                 {
                     checks.add_seq(check);
                 }
@@ -364,6 +436,88 @@ fn emit_component<F: io::Write>(
     Ok(())
 }
 
+/// Comparison primitives instantiated for the guards of a component.
+#[derive(Default)]
+struct Comparators {
+    /// The instance that computes each comparison, keyed by the index of the
+    /// comparison in the [ir::GuardPool].
+    names: HashMap<u32, String>,
+    /// The instances in the order they are emitted.
+    instances: Vec<(String, v::Instance)>,
+}
+
+impl Comparators {
+    /// Instantiates the comparison primitive for each distinct comparison in
+    /// `pool` so that guards read the output of the primitive instead of
+    /// using the Verilog operator. Comparisons against power-of-two
+    /// boundaries are still emitted as bit tests.
+    fn new(pool: &ir::GuardPool) -> Self {
+        let mut cmps = Comparators::default();
+        let mut seen: HashMap<_, String> = HashMap::new();
+        for (idx, guard) in pool.iter() {
+            let FlatGuard::CompOp(op, l, r) = guard else {
+                continue;
+            };
+            if bit_test(op, l, r).is_some() {
+                continue;
+            }
+            let prim = comparison_prim(op);
+            let key = (prim, l.borrow().canonical(), r.borrow().canonical());
+            let name = seen.entry(key).or_insert_with(|| {
+                let name = format!("_cmp{}", cmps.instances.len());
+                cmps.instances.push((
+                    name.clone(),
+                    comparator_instance(&name, prim, l, r),
+                ));
+                name
+            });
+            cmps.names.insert(idx.index(), name.clone());
+        }
+        cmps
+    }
+
+    /// The instance that computes the comparison `guard`, if any.
+    fn get(&self, guard: GuardRef) -> Option<&String> {
+        self.names.get(&guard.index())
+    }
+}
+
+/// The comparison primitive that implements `op`.
+fn comparison_prim(op: &ir::PortComp) -> &'static str {
+    match op {
+        ir::PortComp::Eq => "std_eq",
+        ir::PortComp::Neq => "std_neq",
+        ir::PortComp::Gt => "std_gt",
+        ir::PortComp::Lt => "std_lt",
+        ir::PortComp::Geq => "std_ge",
+        ir::PortComp::Leq => "std_le",
+    }
+}
+
+/// Instantiates the comparison primitive `prim` as `name`:
+/// ```verilog
+/// std_lt # (
+///     .WIDTH(8)
+/// ) _cmp0 (
+///     .left(x),
+///     .out(_cmp0_out),
+///     .right(y)
+/// );
+/// ```
+fn comparator_instance(
+    name: &str,
+    prim: &str,
+    l: &RRC<ir::Port>,
+    r: &RRC<ir::Port>,
+) -> v::Instance {
+    let mut inst = v::Instance::new(name, prim);
+    inst.add_param("WIDTH", v::Expr::new_int(l.borrow().width as i32));
+    inst.connect("left", port_to_ref(l));
+    inst.connect("right", port_to_ref(r));
+    inst.connect("out", v::Expr::new_ref(format!("{name}_out")));
+    inst
+}
+
 fn wire_decls(cell: &ir::Cell) -> Vec<(String, u64, ir::Direction)> {
     cell.ports
         .iter()
@@ -456,6 +610,7 @@ fn emit_guard_disjoint_check(
     dst: &RRC<ir::Port>,
     assignments: &[(RRC<ir::Port>, GuardRef)],
     pool: &ir::GuardPool,
+    cmps: &Comparators,
     flat: bool,
 ) -> Option<v::Sequential> {
     if assignments.len() < 2 {
@@ -467,8 +622,7 @@ fn emit_guard_disjoint_check(
         let expr = if flat {
             v::Expr::new_ref(VerilogGuardRef(*gr).to_string())
         } else {
-            let guard = pool.get(*gr);
-            guard_to_expr(guard, pool)
+            guard_to_expr(*gr, pool, cmps)
         };
         concat.add_expr(expr);
     });
@@ -509,9 +663,32 @@ fn is_data_port(pr: &RRC<ir::Port>) -> bool {
     false
 }
 
+/// The value driven onto a non-data port when none of its guards are active.
+/// Returns `None` for the `'x` default. The same constant is used for ports
+/// of every width, so it is truncated to the width of `dst`.
+fn guard_default_val(
+    dst: &RRC<ir::Port>,
+    guard_default: ir::GuardDefault,
+) -> Option<(u64, u64)> {
+    let width = dst.borrow().width;
+    match guard_default {
+        ir::GuardDefault::Zero => Some((width, 0)),
+        ir::GuardDefault::Undef => None,
+        ir::GuardDefault::Const(val) => {
+            let mask = if width >= 64 {
+                u64::MAX
+            } else {
+                (1 << width) - 1
+            };
+            Some((width, val & mask))
+        }
+    }
+}
+
 /// Generates an assign statement that uses ternaries to select the correct
-/// assignment to enable and adds a default assignment to 0 when none of the
-/// guards are active.
+/// assignment to enable and adds a default assignment when none of the
+/// guards are active. The default is 0 unless overridden by
+/// [ir::GuardDefault].
 ///
 /// Example:
 /// ```
@@ -527,13 +704,14 @@ fn emit_assignment(
     dst: &RRC<ir::Port>,
     assignments: &[(RRC<ir::Port>, GuardRef)],
     pool: &ir::GuardPool,
+    cmps: &Comparators,
+    guard_default: ir::GuardDefault,
 ) -> v::Parallel {
     // Mux over the assignment with the given default value.
     let fold_assigns = |init: v::Expr| -> v::Expr {
         assignments.iter().rfold(init, |acc, (src, gr)| {
-            let guard = pool.get(*gr);
             let asgn = port_to_ref(src);
-            v::Expr::new_mux(guard_to_expr(guard, pool), asgn, acc)
+            v::Expr::new_mux(guard_to_expr(*gr, pool, cmps), asgn, acc)
         })
     };
 
@@ -551,21 +729,25 @@ fn emit_assignment(
             fold_assigns(v::Expr::X)
         }
     } else {
-        let init =
-            v::Expr::new_ulit_dec(dst.borrow().width as u32, &0.to_string());
+        let init = match guard_default_val(dst, guard_default) {
+            Some((width, val)) => {
+                v::Expr::new_ulit_dec(width as u32, &val.to_string())
+            }
+            None => v::Expr::X,
+        };
 
         // Flatten the mux expression if there is exactly one assignment with a true guard.
         if assignments.len() == 1 {
             let (src, gr) = &assignments[0];
             if gr.is_true() {
                 port_to_ref(src)
-            } else if src.borrow().is_constant(1, 1) {
-                let guard = pool.get(*gr);
-                guard_to_expr(guard, pool)
+            } else if src.borrow().is_constant(1, 1)
+                && guard_default == ir::GuardDefault::Zero
+            {
+                guard_to_expr(*gr, pool, cmps)
             } else {
-                let guard = pool.get(*gr);
                 v::Expr::new_mux(
-                    guard_to_expr(guard, pool),
+                    guard_to_expr(*gr, pool, cmps),
                     port_to_ref(src),
                     init,
                 )
@@ -580,6 +762,7 @@ fn emit_assignment(
 fn emit_assignment_flat<F: io::Write>(
     dst: &RRC<ir::Port>,
     assignments: &[(RRC<ir::Port>, GuardRef)],
+    guard_default: ir::GuardDefault,
     f: &mut F,
 ) -> io::Result<()> {
     let data = is_data_port(dst);
@@ -605,7 +788,9 @@ fn emit_assignment_flat<F: io::Write>(
                     VerilogPortRef(dst),
                     VerilogPortRef(src)
                 );
-            } else if src.borrow().is_constant(1, 1) {
+            } else if src.borrow().is_constant(1, 1)
+                && guard_default == ir::GuardDefault::Zero
+            {
                 return writeln!(
                     f,
                     "assign {} = {};",
@@ -631,7 +816,10 @@ fn emit_assignment_flat<F: io::Write>(
     if data {
         writeln!(f, "  'x;")
     } else {
-        writeln!(f, "  {}'d0;", dst.borrow().width)
+        match guard_default_val(dst, guard_default) {
+            Some((width, val)) => writeln!(f, "  {width}'d{val};"),
+            None => writeln!(f, "  'x;"),
+        }
     }
 }
 
@@ -658,37 +846,72 @@ fn port_to_ref(port_ref: &RRC<ir::Port>) -> v::Expr {
     }
 }
 
-fn guard_to_expr(guard: &ir::FlatGuard, pool: &ir::GuardPool) -> v::Expr {
-    let op = |g: &ir::FlatGuard| match g {
-        FlatGuard::Or(..) => v::Expr::new_bit_or,
-        FlatGuard::And(..) => v::Expr::new_bit_and,
-        FlatGuard::CompOp(op, ..) => match op {
-            ir::PortComp::Eq => v::Expr::new_eq,
-            ir::PortComp::Neq => v::Expr::new_neq,
-            ir::PortComp::Gt => v::Expr::new_gt,
-            ir::PortComp::Lt => v::Expr::new_lt,
-            ir::PortComp::Geq => v::Expr::new_geq,
-            ir::PortComp::Leq => v::Expr::new_leq,
-        },
-        FlatGuard::Not(..) | FlatGuard::Port(..) | FlatGuard::True => {
-            unreachable!()
-        }
+/// The guard as a Verilog expression, reading the comparison primitives in
+/// `cmps` instead of comparing the ports.
+fn guard_to_expr(
+    gr: GuardRef,
+    pool: &ir::GuardPool,
+    cmps: &Comparators,
+) -> v::Expr {
+    let ctx = VerilogCtx {
+        pool,
+        wires: false,
+        cmps: Some(cmps),
     };
+    v::Expr::new_ref(ctx.operand(gr, 0))
+}
 
-    match guard {
-        FlatGuard::And(l, r) | FlatGuard::Or(l, r) => {
-            let lg = pool.get(*l);
-            let rg = pool.get(*r);
-            op(guard)(guard_to_expr(lg, pool), guard_to_expr(rg, pool))
-        }
-        FlatGuard::CompOp(_, l, r) => op(guard)(port_to_ref(l), port_to_ref(r)),
-        FlatGuard::Not(r) => {
-            let g = pool.get(*r);
-            v::Expr::new_not(guard_to_expr(g, pool))
-        }
-        FlatGuard::Port(p) => port_to_ref(p),
-        FlatGuard::True => v::Expr::new_ulit_bin(1, &1.to_string()),
+/// A comparison that only depends on whether the bits `port[width-1:lo]` are
+/// all zero.
+struct BitTest {
+    port: RRC<ir::Port>,
+    lo: u64,
+    /// The comparison is true when the bits are zero.
+    zero: bool,
+}
+
+/// Recognize comparisons of a port against a power-of-two boundary, such as
+/// `x < 8` or `x <= 7`, which are cheaper to implement as a test on the upper
+/// bits of the port than as a magnitude comparator.
+fn bit_test(
+    op: &ir::PortComp,
+    l: &RRC<ir::Port>,
+    r: &RRC<ir::Port>,
+) -> Option<BitTest> {
+    // Normalize the comparison so that the constant is on the right.
+    let (op, port, val) =
+        match (l.borrow().constant_value(), r.borrow().constant_value()) {
+            (None, Some(val)) => (op.clone(), l, val),
+            (Some(val), None) => {
+                let op = match op {
+                    ir::PortComp::Lt => ir::PortComp::Gt,
+                    ir::PortComp::Gt => ir::PortComp::Lt,
+                    ir::PortComp::Leq => ir::PortComp::Geq,
+                    ir::PortComp::Geq => ir::PortComp::Leq,
+                    ir::PortComp::Eq | ir::PortComp::Neq => return None,
+                };
+                (op, r, val)
+            }
+            _ => return None,
+        };
+    // `x < 2^k` and `x >= 2^k` check the bits at and above `k`. `x <= 2^k - 1`
+    // and `x > 2^k - 1` do too, as long as the constant isn't all ones.
+    let (bound, zero) = match op {
+        ir::PortComp::Lt => (val, true),
+        ir::PortComp::Geq => (val, false),
+        ir::PortComp::Leq => (val.checked_add(1)?, true),
+        ir::PortComp::Gt => (val.checked_add(1)?, false),
+        ir::PortComp::Eq | ir::PortComp::Neq => return None,
+    };
+    let lo = bound.trailing_zeros() as u64;
+    if !bound.is_power_of_two() || lo >= port.borrow().width {
+        return None;
     }
+    Some(BitTest {
+        port: Rc::clone(port),
+        lo,
+        zero,
+    })
 }
 
 /// A little newtype wrapper for GuardRefs that makes it easy to format them as Verilog variables.
@@ -733,28 +956,182 @@ impl<'a> std::fmt::Display for VerilogPortRef<'a> {
     }
 }
 
-fn emit_guard<F: std::io::Write>(
-    guard: &ir::FlatGuard,
-    f: &mut F,
-) -> io::Result<()> {
-    let gr = VerilogGuardRef;
-    match guard {
-        FlatGuard::Or(l, r) => write!(f, "{} | {}", gr(*l), gr(*r)),
-        FlatGuard::And(l, r) => write!(f, "{} & {}", gr(*l), gr(*r)),
-        FlatGuard::CompOp(op, l, r) => {
-            let op = match op {
-                ir::PortComp::Eq => "==",
-                ir::PortComp::Neq => "!=",
-                ir::PortComp::Gt => ">",
-                ir::PortComp::Lt => "<",
-                ir::PortComp::Geq => ">=",
-                ir::PortComp::Leq => "<=",
-            };
-            write!(f, "{} {} {}", VerilogPortRef(l), op, VerilogPortRef(r))
+/// How [ToVerilogExpr] prints the operands of a flattened guard. Nested
+/// guards always inline their operands.
+pub struct VerilogCtx<'a> {
+    /// The pool the operands are stored in.
+    pool: &'a ir::GuardPool,
+    /// Refer to operands by the `_guardN` wires that the backend emits them
+    /// to instead of inlining them.
+    wires: bool,
+    /// Refer to comparisons by the output of the primitive computing them.
+    cmps: Option<&'a Comparators>,
+}
+
+impl<'a> VerilogCtx<'a> {
+    /// Inline the operands of guards stored in `pool`.
+    pub fn new(pool: &'a ir::GuardPool) -> Self {
+        VerilogCtx {
+            pool,
+            wires: false,
+            cmps: None,
         }
-        FlatGuard::Not(g) => write!(f, "~{}", gr(*g)),
-        FlatGuard::True => write!(f, "1"),
-        FlatGuard::Port(p) => write!(f, "{}", VerilogPortRef(p)),
+    }
+
+    /// Refer to the operands of guards stored in `pool` by their `_guardN`
+    /// wires, as the backend does when emitting flattened assignments.
+    pub fn with_wires(pool: &'a ir::GuardPool) -> Self {
+        VerilogCtx {
+            pool,
+            wires: true,
+            cmps: None,
+        }
+    }
+
+    /// Print an operand, parenthesized if its operator binds weaker than
+    /// the precedence `prec` of its parent.
+    fn operand(&self, guard: GuardRef, prec: u8) -> String {
+        if self.wires {
+            return VerilogGuardRef(guard).to_string();
+        }
+        if let Some(name) = self.cmps.and_then(|cmps| cmps.get(guard)) {
+            return format!("{name}_out");
+        }
+        let guard = self.pool.get(guard);
+        parenthesize(
+            guard.to_verilog_expr(self),
+            flat_node(guard).precedence(),
+            prec,
+        )
+    }
+}
+
+/// The operator at the root of a guard, with operands of type `G`. Both
+/// kinds of guards are printed through this so that they agree.
+enum ExprNode<'a, G> {
+    Or(G, G),
+    And(G, G),
+    /// Printed as `~l | r`.
+    Implies(G, G),
+    Not(G),
+    CompOp(&'a ir::PortComp, &'a RRC<ir::Port>, &'a RRC<ir::Port>),
+    Port(&'a RRC<ir::Port>),
+    True,
+}
+
+impl<G> ExprNode<'_, G> {
+    /// How tightly the operator binds in Verilog.
+    fn precedence(&self) -> u8 {
+        match self {
+            ExprNode::Or(..) | ExprNode::Implies(..) => 1,
+            ExprNode::And(..) => 2,
+            ExprNode::CompOp(..) => 3,
+            ExprNode::Not(_) | ExprNode::True | ExprNode::Port(_) => 4,
+        }
+    }
+
+    /// Print the node, using `operand` to print each operand given the
+    /// precedence of the operator it appears under.
+    fn print(self, operand: impl Fn(G, u8) -> String) -> String {
+        match self {
+            ExprNode::Or(l, r) => {
+                format!("{} | {}", operand(l, 1), operand(r, 1))
+            }
+            ExprNode::And(l, r) => {
+                format!("{} & {}", operand(l, 2), operand(r, 2))
+            }
+            ExprNode::Implies(l, r) => {
+                format!("~{} | {}", operand(l, 4), operand(r, 1))
+            }
+            ExprNode::Not(g) => format!("~{}", operand(g, 4)),
+            ExprNode::CompOp(op, l, r) => {
+                if let Some(BitTest { port, lo, zero }) = bit_test(op, l, r) {
+                    let width = port.borrow().width;
+                    return format!(
+                        "{}[{}:{lo}] {} {}'d0",
+                        VerilogPortRef(&port),
+                        width - 1,
+                        if zero { "==" } else { "!=" },
+                        width - lo,
+                    );
+                }
+                let op = match op {
+                    ir::PortComp::Eq => "==",
+                    ir::PortComp::Neq => "!=",
+                    ir::PortComp::Gt => ">",
+                    ir::PortComp::Lt => "<",
+                    ir::PortComp::Geq => ">=",
+                    ir::PortComp::Leq => "<=",
+                };
+                format!("{} {} {}", VerilogPortRef(l), op, VerilogPortRef(r))
+            }
+            ExprNode::Port(p) => VerilogPortRef(p).to_string(),
+            ExprNode::True => "1'd1".to_string(),
+        }
+    }
+}
+
+fn flat_node(guard: &ir::FlatGuard) -> ExprNode<'_, GuardRef> {
+    match guard {
+        FlatGuard::Or(l, r) => ExprNode::Or(*l, *r),
+        FlatGuard::And(l, r) => ExprNode::And(*l, *r),
+        FlatGuard::Not(g) => ExprNode::Not(*g),
+        FlatGuard::CompOp(op, l, r) => ExprNode::CompOp(op, l, r),
+        FlatGuard::Port(p) => ExprNode::Port(p),
+        FlatGuard::True => ExprNode::True,
+    }
+}
+
+fn nested_node(guard: &Guard<Nothing>) -> ExprNode<'_, &Guard<Nothing>> {
+    match guard {
+        Guard::Or(l, r) => ExprNode::Or(l, r),
+        Guard::And(l, r) => ExprNode::And(l, r),
+        Guard::Implies(l, r) => ExprNode::Implies(l, r),
+        Guard::Not(g) => ExprNode::Not(g),
+        Guard::CompOp(op, l, r) => ExprNode::CompOp(op, l, r),
+        Guard::Port(p) => ExprNode::Port(p),
+        Guard::True => ExprNode::True,
+        Guard::Info(_) => unreachable!("guard should not have info"),
+    }
+}
+
+/// Parenthesize `expr`, whose operator has precedence `prec`, if it binds
+/// weaker than the operator `parent` it appears under.
+fn parenthesize(expr: String, prec: u8, parent: u8) -> String {
+    if prec < parent {
+        format!("({expr})")
+    } else {
+        expr
+    }
+}
+
+/// Guards that can be printed as a combinational Verilog expression:
+/// ```ignore
+/// let expr = guard.to_verilog_expr(&VerilogCtx::new(&pool));
+/// ```
+/// Flattened guards print their operands as `ctx` says, while nested guards
+/// always inline them.
+pub trait ToVerilogExpr {
+    fn to_verilog_expr(&self, ctx: &VerilogCtx) -> String;
+}
+
+/// Comparisons against power-of-two boundaries are printed as tests on the
+/// upper bits of the port, and `True` is the 1-bit literal `1'd1` so that it
+/// does not widen the expression it is part of.
+impl ToVerilogExpr for ir::FlatGuard {
+    fn to_verilog_expr(&self, ctx: &VerilogCtx) -> String {
+        flat_node(self).print(|g, prec| ctx.operand(g, prec))
+    }
+}
+
+/// Prints the same expression as the guard flattened into a
+/// [ir::GuardPool], with implications as `~l | r`.
+impl ToVerilogExpr for Guard<Nothing> {
+    fn to_verilog_expr(&self, ctx: &VerilogCtx) -> String {
+        nested_node(self).print(|g, prec| {
+            let prec_g = nested_node(g).precedence();
+            parenthesize(g.to_verilog_expr(ctx), prec_g, prec)
+        })
     }
 }
 
@@ -870,3 +1247,129 @@ fn memory_read_write(comp: &ir::Component) -> Vec<v::Stmt> {
         v::Stmt::new_parallel(v::Parallel::new_process(final_block)),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ToVerilogExpr, VerilogCtx};
+    use calyx_ir::{self as ir, rrc, Guard, Nothing, PortComp, RRC, WRC};
+
+    fn cell(
+        name: &str,
+        prototype: ir::CellType,
+        ports: &[(&str, u64)],
+    ) -> RRC<ir::Cell> {
+        let cell = rrc(ir::Cell::new(ir::Id::from(name), prototype));
+        for (port, width) in ports {
+            let port = rrc(ir::Port {
+                name: ir::Id::from(*port),
+                width: *width,
+                direction: ir::Direction::Output,
+                parent: ir::PortParent::Cell(WRC::from(&cell)),
+                attributes: ir::Attributes::default(),
+            });
+            cell.borrow_mut().ports.push(port);
+        }
+        cell
+    }
+
+    fn constant(val: u64, width: u64) -> RRC<ir::Cell> {
+        let prototype = ir::CellType::Constant { val, width };
+        let name = ir::Cell::constant_name(val, width);
+        cell(name.as_ref(), prototype, &[("out", width)])
+    }
+
+    fn expr(guard: &Guard<Nothing>, wires: bool) -> String {
+        let mut pool = ir::GuardPool::new();
+        let guard = pool.flatten(guard);
+        let ctx = if wires {
+            VerilogCtx::with_wires(&pool)
+        } else {
+            VerilogCtx::new(&pool)
+        };
+        pool.get(guard).to_verilog_expr(&ctx)
+    }
+
+    /// Prints the guard without flattening it, checking that it agrees with
+    /// the flattened guard.
+    fn nested(guard: &Guard<Nothing>) -> String {
+        let pool = ir::GuardPool::new();
+        let printed = guard.to_verilog_expr(&VerilogCtx::new(&pool));
+        assert_eq!(printed, expr(guard, false));
+        printed
+    }
+
+    #[test]
+    fn to_verilog_expr() {
+        let prototype = ir::CellType::Primitive {
+            name: ir::Id::from("std_reg"),
+            param_binding: Box::default(),
+            is_comb: false,
+            latency: None,
+        };
+        let c = cell("c", prototype, &[("a", 1), ("b", 1), ("x", 4)]);
+        let port = |name: &str| c.borrow().get(name);
+        let (a, b) = (Guard::port(port("a")), Guard::port(port("b")));
+        let (three, eight) = (constant(3, 4), constant(8, 4));
+        let bx = |g: Guard<Nothing>| Box::new(g);
+
+        let eq =
+            Guard::CompOp(PortComp::Eq, port("x"), three.borrow().get("out"));
+        let g = Guard::And(
+            bx(Guard::Or(bx(a.clone()), bx(b.clone()))),
+            bx(Guard::Not(bx(eq))),
+        );
+        assert_eq!(expr(&g, false), "(c_a | c_b) & ~(c_x == 4'd3)");
+        assert_eq!(expr(&g, true), "_guard3 & _guard5");
+
+        // Comparisons bind tighter than `&`, which binds tighter than `|`.
+        let lt =
+            Guard::CompOp(PortComp::Lt, port("x"), eight.borrow().get("out"));
+        let g =
+            Guard::Or(bx(Guard::And(bx(a.clone()), bx(lt))), bx(Guard::True));
+        assert_eq!(expr(&g, false), "c_a & c_x[3:3] == 1'd0 | 1'd1");
+
+        let g = Guard::Not(bx(Guard::And(bx(a), bx(b))));
+        assert_eq!(expr(&g, false), "~(c_a & c_b)");
+        assert_eq!(expr(&g, true), "~_guard3");
+
+        assert_eq!(expr(&Guard::True, true), "1'd1");
+    }
+
+    #[test]
+    fn nested_to_verilog_expr() {
+        let prototype = ir::CellType::Primitive {
+            name: ir::Id::from("std_reg"),
+            param_binding: Box::default(),
+            is_comb: false,
+            latency: None,
+        };
+        let c = cell("c", prototype, &[("a", 1), ("b", 1), ("x", 4)]);
+        let port = |name: &str| c.borrow().get(name);
+        let (a, b) = (Guard::port(port("a")), Guard::port(port("b")));
+        let (three, eight) = (constant(3, 4), constant(8, 4));
+        let bx = |g: Guard<Nothing>| Box::new(g);
+
+        let eq =
+            Guard::CompOp(PortComp::Eq, port("x"), three.borrow().get("out"));
+        let g = Guard::And(
+            bx(Guard::Or(bx(a.clone()), bx(b.clone()))),
+            bx(Guard::Not(bx(eq))),
+        );
+        assert_eq!(nested(&g), "(c_a | c_b) & ~(c_x == 4'd3)");
+
+        let lt =
+            Guard::CompOp(PortComp::Lt, port("x"), eight.borrow().get("out"));
+        let g =
+            Guard::Or(bx(Guard::And(bx(a.clone()), bx(lt))), bx(Guard::True));
+        assert_eq!(nested(&g), "c_a & c_x[3:3] == 1'd0 | 1'd1");
+
+        let g =
+            Guard::Implies(bx(Guard::And(bx(a.clone()), bx(b.clone()))), bx(b));
+        assert_eq!(nested(&g), "~(c_a & c_b) | c_b");
+        let g = Guard::And(
+            bx(Guard::Implies(bx(a.clone()), bx(Guard::True))),
+            bx(a),
+        );
+        assert_eq!(nested(&g), "(~c_a | 1'd1) & c_a");
+    }
+}
@@ -138,6 +138,10 @@ pub enum Atom {
     Port(Port),
     /// A constant.
     Num(BitNum),
+    /// A constant without an explicit width, such as `0xFF`, whose width is
+    /// the minimum width needed to represent it. It takes the width of the
+    /// port it is compared against.
+    Unsized(BitNum),
 }
 
 /// The AST for GuardExprs
@@ -146,8 +150,13 @@ pub enum GuardExpr {
     // Logical operations
     And(Box<GuardExpr>, Box<GuardExpr>),
     Or(Box<GuardExpr>, Box<GuardExpr>),
+    /// `a -> b`: `b` holds whenever `a` does.
+    Implies(Box<GuardExpr>, Box<GuardExpr>),
     Not(Box<GuardExpr>),
     CompOp(CompGuard),
+    /// `approx_eq(l, r, tolerance)`: the unsigned values of `l` and `r`
+    /// differ by at most `tolerance`.
+    ApproxEq(Atom, Atom, u64),
     Atom(Atom),
 }
 
@@ -159,6 +168,7 @@ pub type CompGuard = (GuardComp, Atom, Atom);
 pub enum StaticGuardExpr {
     And(Box<StaticGuardExpr>, Box<StaticGuardExpr>),
     Or(Box<StaticGuardExpr>, Box<StaticGuardExpr>),
+    Implies(Box<StaticGuardExpr>, Box<StaticGuardExpr>),
     Not(Box<StaticGuardExpr>),
     CompOp(CompGuard),
     Atom(Atom),
@@ -317,6 +327,22 @@ pub enum Control {
         /// Attributes
         attributes: Attributes,
     },
+    /// An if statement whose condition is a guard expression. The
+    /// condition is computed by a combinational group generated when the
+    /// statement is lowered.
+    IfGuard {
+        /// The condition of the statement.
+        guard: GuardExpr,
+
+        /// Control for the true branch.
+        tbranch: Box<Control>,
+
+        /// Control for the false branch.
+        fbranch: Box<Control>,
+
+        /// Attributes
+        attributes: Attributes,
+    },
     /// Standard imperative while statement
     While {
         /// Port that connects the conditional check.
@@ -448,6 +474,7 @@ impl Control {
             Control::Seq { attributes, .. } => attributes,
             Control::Par { attributes, .. } => attributes,
             Control::If { attributes, .. } => attributes,
+            Control::IfGuard { attributes, .. } => attributes,
             Control::While { attributes, .. } => attributes,
             Control::Repeat { attributes, .. } => attributes,
             Control::Enable { attributes, .. } => attributes,
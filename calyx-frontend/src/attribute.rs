@@ -76,6 +76,20 @@ pub enum BoolAttr {
     #[strum(serialize = "fast")]
     /// https://github.com/calyxir/calyx/issues/1828
     Fast,
+    #[strum(serialize = "dont_care")]
+    /// The guard of this assignment is a don't-care condition for its
+    /// destination instead of a write.
+    DontCare,
+    #[strum(serialize = "zero_init")]
+    /// The initial zero value of this register is intentional
+    ZeroInit,
+    #[strum(serialize = "float")]
+    /// The outputs of this cell hold IEEE-754 floating-point values
+    Float,
+    #[strum(serialize = "always")]
+    /// This group is active in every cycle, so its assignments do not need
+    /// to be guarded by its `go` signal
+    Always,
 }
 
 impl From<BoolAttr> for Attribute {
@@ -37,6 +37,7 @@ const _GRAMMAR: &str = include_str!("syntax.pest");
 lazy_static::lazy_static! {
     static ref PRATT: PrattParser<Rule> =
     PrattParser::new()
+        .op(Op::infix(Rule::guard_implies, Assoc::Right))
         .op(Op::infix(Rule::guard_or, Assoc::Left))
         .op(Op::infix(Rule::guard_and, Assoc::Left));
 }
@@ -150,6 +151,9 @@ impl CalyxParser {
             })
             .map_infix(|lhs, op, rhs| {
                 Ok(match op.as_rule() {
+                    Rule::guard_implies => {
+                        Box::new(GuardExpr::Implies(lhs?, rhs?))
+                    }
                     Rule::guard_or => Box::new(GuardExpr::Or(lhs?, rhs?)),
                     Rule::guard_and => Box::new(GuardExpr::And(lhs?, rhs?)),
                     _ => unreachable!(),
@@ -176,6 +180,9 @@ impl CalyxParser {
             })
             .map_infix(|lhs, op, rhs| {
                 Ok(match op.as_rule() {
+                    Rule::guard_implies => {
+                        Box::new(StaticGuardExpr::Implies(lhs?, rhs?))
+                    }
                     Rule::guard_or => Box::new(StaticGuardExpr::Or(lhs?, rhs?)),
                     Rule::guard_and => {
                         Box::new(StaticGuardExpr::And(lhs?, rhs?))
@@ -347,6 +354,23 @@ impl CalyxParser {
         }
     }
 
+    fn unsized_lit(input: Node) -> ParseResult<BitNum> {
+        let span = Self::get_span(&input);
+        let (num_type, val) = match_nodes!(
+            input.into_children();
+            [hex(val)] => (NumType::Hex, val),
+            [binary(val)] => (NumType::Binary, val),
+        );
+        // The minimum width needed to represent the value.
+        let width = u64::from((u64::BITS - val.leading_zeros()).max(1));
+        Ok(BitNum {
+            width,
+            num_type,
+            val,
+            span,
+        })
+    }
+
     fn char(input: Node) -> ParseResult<&str> {
         Ok(input.as_str())
     }
@@ -622,15 +646,32 @@ impl CalyxParser {
         Ok(())
     }
 
+    fn cmp_operand(input: Node) -> ParseResult<ast::Atom> {
+        Ok(match_nodes!(
+            input.into_children();
+            [unsized_lit(num)] => ast::Atom::Unsized(num),
+            [expr(atom)] => atom,
+        ))
+    }
+
     fn cmp_expr(input: Node) -> ParseResult<ast::CompGuard> {
         Ok(match_nodes!(
             input.into_children();
-            [expr(l), guard_eq(_), expr(r)] => (GC::Eq, l, r),
-            [expr(l), guard_neq(_), expr(r)] => (GC::Neq, l, r),
-            [expr(l), guard_geq(_), expr(r)] => (GC::Geq, l, r),
-            [expr(l), guard_leq(_), expr(r)] => (GC::Leq, l, r),
-            [expr(l), guard_gt(_), expr(r)] =>  (GC::Gt, l, r),
-            [expr(l), guard_lt(_), expr(r)] =>  (GC::Lt, l, r),
+            [cmp_operand(l), guard_eq(_), cmp_operand(r)] => (GC::Eq, l, r),
+            [cmp_operand(l), guard_neq(_), cmp_operand(r)] => (GC::Neq, l, r),
+            [cmp_operand(l), guard_geq(_), cmp_operand(r)] => (GC::Geq, l, r),
+            [cmp_operand(l), guard_leq(_), cmp_operand(r)] => (GC::Leq, l, r),
+            [cmp_operand(l), guard_gt(_), cmp_operand(r)] =>  (GC::Gt, l, r),
+            [cmp_operand(l), guard_lt(_), cmp_operand(r)] =>  (GC::Lt, l, r),
+        ))
+    }
+
+    fn approx_eq(input: Node) -> ParseResult<(ast::Atom, ast::Atom, u64)> {
+        Ok(match_nodes!(
+            input.into_children();
+            [cmp_operand(l), cmp_operand(r), bitwidth(tolerance)] => {
+                (l, r, tolerance)
+            }
         ))
     }
 
@@ -653,6 +694,7 @@ impl CalyxParser {
             input.into_children();
             [guard_expr(guard)] => *guard,
             [cmp_expr((gc, a1, a2))] => ast::GuardExpr::CompOp((gc, a1, a2)),
+            [approx_eq((a1, a2, tol))] => ast::GuardExpr::ApproxEq(a1, a2, tol),
             [expr(e)] => ast::GuardExpr::Atom(e),
             [guard_not(_), expr(e)] => {
                 ast::GuardExpr::Not(Box::new(ast::GuardExpr::Atom(e)))
@@ -663,6 +705,9 @@ impl CalyxParser {
             [guard_not(_), guard_expr(e)] => {
                 ast::GuardExpr::Not(e)
             },
+            [guard_not(_), approx_eq((a1, a2, tol))] => {
+                ast::GuardExpr::Not(Box::new(ast::GuardExpr::ApproxEq(a1, a2, tol)))
+            },
             [guard_not(_), expr(e)] =>
                 ast::GuardExpr::Not(Box::new(ast::GuardExpr::Atom(e)))
         ))
@@ -956,6 +1001,13 @@ impl CalyxParser {
         ))
     }
 
+    fn if_guard(input: Node) -> ParseResult<ast::GuardExpr> {
+        Ok(match_nodes!(
+            input.into_children();
+            [guard_expr(guard)] => *guard,
+        ))
+    }
+
     fn if_stmt(input: Node) -> ParseResult<ast::Control> {
         let span = Self::get_span(&input);
         Ok(match_nodes!(
@@ -983,6 +1035,26 @@ impl CalyxParser {
                     fbranch: Box::new(fbranch),
                     attributes: attrs.add_span(span),
                 },
+            [at_attributes(attrs), if_guard(guard), block(stmt)] => ast::Control::IfGuard {
+                guard,
+                tbranch: Box::new(stmt),
+                fbranch: Box::new(ast::Control::Empty { attributes: Attributes::default() }),
+                attributes: attrs.add_span(span),
+            },
+            [at_attributes(attrs), if_guard(guard), block(tbranch), block(fbranch)] =>
+                ast::Control::IfGuard {
+                    guard,
+                    tbranch: Box::new(tbranch),
+                    fbranch: Box::new(fbranch),
+                    attributes: attrs.add_span(span),
+                },
+            [at_attributes(attrs), if_guard(guard), block(tbranch), if_stmt(fbranch)] =>
+                ast::Control::IfGuard {
+                    guard,
+                    tbranch: Box::new(tbranch),
+                    fbranch: Box::new(fbranch),
+                    attributes: attrs.add_span(span),
+                },
 
         ))
     }
@@ -0,0 +1,90 @@
+//! And-inverter graphs in the style of the AIGER format.
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+/// An and-inverter graph with a single output, built by
+/// [Guard::to_aig](crate::Guard::to_aig).
+///
+/// Signals are AIGER literals: variable `v` is the literal `2 * v` and its
+/// negation is `2 * v + 1`, and the literals `0` and `1` are the constants
+/// false and true. The primary inputs are the variables `1` to `inputs`,
+/// where input `k` is the literal [Aig::input], and the and gates are the
+/// variables after them, in the order they are stored in `ands`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aig {
+    /// The number of primary inputs.
+    pub inputs: usize,
+    /// The fanins of each and gate. The larger literal comes first and both
+    /// are smaller than the literal of the gate.
+    pub ands: Vec<(usize, usize)>,
+    /// The literal of the output.
+    pub output: usize,
+    /// The gate of each pair of fanins, so equal gates are built once.
+    strash: HashMap<(usize, usize), usize>,
+}
+
+impl Aig {
+    pub const FALSE: usize = 0;
+    pub const TRUE: usize = 1;
+
+    /// A graph with `inputs` inputs and no gates whose output is false.
+    pub fn new(inputs: usize) -> Self {
+        Aig {
+            inputs,
+            ands: vec![],
+            output: Self::FALSE,
+            strash: HashMap::new(),
+        }
+    }
+
+    /// The literal of input `k`.
+    pub fn input(&self, k: usize) -> usize {
+        assert!(k < self.inputs, "input {k} out of range");
+        2 * (k + 1)
+    }
+
+    /// The negation of a literal.
+    pub fn not(lit: usize) -> usize {
+        lit ^ 1
+    }
+
+    /// The literal of the conjunction of two literals. Constants and equal
+    /// or complementary fanins are folded without adding a gate.
+    pub fn and(&mut self, l: usize, r: usize) -> usize {
+        let (l, r) = (l.max(r), l.min(r));
+        if r == Self::FALSE || l == Self::not(r) {
+            return Self::FALSE;
+        }
+        if r == Self::TRUE || l == r {
+            return l;
+        }
+        if let Some(&lit) = self.strash.get(&(l, r)) {
+            return lit;
+        }
+        self.ands.push((l, r));
+        let lit = 2 * (self.inputs + self.ands.len());
+        self.strash.insert((l, r), lit);
+        lit
+    }
+
+    /// The literal of the disjunction of two literals, by De Morgan's law.
+    pub fn or(&mut self, l: usize, r: usize) -> usize {
+        Self::not(self.and(Self::not(l), Self::not(r)))
+    }
+}
+
+/// Print the graph in the ASCII AIGER format without latches.
+impl Display for Aig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (i, a) = (self.inputs, self.ands.len());
+        writeln!(f, "aag {} {i} 0 1 {a}", i + a)?;
+        for k in 0..i {
+            writeln!(f, "{}", self.input(k))?;
+        }
+        writeln!(f, "{}", self.output)?;
+        for (idx, (l, r)) in self.ands.iter().enumerate() {
+            writeln!(f, "{} {l} {r}", 2 * (i + 1 + idx))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+//! Reduced ordered binary decision diagrams over numbered boolean variables.
+use std::collections::HashMap;
+
+/// A node of a [Bdd], identified by its index.
+pub(crate) type Node = usize;
+
+/// A collection of reduced ordered binary decision diagrams that share their
+/// nodes. Variables are ordered by their number and equivalent functions are
+/// represented by the same node, so two functions are equivalent exactly
+/// when their nodes are equal.
+pub(crate) struct Bdd {
+    /// The variable, low child, and high child of each node. The constants
+    /// are the first two nodes and have the variable `usize::MAX`.
+    nodes: Vec<(usize, Node, Node)>,
+    /// The node of each (variable, low child, high child) triple.
+    unique: HashMap<(usize, Node, Node), Node>,
+    /// Memoized results of [Bdd::ite].
+    computed: HashMap<(Node, Node, Node), Node>,
+}
+
+impl Default for Bdd {
+    fn default() -> Self {
+        Bdd {
+            nodes: vec![
+                (usize::MAX, Self::FALSE, Self::FALSE),
+                (usize::MAX, Self::TRUE, Self::TRUE),
+            ],
+            unique: HashMap::new(),
+            computed: HashMap::new(),
+        }
+    }
+}
+
+impl Bdd {
+    pub const FALSE: Node = 0;
+    pub const TRUE: Node = 1;
+
+    /// The function that is true exactly when `var` is.
+    pub fn var(&mut self, var: usize) -> Node {
+        self.node(var, Self::FALSE, Self::TRUE)
+    }
+
+    pub fn not(&mut self, f: Node) -> Node {
+        self.ite(f, Self::FALSE, Self::TRUE)
+    }
+
+    pub fn and(&mut self, f: Node, g: Node) -> Node {
+        self.ite(f, g, Self::FALSE)
+    }
+
+    pub fn or(&mut self, f: Node, g: Node) -> Node {
+        self.ite(f, Self::TRUE, g)
+    }
+
+    /// The node testing `var`, sharing existing nodes and skipping the test
+    /// when both children are the same.
+    fn node(&mut self, var: usize, low: Node, high: Node) -> Node {
+        if low == high {
+            return low;
+        }
+        let key = (var, low, high);
+        if let Some(&node) = self.unique.get(&key) {
+            return node;
+        }
+        self.nodes.push(key);
+        let node = self.nodes.len() - 1;
+        self.unique.insert(key, node);
+        node
+    }
+
+    /// The children of `f` when testing `var`, which are `f` itself if `f`
+    /// does not test `var`.
+    fn cofactors(&self, f: Node, var: usize) -> (Node, Node) {
+        match self.nodes[f] {
+            (v, low, high) if v == var => (low, high),
+            _ => (f, f),
+        }
+    }
+
+    /// The function `if f then g else h`.
+    fn ite(&mut self, f: Node, g: Node, h: Node) -> Node {
+        if f == Self::TRUE || g == h {
+            return g;
+        }
+        if f == Self::FALSE {
+            return h;
+        }
+        if g == Self::TRUE && h == Self::FALSE {
+            return f;
+        }
+        if let Some(&node) = self.computed.get(&(f, g, h)) {
+            return node;
+        }
+        let var = self.nodes[f].0.min(self.nodes[g].0).min(self.nodes[h].0);
+        let (f0, f1) = self.cofactors(f, var);
+        let (g0, g1) = self.cofactors(g, var);
+        let (h0, h1) = self.cofactors(h, var);
+        let low = self.ite(f0, g0, h0);
+        let high = self.ite(f1, g1, h1);
+        let node = self.node(var, low, high);
+        self.computed.insert((f, g, h), node);
+        node
+    }
+}
@@ -2,7 +2,7 @@
 //! representation.
 use crate::{self as ir, LibrarySignatures, Nothing, RRC, WRC};
 use calyx_frontend::BoolAttr;
-use calyx_utils::CalyxResult;
+use calyx_utils::{CalyxResult, Error};
 use std::rc::Rc;
 
 use super::{CellType, PortDef};
@@ -233,7 +233,9 @@ impl<'a> Builder<'a> {
         Prim: Into<ir::Id>,
     {
         let prim_id = primitive.into();
-        let prim = &self.lib.get_primitive(prim_id);
+        let Some(prim) = self.lib.find_primitive(prim_id) else {
+            return Err(Error::undefined(prim_id, "primitive"));
+        };
         let (param_binding, ports) = prim.resolve(param_values)?;
 
         let name = self.component.generate_name(prefix);
@@ -286,6 +286,19 @@ impl Component {
         self.continuous_assignments.iter_mut().for_each(f);
     }
 
+    /// The number of comparators needed by the guards of all the assignments
+    /// in the component. See [crate::Guard::count_comparators].
+    pub fn count_guard_comparators(&self) -> usize {
+        let mut count = 0;
+        self.iter_assignments(|assign| {
+            count += assign.guard.count_comparators()
+        });
+        self.iter_static_assignments(|assign| {
+            count += assign.guard.count_comparators()
+        });
+        count
+    }
+
     /// Iterate over all non-static assignments contained within the component.
     pub fn iter_assignments<F>(&self, mut f: F)
     where
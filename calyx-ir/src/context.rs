@@ -3,6 +3,39 @@
 //! Passes usually have transform/analyze the components in the IR.
 use super::{Component, Id};
 use calyx_frontend::LibrarySignatures;
+use std::str::FromStr;
+
+/// The value that the Verilog backend drives onto a non-data port when none
+/// of the guards for the port are active.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuardDefault {
+    /// Drive the port to zero.
+    #[default]
+    Zero,
+    /// Drive the port to `'x`.
+    Undef,
+    /// Drive the port to the given constant, truncated to the width of the
+    /// port.
+    Const(u64),
+}
+
+impl FromStr for GuardDefault {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zero" => Ok(GuardDefault::Zero),
+            "x" => Ok(GuardDefault::Undef),
+            _ => s
+                .strip_prefix("const:")
+                .and_then(|n| n.parse::<u64>().ok())
+                .map(GuardDefault::Const)
+                .ok_or_else(|| {
+                    format!("Unknown guard default: {}. Valid options are `zero`, `x`, or `const:<n>`", s)
+                }),
+        }
+    }
+}
 
 /// Configuration information for the backends.
 #[derive(Default)]
@@ -13,6 +46,11 @@ pub struct BackendConf {
     pub enable_verification: bool,
     /// Use flat (ANF) assignments for guards instead of deep expression trees.
     pub flat_assign: bool,
+    /// Value driven onto non-data ports when none of their guards are active.
+    pub guard_default: GuardDefault,
+    /// Emit guard comparisons as instances of the comparison primitives
+    /// instead of Verilog operators.
+    pub comparison_primitives: bool,
     /// [FIRRTL backend only] Emit extmodule declarations for primtives
     /// for use with SystemVerilog implementations
     pub emit_primitive_extmodules: bool,
@@ -85,6 +85,14 @@ impl GuardPool {
                 let flat_r = self.flatten(r);
                 self.add(FlatGuard::And(flat_l, flat_r))
             }
+            // Flat guards do not have implications, so `l -> r` is stored as
+            // `!l | r`.
+            Guard::Implies(l, r) => {
+                let flat_l = self.flatten(l);
+                let not_l = self.add(FlatGuard::Not(flat_l));
+                let flat_r = self.flatten(r);
+                self.add(FlatGuard::Or(not_l, flat_r))
+            }
             Guard::Not(g) => {
                 let flat_g = self.flatten(g);
                 self.add(FlatGuard::Not(flat_g))
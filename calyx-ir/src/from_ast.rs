@@ -1,6 +1,6 @@
 use super::{
-    Assignment, Attributes, BackendConf, Builder, Cell, CellType, Component,
-    Context, Control, Direction, GetAttributes, Guard, Id, Invoke,
+    Assignment, Attributes, BackendConf, Builder, Cell, CellType, CombGroup,
+    Component, Context, Control, Direction, GetAttributes, Guard, Id, Invoke,
     LibrarySignatures, Port, PortDef, StaticControl, StaticInvoke,
     RESERVED_NAMES, RRC,
 };
@@ -344,9 +344,11 @@ fn build_component(
         .into_iter()
         .try_for_each(|g| add_static_group(g, &mut builder))?;
 
+    // Guards such as `approx_eq` may have already added continuous
+    // assignments to the component.
     let continuous_assignments =
         build_assignments(comp.continuous_assignments, &mut builder)?;
-    builder.component.continuous_assignments = continuous_assignments;
+    builder.add_continuous_assignments(continuous_assignments);
 
     // Build the Control ast using ast::Control.
     let control =
@@ -488,7 +490,7 @@ fn atom_to_port(
     builder: &mut Builder,
 ) -> CalyxResult<RRC<Port>> {
     match atom {
-        ast::Atom::Num(n) => {
+        ast::Atom::Num(n) | ast::Atom::Unsized(n) => {
             let port = builder.add_constant(n.val, n.width).borrow().get("out");
             Ok(Rc::clone(&port))
         }
@@ -496,6 +498,64 @@ fn atom_to_port(
     }
 }
 
+/// The port of a constant for a literal without a width, which takes the
+/// given width. Returns an error if the literal does not fit in it.
+fn unsized_to_port(
+    num: ast::BitNum,
+    width: u64,
+    builder: &mut Builder,
+) -> CalyxResult<RRC<Port>> {
+    if num.width > width {
+        let lit = match num.num_type {
+            ast::NumType::Binary => format!("0b{:b}", num.val),
+            ast::NumType::Hex => format!("0x{:x}", num.val),
+            ast::NumType::Decimal | ast::NumType::Octal => {
+                unreachable!("unsized literals are binary or hexadecimal")
+            }
+        };
+        return Err(Error::malformed_structure(format!(
+            "Literal `{lit}' needs {} bits but is compared against a {width}-bit port",
+            num.width
+        ))
+        .with_pos(&num.span));
+    }
+    Ok(builder.add_constant(num.val, width).borrow().get("out"))
+}
+
+/// Get the ports of the operands of a comparison. A literal without a width
+/// takes the width of the other operand, and two such literals take the
+/// larger of their widths.
+fn comparison_operands(
+    l: ast::Atom,
+    r: ast::Atom,
+    builder: &mut Builder,
+) -> CalyxResult<(RRC<Port>, RRC<Port>)> {
+    let (nl, nr) = match (l, r) {
+        (ast::Atom::Unsized(l), ast::Atom::Unsized(r)) => {
+            let width = l.width.max(r.width);
+            (
+                unsized_to_port(l, width, builder)?,
+                unsized_to_port(r, width, builder)?,
+            )
+        }
+        (ast::Atom::Unsized(l), r) => {
+            let nr = atom_to_port(r, builder)?;
+            let width = nr.borrow().width;
+            (unsized_to_port(l, width, builder)?, nr)
+        }
+        (l, ast::Atom::Unsized(r)) => {
+            let nl = atom_to_port(l, builder)?;
+            let width = nl.borrow().width;
+            (nl, unsized_to_port(r, width, builder)?)
+        }
+        (l, r) => (atom_to_port(l, builder)?, atom_to_port(r, builder)?),
+    };
+    Ok((
+        ensure_direction(nl, Direction::Output)?,
+        ensure_direction(nr, Direction::Output)?,
+    ))
+}
+
 /// Ensures that the given port has the required direction.
 fn ensure_direction(pr: RRC<Port>, dir: Direction) -> CalyxResult<RRC<Port>> {
     let port_dir = pr.borrow().direction.clone();
@@ -625,10 +685,12 @@ fn build_guard(
         )?),
         GE::Or(l, r) => Guard::or(build_guard(*l, bd)?, build_guard(*r, bd)?),
         GE::And(l, r) => Guard::and(build_guard(*l, bd)?, build_guard(*r, bd)?),
+        GE::Implies(l, r) => {
+            build_guard(*l, bd)?.implies_guard(build_guard(*r, bd)?)
+        }
         GE::Not(g) => Guard::Not(into_box_guard(g, bd)?),
         GE::CompOp((op, l, r)) => {
-            let nl = ensure_direction(atom_to_port(l, bd)?, Direction::Output)?;
-            let nr = ensure_direction(atom_to_port(r, bd)?, Direction::Output)?;
+            let (nl, nr) = comparison_operands(l, r, bd)?;
             let nop = match op {
                 ast::GuardComp::Eq => PortComp::Eq,
                 ast::GuardComp::Neq => PortComp::Neq,
@@ -639,6 +701,83 @@ fn build_guard(
             };
             Guard::CompOp(nop, nl, nr)
         }
+        GE::ApproxEq(l, r, tolerance) => build_approx_eq(l, r, tolerance, bd)?,
+    })
+}
+
+/// Build `approx_eq(l, r, tolerance)` from subtractions in both directions,
+/// and compare the one that does not wrap around against the tolerance:
+/// ```
+/// diff.left = l; diff.right = r;
+/// diff0.left = r; diff0.right = l;
+/// (l >= r & diff.out <= tolerance) | (l < r & diff0.out <= tolerance)
+/// ```
+/// Occurrences of `approx_eq` in the same component with the same operands
+/// share their subtractors.
+fn build_approx_eq(
+    l: ast::Atom,
+    r: ast::Atom,
+    tolerance: u64,
+    bd: &mut Builder,
+) -> CalyxResult<Guard<Nothing>> {
+    let (nl, nr) = comparison_operands(l, r, bd)?;
+    let width = nl.borrow().width;
+    if (64 - tolerance.leading_zeros()) as u64 > width {
+        return Err(Error::malformed_structure(format!(
+            "Tolerance {tolerance} of `approx_eq' does not fit in {width} bits"
+        )));
+    }
+    let tol = bd.add_constant(tolerance, width).borrow().get("out");
+    let mut diff = |a: &RRC<Port>, b: &RRC<Port>| -> CalyxResult<_> {
+        if let Some(out) = find_diff(bd.component, a, b) {
+            return Ok(out);
+        }
+        let sub = bd.try_add_primitive("diff", "std_sub", &[width]).map_err(
+            |_| {
+                Error::malformed_structure(
+                    "`approx_eq' requires the `std_sub' primitive, which is defined in `primitives/core.futil'",
+                )
+            },
+        )?;
+        sub.borrow_mut().add_attribute(BoolAttr::Generated, 1);
+        let sub = sub.borrow();
+        let assigns = vec![
+            bd.build_assignment(sub.get("left"), Rc::clone(a), Guard::True),
+            bd.build_assignment(sub.get("right"), Rc::clone(b), Guard::True),
+        ];
+        bd.add_continuous_assignments(assigns);
+        Ok(sub.get("out"))
+    };
+    let (lr, rl) = (diff(&nl, &nr)?, diff(&nr, &nl)?);
+    let within = |d| Guard::CompOp(PortComp::Leq, d, Rc::clone(&tol));
+    let l_geq = Guard::CompOp(PortComp::Geq, Rc::clone(&nl), Rc::clone(&nr));
+    let l_lt = Guard::CompOp(PortComp::Lt, nl, nr);
+    Ok(Guard::or(
+        Guard::and(l_geq, within(lr)),
+        Guard::and(l_lt, within(rl)),
+    ))
+}
+
+/// The output of a subtractor generated by [build_approx_eq] that computes
+/// `a - b`, if there is one.
+fn find_diff(
+    comp: &Component,
+    a: &RRC<Port>,
+    b: &RRC<Port>,
+) -> Option<RRC<Port>> {
+    let driven_by = |port: RRC<Port>, src: &RRC<Port>| {
+        comp.continuous_assignments.iter().any(|asgn| {
+            Rc::ptr_eq(&asgn.dst, &port) && Rc::ptr_eq(&asgn.src, src)
+        })
+    };
+    comp.cells.iter().find_map(|cell| {
+        let cell = cell.borrow();
+        let generated = cell.is_primitive(Some("std_sub"))
+            && cell.attributes.has(BoolAttr::Generated);
+        (generated
+            && driven_by(cell.get("left"), a)
+            && driven_by(cell.get("right"), b))
+        .then(|| cell.get("out"))
     })
 }
 
@@ -665,10 +804,11 @@ fn build_static_guard(
         SGE::And(l, r) => {
             Guard::and(build_static_guard(*l, bd)?, build_static_guard(*r, bd)?)
         }
+        SGE::Implies(l, r) => build_static_guard(*l, bd)?
+            .implies_guard(build_static_guard(*r, bd)?),
         SGE::Not(g) => Guard::Not(into_box_guard(g, bd)?),
         SGE::CompOp((op, l, r)) => {
-            let nl = ensure_direction(atom_to_port(l, bd)?, Direction::Output)?;
-            let nr = ensure_direction(atom_to_port(r, bd)?, Direction::Output)?;
+            let (nl, nr) = comparison_operands(l, r, bd)?;
             let nop = match op {
                 ast::GuardComp::Eq => PortComp::Eq,
                 ast::GuardComp::Neq => PortComp::Neq,
@@ -949,6 +1089,7 @@ fn build_static_control(
         }
         ast::Control::Par { .. }
         | ast::Control::If { .. }
+        | ast::Control::IfGuard { .. }
         | ast::Control::While { .. }
         | ast::Control::Seq { .. }
         | ast::Control::Repeat { .. }
@@ -967,6 +1108,29 @@ fn build_static_control(
     Ok(sc)
 }
 
+/// Build a combinational group that computes the condition of an `if`
+/// into a 1-bit wire, and return the output of the wire and the group:
+/// ```
+/// comb group cond { cond_wire.in = x.out < limit.out ? 1'd1; }
+/// ```
+fn build_if_guard(
+    guard: ast::GuardExpr,
+    builder: &mut Builder,
+) -> CalyxResult<(RRC<Port>, RRC<CombGroup>)> {
+    let guard = build_guard(guard, builder)?;
+    let group = builder.add_comb_group("cond");
+    let wire = builder.add_primitive("cond_wire", "std_wire", &[1]);
+    let one = builder.add_constant(1, 1);
+    let assign = builder.build_assignment(
+        wire.borrow().get("in"),
+        one.borrow().get("out"),
+        guard,
+    );
+    group.borrow_mut().assignments.push(assign);
+    let out = wire.borrow().get("out");
+    Ok((out, group))
+}
+
 /// Transform ast::Control to ir::Control.
 fn build_control(
     control: ast::Control,
@@ -1273,6 +1437,22 @@ fn build_control(
             *con.get_mut_attributes() = attributes;
             con
         }
+        ast::Control::IfGuard {
+            guard,
+            tbranch,
+            fbranch,
+            attributes,
+        } => {
+            let (port, group) = build_if_guard(guard, builder)?;
+            let mut con = Control::if_(
+                port,
+                Some(group),
+                Box::new(build_control(*tbranch, sig_ctx, builder)?),
+                Box::new(build_control(*fbranch, sig_ctx, builder)?),
+            );
+            *con.get_mut_attributes() = attributes;
+            con
+        }
         ast::Control::While {
             port,
             cond: maybe_cond,
@@ -1,10 +1,16 @@
+#[cfg(feature = "bdd")]
+use crate::bdd::{Bdd, Node as BddNode};
 use crate::Printer;
 
-use super::{NumAttr, Port, RRC};
-use calyx_utils::Error;
+use super::{
+    Aig, Builder, Canonical, Cell, Component, Id, NumAttr, Port, PortParent,
+    RRC,
+};
+use calyx_utils::{CalyxResult, Error, UnionFind};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 use std::mem;
-use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+use std::ops::{AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
 use std::{cmp::Ordering, hash::Hash, rc::Rc};
 
 #[derive(Debug, Clone, Default, Eq, PartialEq)]
@@ -18,7 +24,7 @@ impl ToString for Nothing {
 }
 
 /// Comparison operations that can be performed between ports by [Guard::CompOp].
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
 pub enum PortComp {
     /// p1 == p2
@@ -35,6 +41,54 @@ pub enum PortComp {
     Leq,
 }
 
+/// Number of times each operator occurs in a guard. Computed by
+/// [Guard::count_ops]. Since `And` and `Or` are binary, each node counts as one
+/// use of the operator, i.e., `a & b & c` uses `And` twice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GuardOpCounts {
+    pub and: usize,
+    pub or: usize,
+    pub not: usize,
+    pub eq: usize,
+    pub neq: usize,
+    pub gt: usize,
+    pub lt: usize,
+    pub geq: usize,
+    pub leq: usize,
+}
+
+/// Sum the counts of two guards, e.g., to profile all guards in a component.
+impl AddAssign for GuardOpCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.and += other.and;
+        self.or += other.or;
+        self.not += other.not;
+        self.eq += other.eq;
+        self.neq += other.neq;
+        self.gt += other.gt;
+        self.lt += other.lt;
+        self.geq += other.geq;
+        self.leq += other.leq;
+    }
+}
+
+impl GuardOpCounts {
+    /// The number of comparisons, each of which needs a comparator.
+    pub fn comparisons(&self) -> usize {
+        self.eq + self.neq + self.gt + self.lt + self.geq + self.leq
+    }
+}
+
+/// The widths of a guard computed by [Guard::infer_widths].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardWidths {
+    /// The width of the value of the guard, which is always 1.
+    pub result: u64,
+    /// The width of the operands of each comparison, in the order the
+    /// comparisons appear in the guard.
+    pub comparisons: Vec<u64>,
+}
+
 /// An assignment guard which has pointers to the various ports from which it reads.
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
@@ -43,6 +97,8 @@ pub enum Guard<T> {
     Or(Box<Guard<T>>, Box<Guard<T>>),
     /// Represents `c1 && c2`.
     And(Box<Guard<T>>, Box<Guard<T>>),
+    /// Represents `c1 -> c2`, which has the same value as `!c1 || c2`.
+    Implies(Box<Guard<T>>, Box<Guard<T>>),
     /// Represents `!c1`
     Not(Box<Guard<T>>),
     #[default]
@@ -89,13 +145,49 @@ impl StaticTiming {
     }
 }
 
+/// A user-defined rewrite rule for [Guard::rewrite_with]. The ports of the
+/// pattern are metavariables: each port matches any leaf of a guard, that
+/// is a port, comparison, `true` or info, and every occurrence of the same
+/// port must match the same leaf. In the replacement, ports bound by the
+/// pattern are replaced with the leaves they matched, and other ports are
+/// kept as they are.
+#[derive(Debug, Clone)]
+pub struct GuardRule<T> {
+    pub pattern: Guard<T>,
+    pub replacement: Guard<T>,
+}
+
+/// A rule applied by [Guard::simplify_reporting].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimplifyRule {
+    /// `true & g == g`, `false & g == false`, and their duals for `|`.
+    ConstantFold,
+    /// `a & (b & c) == (a & b) & c`, and its dual for `|`.
+    Flatten,
+    /// `a & a == a` and `a | a == a`, including repeated operands of a
+    /// chain like `a & b & a`.
+    Idempotence,
+    /// `a | (a & b) == a` and `a & (a | b) == a`.
+    Absorption,
+    /// `!!g == g`.
+    DoubleNegation,
+}
+
+/// A rewrite applied by [Guard::simplify_reporting] and the subtree it
+/// rewrote.
+#[derive(Debug, Clone)]
+pub struct SimplifyStep<T> {
+    pub rule: SimplifyRule,
+    pub subtree: Guard<T>,
+}
+
 impl<T> Hash for Guard<T>
 where
     T: ToString,
 {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         match self {
-            Guard::Or(l, r) | Guard::And(l, r) => {
+            Guard::Or(l, r) | Guard::And(l, r) | Guard::Implies(l, r) => {
                 l.hash(state);
                 r.hash(state)
             }
@@ -130,6 +222,11 @@ impl From<Guard<Nothing>> for Guard<StaticTiming> {
                 let r = Self::from(*right);
                 Guard::And(Box::new(l), Box::new(r))
             }
+            Guard::Implies(left, right) => {
+                let l = Self::from(*left);
+                let r = Self::from(*right);
+                Guard::Implies(Box::new(l), Box::new(r))
+            }
             Guard::Not(c) => {
                 let inside = Self::from(*c);
                 Guard::Not(Box::new(inside))
@@ -179,6 +276,40 @@ impl<T> Guard<T> {
         false
     }
 
+    /// Returns the operands if the guard is an `&`.
+    pub fn as_and(&self) -> Option<(&Guard<T>, &Guard<T>)> {
+        match self {
+            Guard::And(l, r) => Some((l, r)),
+            _ => None,
+        }
+    }
+
+    /// Returns the operands if the guard is an `|`.
+    pub fn as_or(&self) -> Option<(&Guard<T>, &Guard<T>)> {
+        match self {
+            Guard::Or(l, r) => Some((l, r)),
+            _ => None,
+        }
+    }
+
+    /// Returns the operator and the compared ports if the guard is a
+    /// comparison.
+    pub fn as_cmp(&self) -> Option<(&PortComp, &RRC<Port>, &RRC<Port>)> {
+        match self {
+            Guard::CompOp(op, l, r) => Some((op, l, r)),
+            _ => None,
+        }
+    }
+
+    /// Returns the port if the guard reads a single port, looking through
+    /// `&` nodes with a `true` operand such as `p & true`.
+    pub fn as_single_port(&self) -> Option<RRC<Port>> {
+        match self.strip_true() {
+            Guard::Port(p) => Some(Rc::clone(p)),
+            _ => None,
+        }
+    }
+
     /// Update the guard in place. Replaces this guard with `upd(self)`.
     /// Uses `std::mem::take` for the in-place update.
     #[inline(always)]
@@ -196,6 +327,7 @@ impl<T> Guard<T> {
         match self {
             Guard::And(..) => "&".to_string(),
             Guard::Or(..) => "|".to_string(),
+            Guard::Implies(..) => "->".to_string(),
             Guard::CompOp(op, _, _) => match op {
                 PortComp::Eq => "==".to_string(),
                 PortComp::Neq => "!=".to_string(),
@@ -257,6 +389,98 @@ impl<T> Guard<T> {
         }
     }
 
+    /// The implication `self -> consequent`. It has the same value as
+    /// `!self | consequent`, but is kept as a [Guard::Implies] so that it
+    /// is printed and negated as an implication.
+    pub fn implies_guard(self, consequent: Guard<T>) -> Self {
+        Guard::Implies(Box::new(self), Box::new(consequent))
+    }
+
+    /// If this guard is an implication `a -> b`, return `!a | b`, the guard
+    /// it stands for. Other guards are returned unchanged.
+    pub fn expand_implies(self) -> Self {
+        match self {
+            Guard::Implies(l, r) => Guard::Or(Box::new(!*l), r),
+            g => g,
+        }
+    }
+
+    /// Guard that is true when all of the ports are high. Returns `true` if
+    /// there are no ports.
+    pub fn all_high<I>(ports: I) -> Self
+    where
+        I: IntoIterator<Item = RRC<Port>>,
+        T: Eq,
+    {
+        ports
+            .into_iter()
+            .map(Guard::port)
+            .fold(Guard::True, Guard::and)
+    }
+
+    /// Guard that is true when any of the ports is high. Returns `false` if
+    /// there are no ports.
+    pub fn any_high<I>(ports: I) -> Self
+    where
+        I: IntoIterator<Item = RRC<Port>>,
+        T: Eq,
+    {
+        ports
+            .into_iter()
+            .map(Guard::port)
+            .reduce(Guard::or)
+            .unwrap_or_else(|| !Guard::True)
+    }
+
+    /// The guards under which each assignment in a priority order takes
+    /// effect: the `i`th guard holds when `ordered[i]` does and none of the
+    /// earlier guards do, `ordered[i] & !ordered[0] & ... & !ordered[i-1]`.
+    /// The resulting guards are pairwise disjoint. A guard that follows a
+    /// `true` guard can never take effect and becomes `false`.
+    pub fn priority_guards(ordered: &[Guard<T>]) -> Vec<Guard<T>>
+    where
+        T: Clone + Eq,
+    {
+        ordered
+            .iter()
+            .enumerate()
+            .map(|(idx, guard)| {
+                let higher = &ordered[..idx];
+                if guard.is_false() || higher.iter().any(Guard::is_true) {
+                    return !Guard::True;
+                }
+                higher
+                    .iter()
+                    .filter(|g| !g.is_false())
+                    .fold(guard.clone(), |acc, g| acc.and(!g.clone()))
+            })
+            .collect()
+    }
+
+    /// Conjunction of the guards that are present. Returns `true` if none of
+    /// the guards are present.
+    pub fn and_all<I>(guards: I) -> Self
+    where
+        I: IntoIterator<Item = Option<Guard<T>>>,
+        T: Eq,
+    {
+        guards.into_iter().flatten().fold(Guard::True, Guard::and)
+    }
+
+    /// Disjunction of the guards that are present. Returns `false` if none of
+    /// the guards are present.
+    pub fn or_all<I>(guards: I) -> Self
+    where
+        I: IntoIterator<Item = Option<Guard<T>>>,
+        T: Eq,
+    {
+        guards
+            .into_iter()
+            .flatten()
+            .reduce(Guard::or)
+            .unwrap_or_else(|| !Guard::True)
+    }
+
     pub fn eq(self, other: Guard<T>) -> Self
     where
         T: Debug + Eq + ToString,
@@ -367,169 +591,2124 @@ impl<T> Guard<T> {
 
     /// Returns all the ports used by this guard.
     pub fn all_ports(&self) -> Vec<RRC<Port>> {
+        let mut ports = vec![];
+        self.collect_ports_into(&mut ports);
+        ports
+    }
+
+    /// Returns the ports used by this guard, each once, sorted by their
+    /// [canonical](Port::canonical) names. Unlike [Guard::all_ports], the
+    /// order does not depend on how the guard was built.
+    pub fn all_ports_sorted(&self) -> Vec<RRC<Port>> {
+        let mut ports = self.all_ports();
+        ports.sort_by_cached_key(|p| p.borrow().canonical());
+        ports.dedup_by(|l, r| l.borrow().canonical() == r.borrow().canonical());
+        ports
+    }
+
+    /// Appends all the ports used by this guard to `buf`, in the same order
+    /// as [Guard::all_ports]. Lets callers reuse one buffer across guards.
+    pub fn collect_ports_into(&self, buf: &mut Vec<RRC<Port>>) {
         match self {
-            Guard::Port(a) => vec![Rc::clone(a)],
-            Guard::And(l, r) | Guard::Or(l, r) => {
-                let mut atoms = l.all_ports();
-                atoms.append(&mut r.all_ports());
-                atoms
+            Guard::Port(a) => buf.push(Rc::clone(a)),
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                l.collect_ports_into(buf);
+                r.collect_ports_into(buf);
             }
             Guard::CompOp(_, l, r) => {
-                vec![Rc::clone(l), Rc::clone(r)]
+                buf.push(Rc::clone(l));
+                buf.push(Rc::clone(r));
             }
-            Guard::Not(g) => g.all_ports(),
-            Guard::True => vec![],
-            Guard::Info(_) => vec![],
+            Guard::Not(g) => g.collect_ports_into(buf),
+            Guard::True | Guard::Info(_) => {}
         }
     }
-}
 
-/// Helper functions for the guard.
-impl<T> Guard<T> {
-    /// Mutates a guard by calling `f` on every leaf in the
-    /// guard tree and replacing the leaf with the guard that `f`
-    /// returns.
-    pub fn for_each<F>(&mut self, f: &mut F)
-    where
-        F: FnMut(RRC<Port>) -> Option<Guard<T>>,
-    {
-        match self {
-            Guard::And(l, r) | Guard::Or(l, r) => {
-                l.for_each(f);
-                r.for_each(f);
+    /// Returns the cells whose ports are used by this guard, each once, in
+    /// the order they are first used. Ports of group holes and of the
+    /// component's own interface are not included because the signature of
+    /// a component is not a cell of the component; use
+    /// [Guard::references_interface] to check for those.
+    pub fn cells(&self) -> Vec<RRC<Cell>> {
+        let mut cells: Vec<RRC<Cell>> = vec![];
+        for port in self.all_ports() {
+            let PortParent::Cell(cell) = &port.borrow().parent else {
+                continue;
+            };
+            let cell = cell.upgrade();
+            if cell.borrow().is_this()
+                || cells.iter().any(|c| Rc::ptr_eq(c, &cell))
+            {
+                continue;
             }
-            Guard::Not(inner) => {
-                inner.for_each(f);
+            cells.push(cell);
+        }
+        cells
+    }
+
+    /// Returns true if this guard reads a port of the interface of `comp`.
+    pub fn references_interface(&self, comp: &Component) -> bool {
+        self.all_ports().iter().any(|port| match &port.borrow().parent {
+            PortParent::Cell(cell) => {
+                Rc::ptr_eq(&cell.upgrade(), &comp.signature)
             }
-            Guard::CompOp(_, l, r) => {
-                match f(Rc::clone(l)) {
-                    Some(Guard::Port(p)) => *l = p,
-                    Some(_) => unreachable!(
-                        "Cannot replace port inside comparison operator"
-                    ),
-                    None => {}
-                }
-                match f(Rc::clone(r)) {
-                    Some(Guard::Port(p)) => *r = p,
-                    Some(_) => unreachable!(
-                        "Cannot replace port inside comparison operator"
-                    ),
-                    None => {}
-                }
+            PortParent::Group(_) | PortParent::StaticGroup(_) => false,
+        })
+    }
+
+    /// Remove the conditions that mention `port` from the guard, treating
+    /// them as always satisfied. Conjuncts of the top-level `&` chain that
+    /// read `port` directly, compare against it, or negate either are
+    /// dropped, and a guard that is such a condition itself becomes `true`.
+    /// Any other conjunct that mentions `port`, such as `port | x`, is kept
+    /// because the condition cannot be removed soundly from it, and a
+    /// warning is logged. Returns `true` if the guard no longer mentions
+    /// `port`.
+    pub fn remove_port_conjuncts(&mut self, port: &RRC<Port>) -> bool {
+        let mut conjuncts = vec![];
+        mem::take(self).into_conjuncts(&mut conjuncts);
+        let mut removed_all = true;
+        conjuncts.retain(|conj| {
+            if !conj.all_ports().iter().any(|p| Rc::ptr_eq(p, port)) {
+                return true;
             }
-            Guard::Port(port) => {
-                let guard = f(Rc::clone(port))
-                    .unwrap_or_else(|| Guard::port(Rc::clone(port)));
-                *self = guard;
+            let leaf = match conj {
+                Guard::Not(g) => &**g,
+                g => g,
+            };
+            if matches!(leaf, Guard::Port(_) | Guard::CompOp(..)) {
+                return false;
             }
-            Guard::True => {}
-            Guard::Info(_) =>
-                // Info shouldn't count as port
-                {}
+            log::warn!(
+                "Cannot soundly remove `{}' from inside an `|' in a guard",
+                port.borrow().canonical()
+            );
+            removed_all = false;
+            true
+        });
+        *self = conjuncts
+            .into_iter()
+            .reduce(|l, r| Guard::And(Box::new(l), Box::new(r)))
+            .unwrap_or(Guard::True);
+        removed_all
+    }
+
+    /// Move the operands of the chain of `&` nodes rooted at this guard
+    /// into `out`.
+    fn into_conjuncts(self, out: &mut Vec<Guard<T>>) {
+        match self {
+            Guard::And(l, r) => {
+                l.into_conjuncts(out);
+                r.into_conjuncts(out);
+            }
+            g => out.push(g),
         }
     }
 
-    /// runs f(info) on each Guard::Info in `guard`.
-    /// if `f(info)` = Some(result)` replaces interval with result.
-    /// if `f(info)` = None` does nothing.
-    pub fn for_each_info<F>(&mut self, f: &mut F)
-    where
-        F: FnMut(&mut T) -> Option<Guard<T>>,
-    {
+    /// The number of nodes in this guard. Comparisons count as a single node.
+    pub fn size(&self) -> usize {
         match self {
-            Guard::And(l, r) | Guard::Or(l, r) => {
-                l.for_each_info(f);
-                r.for_each_info(f);
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                1 + l.size() + r.size()
             }
-            Guard::Not(inner) => {
-                inner.for_each_info(f);
+            Guard::Not(g) => 1 + g.size(),
+            Guard::CompOp(..)
+            | Guard::Port(_)
+            | Guard::True
+            | Guard::Info(_) => 1,
+        }
+    }
+
+    /// The number of `&`, `|`, `->` and `!` operators on the longest path
+    /// from the root of this guard to a leaf. Comparisons count as leaves.
+    pub fn depth(&self) -> usize {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                1 + l.depth().max(r.depth())
             }
-            Guard::True | Guard::Port(_) | Guard::CompOp(_, _, _) => {}
-            Guard::Info(timing_interval) => {
-                if let Some(new_interval) = f(timing_interval) {
-                    *self = new_interval
-                }
+            Guard::Not(g) => 1 + g.depth(),
+            Guard::CompOp(..)
+            | Guard::Port(_)
+            | Guard::True
+            | Guard::Info(_) => 0,
+        }
+    }
+
+    /// The number of `&`, `|`, `->`, `!` and comparison nodes on the longest
+    /// path from a read of `port` to the root of this guard, or `None` if the
+    /// guard does not read the port.
+    pub fn depth_of_port(&self, port: &Canonical) -> Option<usize> {
+        let reads = |p: &RRC<Port>| p.borrow().canonical() == *port;
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => l
+                .depth_of_port(port)
+                .max(r.depth_of_port(port))
+                .map(|d| d + 1),
+            Guard::Not(g) => g.depth_of_port(port).map(|d| d + 1),
+            Guard::CompOp(_, l, r) => (reads(l) || reads(r)).then_some(1),
+            Guard::Port(p) => reads(p).then_some(0),
+            Guard::True | Guard::Info(_) => None,
+        }
+    }
+
+    /// The number of leaves in this guard: ports, constants, comparisons and
+    /// info nodes.
+    pub fn num_leaves(&self) -> usize {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                l.num_leaves() + r.num_leaves()
             }
+            Guard::Not(g) => g.num_leaves(),
+            Guard::CompOp(..)
+            | Guard::Port(_)
+            | Guard::True
+            | Guard::Info(_) => 1,
         }
     }
 
-    /// runs f(info) on each info in `guard`.
-    /// f should return Result<(), Error>, meaning that it essentially does
-    /// nothing if the `f` returns OK(()), but returns an appropraite error otherwise
-    pub fn check_for_each_info<F>(&self, f: &mut F) -> Result<(), Error>
+    /// The number of `&`, `|`, `->` and `!` operators in this guard.
+    /// Together with [Guard::num_leaves] this adds up to [Guard::size].
+    pub fn num_internal(&self) -> usize {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                1 + l.num_internal() + r.num_internal()
+            }
+            Guard::Not(g) => 1 + g.num_internal(),
+            Guard::CompOp(..)
+            | Guard::Port(_)
+            | Guard::True
+            | Guard::Info(_) => 0,
+        }
+    }
+
+    /// Rebuild every chain of at least `threshold` operands joined by the
+    /// same operator, such as `a & b & c & d`, as a balanced tree of that
+    /// operator, such as `(a & b) & (c & d)`. The result has the same value
+    /// and is at most as deep. Operands keep their order, and shorter chains
+    /// keep their shape.
+    pub fn balance(&self, threshold: usize) -> Guard<T>
     where
-        F: Fn(&T) -> Result<(), Error>,
+        T: Clone,
     {
+        let is_and = match self {
+            Guard::And(..) => true,
+            Guard::Or(..) => false,
+            Guard::Not(g) => return Guard::Not(Box::new(g.balance(threshold))),
+            Guard::Implies(l, r) => {
+                return Guard::Implies(
+                    Box::new(l.balance(threshold)),
+                    Box::new(r.balance(threshold)),
+                );
+            }
+            g => return g.clone(),
+        };
+        let mut operands = vec![];
+        self.flatten_chain(is_and, &mut operands);
+        if operands.len() >= threshold {
+            let operands =
+                operands.into_iter().map(|g| g.balance(threshold)).collect();
+            return Self::balanced(operands, is_and);
+        }
         match self {
-            Guard::And(l, r) | Guard::Or(l, r) => {
-                let l_result = l.check_for_each_info(f);
-                if l_result.is_err() {
-                    l_result
-                } else {
-                    r.check_for_each_info(f)
-                }
+            Guard::And(l, r) => Guard::And(
+                Box::new(l.balance(threshold)),
+                Box::new(r.balance(threshold)),
+            ),
+            Guard::Or(l, r) => Guard::Or(
+                Box::new(l.balance(threshold)),
+                Box::new(r.balance(threshold)),
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    /// A balanced tree joining the non-empty `operands` with `&` (or `|`
+    /// when `is_and` is false).
+    fn balanced(mut operands: Vec<Guard<T>>, is_and: bool) -> Guard<T> {
+        if operands.len() == 1 {
+            return operands.pop().unwrap();
+        }
+        let right = operands.split_off(operands.len() / 2);
+        let l = Box::new(Self::balanced(operands, is_and));
+        let r = Box::new(Self::balanced(right, is_and));
+        if is_and {
+            Guard::And(l, r)
+        } else {
+            Guard::Or(l, r)
+        }
+    }
+
+    /// Count the number of times each operator is used in this guard.
+    pub fn count_ops(&self) -> GuardOpCounts {
+        let mut counts = GuardOpCounts::default();
+        self.count_ops_into(&mut counts);
+        counts
+    }
+
+    /// The number of comparisons in this guard, each of which needs a
+    /// comparator when the guard is synthesized. Boolean operators are not
+    /// counted.
+    pub fn count_comparators(&self) -> usize {
+        self.count_ops().comparisons()
+    }
+
+    /// Returns true if this guard contains a comparison. Stops at the first
+    /// comparison found.
+    pub fn contains_comparison(&self) -> bool {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                l.contains_comparison() || r.contains_comparison()
             }
-            Guard::Not(inner) => inner.check_for_each_info(f),
-            Guard::True | Guard::Port(_) | Guard::CompOp(_, _, _) => Ok(()),
-            Guard::Info(timing_interval) => f(timing_interval),
+            Guard::Not(g) => g.contains_comparison(),
+            Guard::CompOp(..) => true,
+            Guard::True | Guard::Port(_) | Guard::Info(_) => false,
         }
     }
-}
 
-impl Guard<StaticTiming> {
-    /// updates self -> self & interval
-    pub fn add_interval(&mut self, timing_interval: StaticTiming) {
-        self.update(|g| g.and(Guard::Info(timing_interval)));
+    fn count_ops_into(&self, counts: &mut GuardOpCounts) {
+        match self {
+            Guard::And(l, r) => {
+                counts.and += 1;
+                l.count_ops_into(counts);
+                r.count_ops_into(counts);
+            }
+            Guard::Or(l, r) => {
+                counts.or += 1;
+                l.count_ops_into(counts);
+                r.count_ops_into(counts);
+            }
+            // Synthesized as `!l | r`.
+            Guard::Implies(l, r) => {
+                counts.or += 1;
+                counts.not += 1;
+                l.count_ops_into(counts);
+                r.count_ops_into(counts);
+            }
+            Guard::Not(g) => {
+                counts.not += 1;
+                g.count_ops_into(counts);
+            }
+            Guard::CompOp(op, _, _) => match op {
+                PortComp::Eq => counts.eq += 1,
+                PortComp::Neq => counts.neq += 1,
+                PortComp::Gt => counts.gt += 1,
+                PortComp::Lt => counts.lt += 1,
+                PortComp::Geq => counts.geq += 1,
+                PortComp::Leq => counts.leq += 1,
+            },
+            Guard::True | Guard::Port(_) | Guard::Info(_) => {}
+        }
     }
-}
 
-/// Construct guards from ports
-impl<T> From<RRC<Port>> for Guard<T> {
-    fn from(port: RRC<Port>) -> Self {
-        Guard::Port(Rc::clone(&port))
+    /// Convert the guard into negation normal form by pushing negations to
+    /// the leaves with De Morgan's laws. Negated comparisons are replaced
+    /// with the complementary comparison, so in the result `!` is only
+    /// applied to ports, `true`, and [Guard::Info] nodes.
+    pub fn to_nnf(self) -> Self {
+        match self {
+            Guard::Not(g) => match *g {
+                Guard::And(l, r) => Guard::Or(
+                    Box::new((!*l).to_nnf()),
+                    Box::new((!*r).to_nnf()),
+                ),
+                Guard::Or(l, r) => Guard::And(
+                    Box::new((!*l).to_nnf()),
+                    Box::new((!*r).to_nnf()),
+                ),
+                Guard::Not(g) => g.to_nnf(),
+                g @ (Guard::CompOp(..) | Guard::Implies(..)) => (!g).to_nnf(),
+                g => Guard::Not(Box::new(g)),
+            },
+            g @ Guard::Implies(..) => g.expand_implies().to_nnf(),
+            Guard::And(l, r) => {
+                Guard::And(Box::new(l.to_nnf()), Box::new(r.to_nnf()))
+            }
+            Guard::Or(l, r) => {
+                Guard::Or(Box::new(l.to_nnf()), Box::new(r.to_nnf()))
+            }
+            g => g,
+        }
     }
-}
 
-impl<T> PartialEq for Guard<T>
-where
-    T: Eq,
-{
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Guard::Or(la, ra), Guard::Or(lb, rb))
-            | (Guard::And(la, ra), Guard::And(lb, rb)) => la == lb && ra == rb,
-            (Guard::CompOp(opa, la, ra), Guard::CompOp(opb, lb, rb)) => {
-                (opa == opb)
-                    && (la.borrow().get_parent_name(), &la.borrow().name)
-                        == (lb.borrow().get_parent_name(), &lb.borrow().name)
-                    && (ra.borrow().get_parent_name(), &ra.borrow().name)
-                        == (rb.borrow().get_parent_name(), &rb.borrow().name)
+    /// A canonical form of the guard, so that guards which only differ in
+    /// the order or nesting of `&` and `|` operands are equal. Chains of
+    /// `&` and `|` are flattened, their operands are sorted by their printed
+    /// form and deduplicated, and the chain is rebuilt left-associatively.
+    /// `true` operands and double negations are removed, implications
+    /// `a -> b` are replaced with `!a | b`, and comparisons between a port
+    /// and a constant put the port on the left, flipping the operator, so
+    /// `8 > x` becomes `x < 8`.
+    pub fn canonicalize(&self) -> Guard<T>
+    where
+        T: Clone + Eq + ToString,
+    {
+        match self {
+            Guard::And(..) | Guard::Or(..) => {
+                let is_and = matches!(self, Guard::And(..));
+                let mut chain = vec![];
+                self.flatten_chain(is_and, &mut chain);
+                let mut operands = vec![];
+                for g in chain {
+                    let canon = g.canonicalize();
+                    let mut sub = vec![];
+                    canon.flatten_chain(is_and, &mut sub);
+                    operands.extend(sub.into_iter().cloned());
+                }
+                if !is_and && operands.iter().any(Guard::is_true) {
+                    return Guard::True;
+                }
+                let mut keyed: Vec<_> = operands
+                    .into_iter()
+                    .filter(|g| !g.is_true())
+                    .map(|g| (Printer::guard_str(&g), g))
+                    .collect();
+                keyed.sort_by(|(l, _), (r, _)| l.cmp(r));
+                keyed.dedup_by(|(l, _), (r, _)| l == r);
+                keyed
+                    .into_iter()
+                    .map(|(_, g)| g)
+                    .reduce(|l, r| {
+                        if is_and {
+                            Guard::And(Box::new(l), Box::new(r))
+                        } else {
+                            Guard::Or(Box::new(l), Box::new(r))
+                        }
+                    })
+                    .unwrap_or(Guard::True)
             }
-            (Guard::Not(a), Guard::Not(b)) => a == b,
-            (Guard::Port(a), Guard::Port(b)) => {
-                (a.borrow().get_parent_name(), &a.borrow().name)
-                    == (b.borrow().get_parent_name(), &b.borrow().name)
+            Guard::Not(g) => match g.canonicalize() {
+                Guard::Not(g) => *g,
+                g => Guard::Not(Box::new(g)),
+            },
+            Guard::Implies(..) => self.clone().expand_implies().canonicalize(),
+            Guard::CompOp(_, l, r)
+                if l.borrow().constant_value().is_some()
+                    && r.borrow().constant_value().is_none() =>
+            {
+                self.swap_operands().unwrap()
             }
-            (Guard::True, Guard::True) => true,
-            (Guard::Info(i1), Guard::Info(i2)) => i1 == i2,
-            _ => false,
+            g => g.clone(),
         }
     }
-}
 
-impl<T> Eq for Guard<T> where T: Eq {}
+    /// Simplify the guard with the absorption laws `a | (a & b) == a` and
+    /// `a & (a | b) == a`, applied to [canonicalized](Guard::canonicalize)
+    /// chains of `|` and `&`: an operand of a chain is removed if it is the
+    /// dual chain of a superset of the operands of another operand. The
+    /// result is canonical, and absorbing it again does not change it.
+    pub fn absorb(&self) -> Guard<T>
+    where
+        T: Clone + Eq + ToString,
+    {
+        let mut guard = self.canonicalize();
+        loop {
+            let next = guard.absorb_once().canonicalize();
+            if Printer::guard_str(&next) == Printer::guard_str(&guard) {
+                return next;
+            }
+            guard = next;
+        }
+    }
 
-/// Define order on guards
-impl<T> PartialOrd for Guard<T>
-where
-    T: Eq,
-{
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    fn absorb_once(&self) -> Guard<T>
+    where
+        T: Clone + Eq + ToString,
+    {
+        match self {
+            Guard::And(..) | Guard::Or(..) => {
+                let is_and = matches!(self, Guard::And(..));
+                let mut chain = vec![];
+                self.flatten_chain(is_and, &mut chain);
+                let operands: Vec<_> =
+                    chain.into_iter().map(Guard::absorb_once).collect();
+                // The operands of the dual chain of each operand.
+                let duals: Vec<HashSet<String>> = operands
+                    .iter()
+                    .map(|g| {
+                        let mut dual = vec![];
+                        g.flatten_chain(!is_and, &mut dual);
+                        dual.into_iter().map(Printer::guard_str).collect()
+                    })
+                    .collect();
+                // Operands that are identical to an earlier one were removed
+                // by canonicalization, so a subset is always strict.
+                let absorbed = |i: usize| {
+                    (0..duals.len()).any(|j| {
+                        j != i
+                            && duals[j].len() < duals[i].len()
+                            && duals[j].is_subset(&duals[i])
+                    })
+                };
+                operands
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, _)| !absorbed(*i))
+                    .map(|(_, g)| g)
+                    .reduce(|l, r| {
+                        if is_and {
+                            Guard::And(Box::new(l), Box::new(r))
+                        } else {
+                            Guard::Or(Box::new(l), Box::new(r))
+                        }
+                    })
+                    .unwrap()
+            }
+            Guard::Not(g) => Guard::Not(Box::new(g.absorb_once())),
+            g => g.clone(),
+        }
     }
-}
 
-/// Define an ordering on the precedence of guards. Guards are
+    /// Simplify the guard bottom-up with the rules of [SimplifyRule] and
+    /// report every rewrite in the order it was applied, together with the
+    /// subtree it rewrote. The children of a node are simplified before the
+    /// node, and a rewritten node is simplified again, so the result is not
+    /// changed by simplifying it again.
+    pub fn simplify_reporting(self) -> (Guard<T>, Vec<SimplifyStep<T>>)
+    where
+        T: Clone + Eq,
+    {
+        let mut steps = vec![];
+        let guard = self.simplify_into(&mut steps);
+        (guard, steps)
+    }
+
+    fn simplify_into(self, steps: &mut Vec<SimplifyStep<T>>) -> Guard<T>
+    where
+        T: Clone + Eq,
+    {
+        let guard = match self {
+            Guard::And(l, r) => Guard::And(
+                Box::new(l.simplify_into(steps)),
+                Box::new(r.simplify_into(steps)),
+            ),
+            Guard::Or(l, r) => Guard::Or(
+                Box::new(l.simplify_into(steps)),
+                Box::new(r.simplify_into(steps)),
+            ),
+            Guard::Implies(l, r) => Guard::Implies(
+                Box::new(l.simplify_into(steps)),
+                Box::new(r.simplify_into(steps)),
+            ),
+            Guard::Not(g) => Guard::Not(Box::new(g.simplify_into(steps))),
+            g => g,
+        };
+        let Some((rule, next)) = guard.simplify_node() else {
+            return guard;
+        };
+        steps.push(SimplifyStep {
+            rule,
+            subtree: guard,
+        });
+        next.simplify_into(steps)
+    }
+
+    /// The first rule of [SimplifyRule] that applies to the root of the
+    /// guard and the guard it produces.
+    fn simplify_node(&self) -> Option<(SimplifyRule, Guard<T>)>
+    where
+        T: Clone + Eq,
+    {
+        match self {
+            Guard::Not(g) => match &**g {
+                Guard::Not(inner) => {
+                    Some((SimplifyRule::DoubleNegation, (**inner).clone()))
+                }
+                _ => None,
+            },
+            Guard::And(l, r) | Guard::Or(l, r) => {
+                let is_and = matches!(self, Guard::And(..));
+                for (c, other) in [(l, r), (r, l)] {
+                    if c.is_true() || c.is_false() {
+                        // `true` is the identity of `&` and `false` of `|`.
+                        let folded =
+                            if c.is_true() == is_and { other } else { c };
+                        let folded = (**folded).clone();
+                        return Some((SimplifyRule::ConstantFold, folded));
+                    }
+                }
+                // Flattening keeps chains on the left, so an operand that
+                // repeats an earlier one of the chain is on the right.
+                let mut chain = vec![];
+                l.flatten_chain(is_and, &mut chain);
+                if chain.contains(&&**r) {
+                    return Some((SimplifyRule::Idempotence, (**l).clone()));
+                }
+                for (a, b) in [(l, r), (r, l)] {
+                    let absorbs = match (is_and, &**b) {
+                        (true, Guard::Or(bl, br))
+                        | (false, Guard::And(bl, br)) => bl == a || br == a,
+                        _ => false,
+                    };
+                    if absorbs {
+                        return Some((SimplifyRule::Absorption, (**a).clone()));
+                    }
+                }
+                let flat = match (is_and, &**r) {
+                    (true, Guard::And(rl, rr)) => Guard::And(
+                        Box::new(Guard::And(l.clone(), rl.clone())),
+                        rr.clone(),
+                    ),
+                    (false, Guard::Or(rl, rr)) => Guard::Or(
+                        Box::new(Guard::Or(l.clone(), rl.clone())),
+                        rr.clone(),
+                    ),
+                    _ => return None,
+                };
+                Some((SimplifyRule::Flatten, flat))
+            }
+            _ => None,
+        }
+    }
+
+    /// Rewrite the guard with user-defined rules until none of them apply.
+    /// The guard and the patterns are [canonicalized](Guard::canonicalize)
+    /// before matching, and the operands of `&` and `|` match in either
+    /// order. Each round rewrites the children of a node before trying the
+    /// rules on the node itself, in order, and applies the first rule that
+    /// matches. Rewriting stops when a round does not change the guard or
+    /// produces a guard that was already seen, so rules that undo each
+    /// other do not loop forever. Returns an error if a replacement compares
+    /// a metavariable that matched a leaf other than a port.
+    pub fn rewrite_with(&self, rules: &[GuardRule<T>]) -> CalyxResult<Guard<T>>
+    where
+        T: Clone + Eq + ToString,
+    {
+        let rules: Vec<_> = rules
+            .iter()
+            .map(|rule| GuardRule {
+                pattern: rule.pattern.canonicalize(),
+                replacement: rule.replacement.clone(),
+            })
+            .collect();
+        let mut guard = self.canonicalize();
+        let mut seen = HashSet::from([Printer::guard_str(&guard)]);
+        loop {
+            let next = guard.rewrite_once(&rules)?.canonicalize();
+            if !seen.insert(Printer::guard_str(&next)) {
+                return Ok(next);
+            }
+            guard = next;
+        }
+    }
+
+    fn rewrite_once(&self, rules: &[GuardRule<T>]) -> CalyxResult<Guard<T>>
+    where
+        T: Clone + Eq + ToString,
+    {
+        let guard = match self {
+            Guard::And(l, r) => Guard::And(
+                Box::new(l.rewrite_once(rules)?),
+                Box::new(r.rewrite_once(rules)?),
+            ),
+            Guard::Or(l, r) => Guard::Or(
+                Box::new(l.rewrite_once(rules)?),
+                Box::new(r.rewrite_once(rules)?),
+            ),
+            Guard::Not(g) => Guard::Not(Box::new(g.rewrite_once(rules)?)),
+            g => g.clone(),
+        };
+        for rule in rules {
+            let mut bindings = HashMap::new();
+            if rule.pattern.match_pattern(&guard, &mut bindings) {
+                return rule.replacement.instantiate(&bindings);
+            }
+        }
+        Ok(guard)
+    }
+
+    /// Match `guard` against this pattern, extending the bindings of the
+    /// metavariables. The bindings are unchanged if the match fails.
+    fn match_pattern(
+        &self,
+        guard: &Guard<T>,
+        bindings: &mut HashMap<Canonical, Guard<T>>,
+    ) -> bool
+    where
+        T: Clone + Eq,
+    {
+        let mut attempt = bindings.clone();
+        let matched = match (self, guard) {
+            (Guard::Port(p), g) => {
+                !matches!(
+                    g,
+                    Guard::And(..)
+                        | Guard::Or(..)
+                        | Guard::Implies(..)
+                        | Guard::Not(_)
+                ) && bind_metavar(&mut attempt, p, g)
+            }
+            (Guard::And(pl, pr), Guard::And(l, r))
+            | (Guard::Or(pl, pr), Guard::Or(l, r)) => {
+                [(l, r), (r, l)].into_iter().any(|(gl, gr)| {
+                    attempt = bindings.clone();
+                    pl.match_pattern(gl, &mut attempt)
+                        && pr.match_pattern(gr, &mut attempt)
+                })
+            }
+            (Guard::Not(p), Guard::Not(g)) => p.match_pattern(g, &mut attempt),
+            (Guard::CompOp(pop, pl, pr), Guard::CompOp(op, l, r)) => {
+                let l = Guard::Port(Rc::clone(l));
+                let r = Guard::Port(Rc::clone(r));
+                pop == op
+                    && bind_metavar(&mut attempt, pl, &l)
+                    && bind_metavar(&mut attempt, pr, &r)
+            }
+            (Guard::True, Guard::True) => true,
+            (Guard::Info(pi), Guard::Info(i)) => pi == i,
+            _ => false,
+        };
+        if matched {
+            *bindings = attempt;
+        }
+        matched
+    }
+
+    /// Replace the metavariables of this replacement with their bindings.
+    /// Fails if an operand of a comparison is bound to a leaf that is not a
+    /// port.
+    fn instantiate(
+        &self,
+        bindings: &HashMap<Canonical, Guard<T>>,
+    ) -> CalyxResult<Guard<T>>
+    where
+        T: Clone + Eq + ToString,
+    {
+        let bound_port = |p: &RRC<Port>| {
+            match bindings.get(&p.borrow().canonical()) {
+                Some(Guard::Port(bound)) => Ok(Rc::clone(bound)),
+                Some(g) => Err(Error::malformed_structure(format!(
+                    "Rewrite rule compares `{}', which matched `{}' instead of a port",
+                    p.borrow().canonical(),
+                    Printer::guard_str(g),
+                ))),
+                None => Ok(Rc::clone(p)),
+            }
+        };
+        Ok(match self {
+            Guard::And(l, r) => {
+                l.instantiate(bindings)? & r.instantiate(bindings)?
+            }
+            Guard::Or(l, r) => {
+                l.instantiate(bindings)? | r.instantiate(bindings)?
+            }
+            Guard::Implies(l, r) => l
+                .instantiate(bindings)?
+                .implies_guard(r.instantiate(bindings)?),
+            Guard::Not(g) => !g.instantiate(bindings)?,
+            Guard::Port(p) => bindings
+                .get(&p.borrow().canonical())
+                .cloned()
+                .unwrap_or_else(|| Guard::Port(Rc::clone(p))),
+            Guard::CompOp(op, l, r) => {
+                Guard::CompOp(op.clone(), bound_port(l)?, bound_port(r)?)
+            }
+            g => g.clone(),
+        })
+    }
+
+    /// A string that identifies the guard up to the reordering performed by
+    /// [Guard::canonicalize]: the printed form of its canonical guard.
+    /// Useful as a cheap key when deduplicating guards.
+    pub fn canonical_key(&self) -> String
+    where
+        T: Clone + Eq + ToString,
+    {
+        Printer::guard_str(&self.canonicalize())
+    }
+
+    /// Collect the operands of the chain of `&` (or `|` when `is_and` is
+    /// false) nodes rooted at this guard.
+    fn flatten_chain<'a>(
+        &'a self,
+        is_and: bool,
+        out: &mut Vec<&'a Guard<T>>,
+    ) {
+        match self {
+            Guard::And(l, r) if is_and => {
+                l.flatten_chain(is_and, out);
+                r.flatten_chain(is_and, out);
+            }
+            Guard::Or(l, r) if !is_and => {
+                l.flatten_chain(is_and, out);
+                r.flatten_chain(is_and, out);
+            }
+            g => out.push(g),
+        }
+    }
+
+    /// The truth table of the guard over `atoms`, the ports, comparisons and
+    /// infos the guard is built from. Comparisons are treated as independent
+    /// booleans. Row `i` gives atom `k` the value of bit `n - 1 - k` of `i`,
+    /// where `n` is the number of atoms, so the first atom is the most
+    /// significant and the first row has every atom false. Returns `None`
+    /// if there are more than `max_atoms` atoms or the guard uses an atom
+    /// that is not in `atoms`.
+    pub fn truth_table(
+        &self,
+        atoms: &[Guard<T>],
+        max_atoms: usize,
+    ) -> Option<Vec<bool>>
+    where
+        T: Eq,
+    {
+        let n = atoms.len();
+        if n > max_atoms || n >= usize::BITS as usize {
+            return None;
+        }
+        (0..1usize << n)
+            .map(|row| {
+                let values: Vec<bool> =
+                    (0..n).map(|k| (row >> (n - 1 - k)) & 1 == 1).collect();
+                self.eval_atoms(atoms, &values)
+            })
+            .collect()
+    }
+
+    /// Evaluate the guard when `atoms[k]` has the value `values[k]`.
+    fn eval_atoms(&self, atoms: &[Guard<T>], values: &[bool]) -> Option<bool>
+    where
+        T: Eq,
+    {
+        match self {
+            Guard::And(l, r) => {
+                let l = l.eval_atoms(atoms, values)?;
+                let r = r.eval_atoms(atoms, values)?;
+                Some(l && r)
+            }
+            Guard::Or(l, r) => {
+                let l = l.eval_atoms(atoms, values)?;
+                let r = r.eval_atoms(atoms, values)?;
+                Some(l || r)
+            }
+            Guard::Implies(l, r) => {
+                let l = l.eval_atoms(atoms, values)?;
+                let r = r.eval_atoms(atoms, values)?;
+                Some(!l || r)
+            }
+            Guard::Not(g) => Some(!g.eval_atoms(atoms, values)?),
+            Guard::True => Some(true),
+            atom => atoms.iter().position(|a| a == atom).map(|k| values[k]),
+        }
+    }
+
+    /// The guard as an [Aig] whose inputs are `atoms`, the ports,
+    /// comparisons and infos the guard is built from: input `k` of the graph
+    /// is `atoms[k]`. `|` is built from `&` and negation by De Morgan's law.
+    /// Returns `None` if the guard uses an atom that is not in `atoms`.
+    pub fn to_aig(&self, atoms: &[Guard<T>]) -> Option<Aig>
+    where
+        T: Eq,
+    {
+        let mut aig = Aig::new(atoms.len());
+        aig.output = self.aig_literal(atoms, &mut aig)?;
+        Some(aig)
+    }
+
+    fn aig_literal(&self, atoms: &[Guard<T>], aig: &mut Aig) -> Option<usize>
+    where
+        T: Eq,
+    {
+        match self {
+            Guard::And(l, r) => {
+                let l = l.aig_literal(atoms, aig)?;
+                let r = r.aig_literal(atoms, aig)?;
+                Some(aig.and(l, r))
+            }
+            Guard::Or(l, r) => {
+                let l = l.aig_literal(atoms, aig)?;
+                let r = r.aig_literal(atoms, aig)?;
+                Some(aig.or(l, r))
+            }
+            Guard::Implies(l, r) => {
+                let l = l.aig_literal(atoms, aig)?;
+                let r = r.aig_literal(atoms, aig)?;
+                Some(aig.or(Aig::not(l), r))
+            }
+            Guard::Not(g) => Some(Aig::not(g.aig_literal(atoms, aig)?)),
+            Guard::True => Some(Aig::TRUE),
+            atom => atoms.iter().position(|a| a == atom).map(|k| aig.input(k)),
+        }
+    }
+
+    /// A minimal sum-of-products form of the guard computed with the
+    /// Quine-McCluskey algorithm, treating its ports, comparisons and infos
+    /// as independent booleans. The result has the fewest products, and the
+    /// fewest literals among those, of all sums of products over the same
+    /// atoms. Guards with more than `max_atoms` atoms are only
+    /// [canonicalized](Guard::canonicalize).
+    pub fn minimize(&self, max_atoms: usize) -> Guard<T>
+    where
+        T: Clone + Eq + ToString,
+    {
+        let mut atoms = vec![];
+        self.collect_atoms(&mut atoms);
+        let Some(table) = self.truth_table(&atoms, max_atoms) else {
+            return self.canonicalize();
+        };
+        let minterms: Vec<usize> = table
+            .iter()
+            .enumerate()
+            .filter_map(|(row, v)| v.then_some(row))
+            .collect();
+        if minterms.is_empty() {
+            return !Guard::True;
+        }
+
+        let primes = prime_implicants(&minterms);
+        let mut best = None;
+        min_cover(&minterms, &primes, atoms.len(), &mut vec![], &mut best);
+        let mut cover = best.unwrap();
+        cover.sort_by(|a, b| b.cmp(a));
+
+        // Bit `n - 1 - k` of an implicant belongs to atom `k`.
+        let n = atoms.len();
+        cover
+            .into_iter()
+            .map(|(value, mask)| {
+                atoms
+                    .iter()
+                    .enumerate()
+                    .filter(|(k, _)| (mask >> (n - 1 - k)) & 1 == 0)
+                    .map(|(k, atom)| {
+                        if (value >> (n - 1 - k)) & 1 == 1 {
+                            atom.clone()
+                        } else {
+                            !atom.clone()
+                        }
+                    })
+                    .fold(Guard::True, Guard::and)
+            })
+            .reduce(Guard::or)
+            .unwrap()
+    }
+
+    /// Add the ports, comparisons and infos of the guard to `atoms`, in the
+    /// order they first appear.
+    fn collect_atoms(&self, atoms: &mut Vec<Guard<T>>)
+    where
+        T: Clone + Eq,
+    {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                l.collect_atoms(atoms);
+                r.collect_atoms(atoms);
+            }
+            Guard::Not(g) => g.collect_atoms(atoms),
+            Guard::True => {}
+            atom => {
+                if !atoms.contains(atom) {
+                    atoms.push(atom.clone());
+                }
+            }
+        }
+    }
+
+    /// Simplify the guard assuming that `atom`, a port, comparison or info,
+    /// has the given value. Occurrences of `atom` are replaced with `value`
+    /// and occurrences of its complement, such as `x >= 8` for `x < 8`, with
+    /// the opposite value. Comparisons also match with their operands
+    /// swapped, so assuming `x < 8` replaces `8 > x` as well.
+    pub fn assume(&self, atom: &Guard<T>, value: bool) -> Guard<T>
+    where
+        T: Clone + Eq,
+    {
+        let complement = !atom.clone();
+        let is = |g: &Guard<T>, a: &Guard<T>| {
+            g == a || g.swap_operands().map_or(false, |s| &s == a)
+        };
+        self.assume_with(&|g| {
+            if is(g, atom) {
+                Some(value)
+            } else if is(g, &complement) {
+                Some(!value)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Replace the leaves for which `known` returns a value with that value
+    /// and simplify the guard.
+    fn assume_with<F>(&self, known: &F) -> Guard<T>
+    where
+        F: Fn(&Guard<T>) -> Option<bool>,
+        T: Clone,
+    {
+        let constant = |v: bool| {
+            if v {
+                Guard::True
+            } else {
+                Guard::Not(Box::new(Guard::True))
+            }
+        };
+        match self {
+            Guard::And(l, r) => {
+                let (l, r) = (l.assume_with(known), r.assume_with(known));
+                if l.is_false() || r.is_true() {
+                    l
+                } else if r.is_false() || l.is_true() {
+                    r
+                } else {
+                    Guard::And(Box::new(l), Box::new(r))
+                }
+            }
+            Guard::Or(l, r) => {
+                let (l, r) = (l.assume_with(known), r.assume_with(known));
+                if l.is_true() || r.is_false() {
+                    l
+                } else if r.is_true() || l.is_false() {
+                    r
+                } else {
+                    Guard::Or(Box::new(l), Box::new(r))
+                }
+            }
+            Guard::Implies(l, r) => {
+                let (l, r) = (l.assume_with(known), r.assume_with(known));
+                if l.is_false() || r.is_true() {
+                    Guard::True
+                } else if l.is_true() {
+                    r
+                } else if r.is_false() {
+                    !l
+                } else {
+                    Guard::Implies(Box::new(l), Box::new(r))
+                }
+            }
+            Guard::Not(g) => match g.assume_with(known) {
+                g if g.is_true() => constant(false),
+                g if g.is_false() => Guard::True,
+                g => Guard::Not(Box::new(g)),
+            },
+            Guard::True => Guard::True,
+            leaf => known(leaf).map_or_else(|| leaf.clone(), constant),
+        }
+    }
+
+    /// The comparison with its operands swapped, such as `8 > x` for
+    /// `x < 8`.
+    fn swap_operands(&self) -> Option<Guard<T>> {
+        let Guard::CompOp(op, l, r) = self else {
+            return None;
+        };
+        let op = match op {
+            PortComp::Eq => PortComp::Eq,
+            PortComp::Neq => PortComp::Neq,
+            PortComp::Gt => PortComp::Lt,
+            PortComp::Lt => PortComp::Gt,
+            PortComp::Geq => PortComp::Leq,
+            PortComp::Leq => PortComp::Geq,
+        };
+        Some(Guard::CompOp(op, Rc::clone(r), Rc::clone(l)))
+    }
+
+    /// Simplify the guard given ports that are known to always have equal
+    /// values, such as the two sides of a continuous assignment `a.in =
+    /// b.out`. Comparisons between ports in the same class of `eqs` are
+    /// replaced with their value for equal operands, so `a.out == b.out`
+    /// becomes `true` and `a.out < b.out` becomes `false`, and the result
+    /// is [canonicalized](Guard::canonicalize).
+    pub fn simplify_with_equalities(
+        &self,
+        eqs: &UnionFind<Canonical>,
+    ) -> Guard<T>
+    where
+        T: Clone + Eq + ToString,
+    {
+        self.assume_with(&|g| {
+            let (op, l, r) = g.as_cmp()?;
+            if !eqs.equiv(&l.borrow().canonical(), &r.borrow().canonical()) {
+                return None;
+            }
+            Some(matches!(op, PortComp::Eq | PortComp::Geq | PortComp::Leq))
+        })
+        .canonicalize()
+    }
+
+    /// Whether the guard is monotone in the value of `port`. Returns
+    /// `Some(true)` if increasing the value of `port` can never make the
+    /// guard go from true to false, `Some(false)` if it can never make the
+    /// guard go from false to true, and `None` if the guard is neither or
+    /// it cannot be determined. A guard that does not depend on `port` is
+    /// treated as increasing.
+    pub fn is_monotone_in(&self, port: &RRC<Port>) -> Option<bool> {
+        match self.monotonicity(port) {
+            Monotonicity::Constant | Monotonicity::Increasing => Some(true),
+            Monotonicity::Decreasing => Some(false),
+            Monotonicity::Unknown => None,
+        }
+    }
+
+    fn monotonicity(&self, port: &RRC<Port>) -> Monotonicity {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) => {
+                l.monotonicity(port).combine(r.monotonicity(port))
+            }
+            Guard::Implies(l, r) => {
+                l.monotonicity(port).flip().combine(r.monotonicity(port))
+            }
+            Guard::Not(g) => g.monotonicity(port).flip(),
+            Guard::Port(p) if Rc::ptr_eq(p, port) => Monotonicity::Increasing,
+            Guard::CompOp(op, l, r) => {
+                let increasing = match op {
+                    PortComp::Gt | PortComp::Geq => Monotonicity::Increasing,
+                    PortComp::Lt | PortComp::Leq => Monotonicity::Decreasing,
+                    PortComp::Eq | PortComp::Neq => Monotonicity::Unknown,
+                };
+                match (Rc::ptr_eq(l, port), Rc::ptr_eq(r, port)) {
+                    (true, false) => increasing,
+                    (false, true) => increasing.flip(),
+                    (true, true) | (false, false) => Monotonicity::Constant,
+                }
+            }
+            Guard::Port(_) | Guard::True | Guard::Info(_) => {
+                Monotonicity::Constant
+            }
+        }
+    }
+
+    /// Structural equality that ignores `true` operands, so `a & 1'd1` is
+    /// equal to `a` and `a | 1'd1` is equal to `1'd1`. Ports are compared
+    /// in the same way as [PartialEq].
+    pub fn eq_modulo_true(&self, other: &Guard<T>) -> bool
+    where
+        T: Eq,
+    {
+        match (self.strip_true(), other.strip_true()) {
+            (Guard::And(l1, r1), Guard::And(l2, r2))
+            | (Guard::Or(l1, r1), Guard::Or(l2, r2))
+            | (Guard::Implies(l1, r1), Guard::Implies(l2, r2)) => {
+                l1.eq_modulo_true(l2) && r1.eq_modulo_true(r2)
+            }
+            (Guard::Not(g1), Guard::Not(g2)) => g1.eq_modulo_true(g2),
+            (l, r) => l == r,
+        }
+    }
+
+    /// Replace every subtree that is structurally equal to `pattern` with
+    /// `replacement` and return the number of replacements. Ports are
+    /// compared in the same way as [PartialEq] and the replacements are not
+    /// searched for further matches.
+    pub fn replace_subtree(
+        &mut self,
+        pattern: &Guard<T>,
+        replacement: &Guard<T>,
+    ) -> usize
+    where
+        T: Eq + Clone,
+    {
+        if *self == *pattern {
+            *self = replacement.clone();
+            return 1;
+        }
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                l.replace_subtree(pattern, replacement)
+                    + r.replace_subtree(pattern, replacement)
+            }
+            Guard::Not(g) => g.replace_subtree(pattern, replacement),
+            Guard::CompOp(..)
+            | Guard::Port(_)
+            | Guard::True
+            | Guard::Info(_) => 0,
+        }
+    }
+
+    /// Conservatively check if this guard implies `other`. Returning true
+    /// means that `other` holds whenever this guard holds but returning false
+    /// does not mean that it doesn't.
+    pub fn implies(&self, other: &Guard<T>) -> bool
+    where
+        T: Eq,
+    {
+        if other.is_true() || self.is_false() || self == other {
+            return true;
+        }
+        match (self, other) {
+            (_, Guard::And(l, r)) => self.implies(l) && self.implies(r),
+            (Guard::Or(l, r), _) => l.implies(other) && r.implies(other),
+            (Guard::And(l, r), _) => l.implies(other) || r.implies(other),
+            (_, Guard::Or(l, r)) => self.implies(l) || self.implies(r),
+            (_, Guard::Implies(_, r)) => self.implies(r),
+            (Guard::CompOp(PortComp::Eq, ..), Guard::CompOp(..)) => {
+                match (self.const_comparison(), other.const_comparison()) {
+                    (Some((p1, _, v1)), Some((p2, op, v2))) if p1 == p2 => {
+                        match op {
+                            PortComp::Eq => v1 == v2,
+                            PortComp::Neq => v1 != v2,
+                            PortComp::Gt => v1 > v2,
+                            PortComp::Lt => v1 < v2,
+                            PortComp::Geq => v1 >= v2,
+                            PortComp::Leq => v1 <= v2,
+                        }
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Conservatively check if this guard and `other` are never true at the
+    /// same time. Returning true means that they are mutually exclusive but
+    /// returning false does not mean that they aren't. Comparisons are
+    /// exclusive if one [implies](Guard::implies) the negation of the other
+    /// or if their [ranges](Guard::port_ranges) conflict.
+    pub fn is_mutually_exclusive(&self, other: &Guard<T>) -> bool
+    where
+        T: Clone + Eq,
+    {
+        if self.is_false() || other.is_false() {
+            return true;
+        }
+        match (self, other) {
+            (Guard::Implies(..), _) => {
+                self.clone().expand_implies().is_mutually_exclusive(other)
+            }
+            (_, Guard::Implies(..)) => {
+                self.is_mutually_exclusive(&other.clone().expand_implies())
+            }
+            (Guard::Or(l, r), _) => {
+                l.is_mutually_exclusive(other) && r.is_mutually_exclusive(other)
+            }
+            (_, Guard::Or(l, r)) => {
+                self.is_mutually_exclusive(l) && self.is_mutually_exclusive(r)
+            }
+            (Guard::And(l, r), _) => {
+                l.is_mutually_exclusive(other) || r.is_mutually_exclusive(other)
+            }
+            (_, Guard::And(l, r)) => {
+                self.is_mutually_exclusive(l) || self.is_mutually_exclusive(r)
+            }
+            (Guard::Not(g), h) | (h, Guard::Not(g)) => h.implies(g),
+            _ => {
+                let both =
+                    Guard::And(Box::new(self.clone()), Box::new(other.clone()));
+                self.implies(&!other.clone())
+                    || both.port_ranges().values().any(Option::is_none)
+            }
+        }
+    }
+
+    /// Compute the inclusive range of values that each port compared against
+    /// a constant in the conjuncts of this guard can take when the guard is
+    /// true, intersecting multiple constraints on the same port. The range is
+    /// `None` if the constraints conflict. Ports without such comparisons are
+    /// omitted, and `!=` comparisons as well as comparisons under `|`, `->`
+    /// or `!` are ignored.
+    pub fn port_ranges(&self) -> HashMap<Canonical, Option<(u64, u64)>> {
+        let mut ranges = HashMap::new();
+        self.port_ranges_into(&mut ranges);
+        ranges
+    }
+
+    fn port_ranges_into(
+        &self,
+        ranges: &mut HashMap<Canonical, Option<(u64, u64)>>,
+    ) {
+        if let Guard::And(l, r) = self {
+            l.port_ranges_into(ranges);
+            r.port_ranges_into(ranges);
+            return;
+        }
+        let (Guard::CompOp(_, l, _), Some((port, op, v))) =
+            (self, self.const_comparison())
+        else {
+            return;
+        };
+        let width = l.borrow().width;
+        let max = if width >= 64 {
+            u64::MAX
+        } else {
+            (1 << width) - 1
+        };
+        let range = match op {
+            PortComp::Eq => Some((v, v)),
+            PortComp::Neq => return,
+            PortComp::Lt => v.checked_sub(1).map(|hi| (0, hi)),
+            PortComp::Leq => Some((0, v)),
+            PortComp::Gt => v.checked_add(1).map(|lo| (lo, max)),
+            PortComp::Geq => Some((v, max)),
+        };
+        let cur = ranges.entry(port).or_insert(Some((0, max)));
+        *cur = match (*cur, range) {
+            (Some((lo1, hi1)), Some((lo2, hi2))) => {
+                let (lo, hi) = (lo1.max(lo2), hi1.min(hi2));
+                (lo <= hi).then_some((lo, hi))
+            }
+            _ => None,
+        };
+    }
+
+    /// Fold the comparisons against constants whose outcome is determined by
+    /// `ranges`, the inclusive range of values each port can take, such as
+    /// the ranges computed by [Guard::port_ranges]. For example, `x < 8`
+    /// becomes `true` if `x` is in the range `(3, 3)`. Ports whose range is
+    /// `None` are ignored.
+    pub fn fold_ranges(
+        &self,
+        ranges: &HashMap<Canonical, Option<(u64, u64)>>,
+    ) -> Guard<T>
+    where
+        T: Clone,
+    {
+        self.assume_with(&|g| {
+            let (port, op, v) = g.const_comparison()?;
+            let (lo, hi) = (*ranges.get(&port)?)?;
+            let (always, never) = match op {
+                PortComp::Eq => (lo == v && hi == v, v < lo || v > hi),
+                PortComp::Neq => (v < lo || v > hi, lo == v && hi == v),
+                PortComp::Lt => (hi < v, lo >= v),
+                PortComp::Leq => (hi <= v, lo > v),
+                PortComp::Gt => (lo > v, hi <= v),
+                PortComp::Geq => (lo >= v, hi < v),
+            };
+            (always || never).then_some(always)
+        })
+    }
+
+    /// Simplify the guard given the `reachable` states of an FSM whose state
+    /// is read from `state_port`. Comparisons `state_port == k` against an
+    /// unreachable state `k` become `false`, and `state_port != k` becomes
+    /// `true`.
+    pub fn prune_unreachable(
+        &self,
+        reachable: &HashSet<u64>,
+        state_port: &Canonical,
+    ) -> Guard<T>
+    where
+        T: Clone,
+    {
+        self.assume_with(&|g| match g.const_comparison()? {
+            (port, PortComp::Eq, v) if &port == state_port => {
+                (!reachable.contains(&v)).then_some(false)
+            }
+            (port, PortComp::Neq, v) if &port == state_port => {
+                (!reachable.contains(&v)).then_some(true)
+            }
+            _ => None,
+        })
+    }
+
+    /// Every `(port, constant)` pair that the guard compares, in the order
+    /// they first appear in the guard, without duplicates.
+    pub fn comparison_constants(&self) -> Vec<(Canonical, u64)> {
+        let mut pairs = vec![];
+        self.comparison_constants_into(&mut pairs);
+        pairs
+    }
+
+    fn comparison_constants_into(&self, pairs: &mut Vec<(Canonical, u64)>) {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                l.comparison_constants_into(pairs);
+                r.comparison_constants_into(pairs);
+            }
+            Guard::Not(g) => g.comparison_constants_into(pairs),
+            g => {
+                if let Some((port, _, v)) = g.const_comparison() {
+                    if !pairs.contains(&(port, v)) {
+                        pairs.push((port, v));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check the widths of the ports read by the guard and compute the widths
+    /// of its comparisons. The width of a port is looked up in `port_widths`
+    /// and defaults to the width of the port. Ports used as guards must be
+    /// 1-bit, and the operands of a comparison must have the same width. The
+    /// error names the offending port or comparison.
+    pub fn infer_widths(
+        &self,
+        port_widths: &HashMap<Canonical, u64>,
+    ) -> CalyxResult<GuardWidths>
+    where
+        T: ToString,
+    {
+        let mut comparisons = vec![];
+        self.infer_widths_into(port_widths, &mut comparisons)?;
+        Ok(GuardWidths {
+            result: 1,
+            comparisons,
+        })
+    }
+
+    fn infer_widths_into(
+        &self,
+        port_widths: &HashMap<Canonical, u64>,
+        comparisons: &mut Vec<u64>,
+    ) -> CalyxResult<()>
+    where
+        T: ToString,
+    {
+        let width = |port: &RRC<Port>| {
+            let port = port.borrow();
+            port_widths
+                .get(&port.canonical())
+                .copied()
+                .unwrap_or(port.width)
+        };
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                l.infer_widths_into(port_widths, comparisons)?;
+                r.infer_widths_into(port_widths, comparisons)
+            }
+            Guard::Not(g) => g.infer_widths_into(port_widths, comparisons),
+            Guard::CompOp(_, l, r) => {
+                let (lw, rw) = (width(l), width(r));
+                if lw != rw {
+                    return Err(Error::malformed_structure(format!(
+                        "Comparison `{}' has operands of widths {lw} and {rw}",
+                        Printer::guard_str(self),
+                    )));
+                }
+                comparisons.push(lw);
+                Ok(())
+            }
+            Guard::Port(p) => match width(p) {
+                1 => Ok(()),
+                w => Err(Error::malformed_structure(format!(
+                    "Guard port `{}' has width {w} instead of 1",
+                    p.borrow().canonical()
+                ))),
+            },
+            Guard::True | Guard::Info(_) => Ok(()),
+        }
+    }
+
+    /// The width of the widest port this guard reads, or 1 if it reads no
+    /// ports. As with [Guard::infer_widths], `port_widths` overrides the
+    /// declared widths of ports.
+    pub fn max_port_width(&self, port_widths: &HashMap<Canonical, u64>) -> u64 {
+        self.all_ports()
+            .iter()
+            .map(|port| {
+                let port = port.borrow();
+                port_widths
+                    .get(&port.canonical())
+                    .copied()
+                    .unwrap_or(port.width)
+            })
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// If this guard compares a port against a constant, return the port, the
+    /// comparison, and the value of the constant with the port on the left.
+    fn const_comparison(&self) -> Option<(Canonical, PortComp, u64)> {
+        let Guard::CompOp(op, l, r) = self else {
+            return None;
+        };
+        match (l.borrow().constant_value(), r.borrow().constant_value()) {
+            (None, Some(v)) => Some((l.borrow().canonical(), op.clone(), v)),
+            (Some(v), None) => {
+                let op = match op {
+                    PortComp::Gt => PortComp::Lt,
+                    PortComp::Lt => PortComp::Gt,
+                    PortComp::Geq => PortComp::Leq,
+                    PortComp::Leq => PortComp::Geq,
+                    PortComp::Eq => PortComp::Eq,
+                    PortComp::Neq => PortComp::Neq,
+                };
+                Some((r.borrow().canonical(), op, v))
+            }
+            _ => None,
+        }
+    }
+
+    /// Skip over `and` and `or` nodes that have a `true` operand.
+    fn strip_true(&self) -> &Guard<T> {
+        match self {
+            Guard::And(l, r) => {
+                let (l, r) = (l.strip_true(), r.strip_true());
+                if r.is_true() {
+                    l
+                } else if l.is_true() {
+                    r
+                } else {
+                    self
+                }
+            }
+            Guard::Or(l, r) => {
+                let (l, r) = (l.strip_true(), r.strip_true());
+                if l.is_true() {
+                    l
+                } else if r.is_true() {
+                    r
+                } else {
+                    self
+                }
+            }
+            _ => self,
+        }
+    }
+}
+
+/// How the value of a guard changes when the value of a port increases.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Monotonicity {
+    /// The guard does not depend on the port.
+    Constant,
+    Increasing,
+    Decreasing,
+    Unknown,
+}
+
+impl Monotonicity {
+    /// Monotonicity of the conjunction or disjunction of two guards.
+    fn combine(self, other: Monotonicity) -> Monotonicity {
+        match (self, other) {
+            (Monotonicity::Constant, m) | (m, Monotonicity::Constant) => m,
+            (l, r) if l == r => l,
+            _ => Monotonicity::Unknown,
+        }
+    }
+
+    /// Monotonicity of the negation of a guard.
+    fn flip(self) -> Monotonicity {
+        match self {
+            Monotonicity::Increasing => Monotonicity::Decreasing,
+            Monotonicity::Decreasing => Monotonicity::Increasing,
+            m => m,
+        }
+    }
+}
+
+/// Bind the metavariable `port` to `guard`, or check that it is already
+/// bound to an equal guard.
+fn bind_metavar<T: Eq + Clone>(
+    bindings: &mut HashMap<Canonical, Guard<T>>,
+    port: &RRC<Port>,
+    guard: &Guard<T>,
+) -> bool {
+    let key = port.borrow().canonical();
+    match bindings.get(&key) {
+        Some(bound) => bound == guard,
+        None => {
+            bindings.insert(key, guard.clone());
+            true
+        }
+    }
+}
+
+/// An implicant of a boolean function, given as the values of the bits that
+/// are fixed and a mask of the bits that can take any value.
+type Implicant = (usize, usize);
+
+/// The prime implicants of the function that is true exactly on `minterms`.
+fn prime_implicants(minterms: &[usize]) -> Vec<Implicant> {
+    let mut primes = BTreeSet::new();
+    let mut current: BTreeSet<Implicant> =
+        minterms.iter().map(|m| (*m, 0)).collect();
+    while !current.is_empty() {
+        let mut next = BTreeSet::new();
+        let mut combined = BTreeSet::new();
+        for (i, &(v1, m1)) in current.iter().enumerate() {
+            for &(v2, m2) in current.iter().skip(i + 1) {
+                let diff = v1 ^ v2;
+                if m1 == m2 && diff.count_ones() == 1 {
+                    next.insert((v1 & !diff, m1 | diff));
+                    combined.insert((v1, m1));
+                    combined.insert((v2, m2));
+                }
+            }
+        }
+        primes.extend(current.difference(&combined));
+        current = next;
+    }
+    primes.into_iter().collect()
+}
+
+/// Find the cheapest set of `primes` covering the `uncovered` minterms by
+/// branching on the primes that cover the first uncovered minterm. The cost
+/// of a cover is its number of implicants and then its number of literals.
+fn min_cover(
+    uncovered: &[usize],
+    primes: &[Implicant],
+    num_atoms: usize,
+    chosen: &mut Vec<Implicant>,
+    best: &mut Option<Vec<Implicant>>,
+) {
+    let cost = |cover: &[Implicant]| {
+        let literals: u32 = cover
+            .iter()
+            .map(|(_, mask)| num_atoms as u32 - mask.count_ones())
+            .sum();
+        (cover.len(), literals)
+    };
+    if best.as_ref().map_or(false, |b| cost(chosen) >= cost(b)) {
+        return;
+    }
+    let Some(&minterm) = uncovered.first() else {
+        *best = Some(chosen.clone());
+        return;
+    };
+    let covers = |&(value, mask): &Implicant, m: usize| (m & !mask) == value;
+    for prime in primes.iter().filter(|p| covers(*p, minterm)) {
+        let rest: Vec<usize> = uncovered
+            .iter()
+            .copied()
+            .filter(|m| !covers(prime, *m))
+            .collect();
+        chosen.push(*prime);
+        min_cover(&rest, primes, num_atoms, chosen, best);
+        chosen.pop();
+    }
+}
+
+/// Helper functions for the guard.
+impl<T> Guard<T> {
+    /// Returns a copy of this guard where every port is replaced with the
+    /// result of calling `f` on it. The ports do not need to belong to the
+    /// same component, so this can be used on guards that mention the
+    /// signature of an invoked component.
+    pub fn map_ports<F>(&self, f: &mut F) -> Guard<T>
+    where
+        F: FnMut(&RRC<Port>) -> RRC<Port>,
+        T: Clone,
+    {
+        match self {
+            Guard::And(l, r) => {
+                Guard::And(Box::new(l.map_ports(f)), Box::new(r.map_ports(f)))
+            }
+            Guard::Or(l, r) => {
+                Guard::Or(Box::new(l.map_ports(f)), Box::new(r.map_ports(f)))
+            }
+            Guard::Implies(l, r) => Guard::Implies(
+                Box::new(l.map_ports(f)),
+                Box::new(r.map_ports(f)),
+            ),
+            Guard::Not(g) => Guard::Not(Box::new(g.map_ports(f))),
+            Guard::CompOp(op, l, r) => Guard::CompOp(op.clone(), f(l), f(r)),
+            Guard::Port(p) => Guard::Port(f(p)),
+            Guard::True => Guard::True,
+            Guard::Info(i) => Guard::Info(i.clone()),
+        }
+    }
+
+    /// Inline the values of the ports in `const_drivers`, which are known to
+    /// be driven by the given constants. An operand of a comparison becomes
+    /// the output of a constant cell added with `builder`, and a port used
+    /// directly as a guard becomes `true` if its value is nonzero and `false`
+    /// otherwise. The result is not simplified.
+    pub fn expand_const_ports(
+        &self,
+        const_drivers: &HashMap<Canonical, u64>,
+        builder: &mut Builder,
+    ) -> Guard<T>
+    where
+        T: Clone,
+    {
+        match self {
+            Guard::And(l, r) => Guard::And(
+                Box::new(l.expand_const_ports(const_drivers, builder)),
+                Box::new(r.expand_const_ports(const_drivers, builder)),
+            ),
+            Guard::Or(l, r) => Guard::Or(
+                Box::new(l.expand_const_ports(const_drivers, builder)),
+                Box::new(r.expand_const_ports(const_drivers, builder)),
+            ),
+            Guard::Implies(l, r) => Guard::Implies(
+                Box::new(l.expand_const_ports(const_drivers, builder)),
+                Box::new(r.expand_const_ports(const_drivers, builder)),
+            ),
+            Guard::Not(g) => Guard::Not(Box::new(
+                g.expand_const_ports(const_drivers, builder),
+            )),
+            Guard::CompOp(op, l, r) => {
+                let mut inline = |port: &RRC<Port>| {
+                    let port = port.borrow();
+                    let val = const_drivers.get(&port.canonical())?;
+                    let cell = builder.add_constant(*val, port.width);
+                    let out = cell.borrow().get("out");
+                    Some(out)
+                };
+                let nl = inline(l).unwrap_or_else(|| Rc::clone(l));
+                let nr = inline(r).unwrap_or_else(|| Rc::clone(r));
+                Guard::CompOp(op.clone(), nl, nr)
+            }
+            Guard::Port(p) => match const_drivers.get(&p.borrow().canonical()) {
+                Some(0) => Guard::Not(Box::new(Guard::True)),
+                Some(_) => Guard::True,
+                None => Guard::Port(Rc::clone(p)),
+            },
+            Guard::True => Guard::True,
+            Guard::Info(i) => Guard::Info(i.clone()),
+        }
+    }
+
+    /// Replace the ports of the invoked component's `signature` cell with the
+    /// ports bound to them by the invoke, identified by the name of the port
+    /// in the signature. Ports of other cells, including the signature of the
+    /// invoking component, and signature ports without a binding are left
+    /// unchanged.
+    pub fn rebind_invoke_ports(
+        &mut self,
+        signature: &RRC<Cell>,
+        bindings: &HashMap<Id, RRC<Port>>,
+    ) {
+        self.for_each(&mut |port| {
+            let port = port.borrow();
+            let PortParent::Cell(cell) = &port.parent else {
+                return None;
+            };
+            if !Rc::ptr_eq(&cell.upgrade(), signature) {
+                return None;
+            }
+            let actual = bindings.get(&port.name)?;
+            Some(Guard::Port(Rc::clone(actual)))
+        })
+    }
+
+    /// Replace every occurrence of the port `old`, including in comparisons,
+    /// with `new`.
+    pub fn replace_port(&mut self, old: &RRC<Port>, new: &RRC<Port>) {
+        self.for_each(&mut |port| {
+            Rc::ptr_eq(&port, old).then(|| Guard::Port(Rc::clone(new)))
+        })
+    }
+
+    /// Repoint every port of a cell `c` whose copy, the cell named `c`
+    /// followed by `suffix`, is in `cells` to the port with the same name on
+    /// the copy, including in comparisons. Ports of the component's
+    /// signature and of cells without a copy are left alone.
+    pub fn rename_ports_by_suffix(
+        &mut self,
+        suffix: &str,
+        cells: &HashMap<Id, RRC<Cell>>,
+    ) {
+        self.for_each(&mut |port| {
+            let port = port.borrow();
+            let PortParent::Cell(cell) = &port.parent else {
+                return None;
+            };
+            let cell = cell.upgrade();
+            let cell = cell.borrow();
+            if cell.is_this() {
+                return None;
+            }
+            let name = Id::from(format!("{}{suffix}", cell.name()));
+            let copy = cells.get(&name)?.borrow().find(port.name)?;
+            Some(Guard::Port(copy))
+        })
+    }
+
+    /// Mutates a guard by calling `f` on every leaf in the
+    /// guard tree and replacing the leaf with the guard that `f`
+    /// returns.
+    pub fn for_each<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(RRC<Port>) -> Option<Guard<T>>,
+    {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                l.for_each(f);
+                r.for_each(f);
+            }
+            Guard::Not(inner) => {
+                inner.for_each(f);
+            }
+            Guard::CompOp(_, l, r) => {
+                match f(Rc::clone(l)) {
+                    Some(Guard::Port(p)) => *l = p,
+                    Some(_) => unreachable!(
+                        "Cannot replace port inside comparison operator"
+                    ),
+                    None => {}
+                }
+                match f(Rc::clone(r)) {
+                    Some(Guard::Port(p)) => *r = p,
+                    Some(_) => unreachable!(
+                        "Cannot replace port inside comparison operator"
+                    ),
+                    None => {}
+                }
+            }
+            Guard::Port(port) => {
+                let guard = f(Rc::clone(port))
+                    .unwrap_or_else(|| Guard::port(Rc::clone(port)));
+                *self = guard;
+            }
+            Guard::True => {}
+            Guard::Info(_) =>
+                // Info shouldn't count as port
+                {}
+        }
+    }
+
+    /// runs f(info) on each Guard::Info in `guard`.
+    /// if `f(info)` = Some(result)` replaces interval with result.
+    /// if `f(info)` = None` does nothing.
+    pub fn for_each_info<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&mut T) -> Option<Guard<T>>,
+    {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                l.for_each_info(f);
+                r.for_each_info(f);
+            }
+            Guard::Not(inner) => {
+                inner.for_each_info(f);
+            }
+            Guard::True | Guard::Port(_) | Guard::CompOp(_, _, _) => {}
+            Guard::Info(timing_interval) => {
+                if let Some(new_interval) = f(timing_interval) {
+                    *self = new_interval
+                }
+            }
+        }
+    }
+
+    /// runs f(info) on each info in `guard`.
+    /// f should return Result<(), Error>, meaning that it essentially does
+    /// nothing if the `f` returns OK(()), but returns an appropraite error otherwise
+    pub fn check_for_each_info<F>(&self, f: &mut F) -> Result<(), Error>
+    where
+        F: Fn(&T) -> Result<(), Error>,
+    {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                let l_result = l.check_for_each_info(f);
+                if l_result.is_err() {
+                    l_result
+                } else {
+                    r.check_for_each_info(f)
+                }
+            }
+            Guard::Not(inner) => inner.check_for_each_info(f),
+            Guard::True | Guard::Port(_) | Guard::CompOp(_, _, _) => Ok(()),
+            Guard::Info(timing_interval) => f(timing_interval),
+        }
+    }
+}
+
+/// JSON schema for the output of [Guard::to_json].
+#[cfg(feature = "serialize")]
+pub const GUARD_JSON_SCHEMA: &str = r##"{
+  "$schema": "http://json-schema.org/draft-07/schema#",
+  "title": "Guard",
+  "$ref": "#/definitions/guard",
+  "definitions": {
+    "guard": {
+      "oneOf": [
+        {
+          "type": "object",
+          "properties": {
+            "op": { "enum": ["and", "or", "implies", "not"] },
+            "children": {
+              "type": "array",
+              "items": { "$ref": "#/definitions/guard" }
+            }
+          },
+          "required": ["op", "children"]
+        },
+        {
+          "type": "object",
+          "properties": {
+            "op": { "enum": ["eq", "neq", "gt", "lt", "geq", "leq"] },
+            "operands": {
+              "type": "array",
+              "items": { "$ref": "#/definitions/operand" },
+              "minItems": 2,
+              "maxItems": 2
+            }
+          },
+          "required": ["op", "operands"]
+        },
+        {
+          "type": "object",
+          "properties": {
+            "op": { "const": "port" },
+            "port": { "$ref": "#/definitions/operand" }
+          },
+          "required": ["op", "port"]
+        },
+        {
+          "type": "object",
+          "properties": { "op": { "const": "true" } },
+          "required": ["op"]
+        },
+        {
+          "type": "object",
+          "properties": {
+            "op": { "const": "info" },
+            "value": { "type": "string" }
+          },
+          "required": ["op", "value"]
+        }
+      ]
+    },
+    "operand": {
+      "oneOf": [
+        {
+          "type": "object",
+          "properties": {
+            "cell": { "type": "string" },
+            "port": { "type": "string" }
+          },
+          "required": ["cell", "port"]
+        },
+        {
+          "type": "object",
+          "properties": {
+            "value": { "type": "integer" },
+            "width": { "type": "integer" }
+          },
+          "required": ["value", "width"]
+        }
+      ]
+    }
+  }
+}"##;
+
+#[cfg(feature = "serialize")]
+impl<T: ToString> Guard<T> {
+    /// Export the guard as JSON for external tools. Every node is an object
+    /// with an `op` field, the children of logical operators are stored in
+    /// `children`, and the ports compared by comparison operators in
+    /// `operands`. Ports are represented as `{"cell": .., "port": ..}` and
+    /// constants as `{"value": .., "width": ..}`. See [GUARD_JSON_SCHEMA] for
+    /// the complete schema.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::json;
+        fn port_json(port: &RRC<Port>) -> serde_json::Value {
+            let port = port.borrow();
+            match port.constant_value() {
+                Some(val) => json!({ "value": val, "width": port.width }),
+                None => json!({
+                    "cell": port.get_parent_name().as_ref(),
+                    "port": port.name.as_ref(),
+                }),
+            }
+        }
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                json!({
+                    "op": self.op_str_json(),
+                    "children": [l.to_json(), r.to_json()],
+                })
+            }
+            Guard::Not(g) => json!({ "op": "not", "children": [g.to_json()] }),
+            Guard::CompOp(_, l, r) => json!({
+                "op": self.op_str_json(),
+                "operands": [port_json(l), port_json(r)],
+            }),
+            Guard::Port(p) => json!({ "op": "port", "port": port_json(p) }),
+            Guard::True => json!({ "op": "true" }),
+            Guard::Info(i) => json!({ "op": "info", "value": i.to_string() }),
+        }
+    }
+
+    /// Name of the operator used in the JSON export.
+    fn op_str_json(&self) -> &'static str {
+        match self {
+            Guard::And(..) => "and",
+            Guard::Or(..) => "or",
+            Guard::Implies(..) => "implies",
+            Guard::Not(..) => "not",
+            Guard::CompOp(op, ..) => match op {
+                PortComp::Eq => "eq",
+                PortComp::Neq => "neq",
+                PortComp::Gt => "gt",
+                PortComp::Lt => "lt",
+                PortComp::Geq => "geq",
+                PortComp::Leq => "leq",
+            },
+            Guard::Port(_) => "port",
+            Guard::True => "true",
+            Guard::Info(_) => "info",
+        }
+    }
+}
+
+impl Guard<StaticTiming> {
+    /// updates self -> self & interval
+    pub fn add_interval(&mut self, timing_interval: StaticTiming) {
+        self.update(|g| g.and(Guard::Info(timing_interval)));
+    }
+
+    /// The end of the last cycle interval mentioned by the guard, i.e., the
+    /// guard can only be active before this cycle if it is gated by the
+    /// interval. Returns `None` if the guard is not gated by any cycle.
+    pub fn max_cycle(&self) -> Option<u64> {
+        match self {
+            Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+                match (l.max_cycle(), r.max_cycle()) {
+                    (Some(l), Some(r)) => Some(l.max(r)),
+                    (l, r) => l.or(r),
+                }
+            }
+            Guard::Not(g) => g.max_cycle(),
+            Guard::Info(timing) => Some(timing.get_interval().1),
+            Guard::True | Guard::CompOp(..) | Guard::Port(_) => None,
+        }
+    }
+}
+
+/// Exact reasoning about the boolean structure of guards with binary decision
+/// diagrams. Ports, comparisons and infos are treated as independent
+/// booleans, except that comparisons match with their operands swapped and
+/// are the opposite of their complement, so `x < 8` is `8 > x` and the
+/// negation of `x >= 8`.
+#[cfg(feature = "bdd")]
+impl<T: Clone + Eq> Guard<T> {
+    /// Whether the guards have the same value for every assignment to their
+    /// atoms.
+    pub fn equiv_exact(&self, other: &Guard<T>) -> bool {
+        let (_, f, g) = self.bdd_pair(other);
+        f == g
+    }
+
+    /// Whether `other` is true whenever this guard is. Unlike
+    /// [Guard::implies], the answer is exact over the boolean structure but
+    /// ignores the meaning of comparisons, so `x == 1` does not imply `x < 2`.
+    pub fn implies_exact(&self, other: &Guard<T>) -> bool {
+        let (mut bdd, f, g) = self.bdd_pair(other);
+        let not_g = bdd.not(g);
+        bdd.and(f, not_g) == Bdd::FALSE
+    }
+
+    /// Whether the guards are never true at the same time.
+    pub fn exclusive_exact(&self, other: &Guard<T>) -> bool {
+        let (mut bdd, f, g) = self.bdd_pair(other);
+        bdd.and(f, g) == Bdd::FALSE
+    }
+
+    /// The diagrams of the two guards over the same variables.
+    fn bdd_pair(&self, other: &Guard<T>) -> (Bdd, BddNode, BddNode) {
+        let (mut atoms, mut bdd) = (vec![], Bdd::default());
+        let f = self.to_bdd(&mut atoms, &mut bdd);
+        let g = other.to_bdd(&mut atoms, &mut bdd);
+        (bdd, f, g)
+    }
+
+    /// The diagram of the guard in which the leaf `atoms[k]` is variable `k`.
+    /// Leaves that are not in `atoms` are added to it.
+    fn to_bdd(&self, atoms: &mut Vec<Guard<T>>, bdd: &mut Bdd) -> BddNode {
+        match self {
+            Guard::And(l, r) => {
+                let (l, r) = (l.to_bdd(atoms, bdd), r.to_bdd(atoms, bdd));
+                bdd.and(l, r)
+            }
+            Guard::Or(l, r) => {
+                let (l, r) = (l.to_bdd(atoms, bdd), r.to_bdd(atoms, bdd));
+                bdd.or(l, r)
+            }
+            Guard::Implies(l, r) => {
+                let (l, r) = (l.to_bdd(atoms, bdd), r.to_bdd(atoms, bdd));
+                let not_l = bdd.not(l);
+                bdd.or(not_l, r)
+            }
+            Guard::Not(g) => {
+                let g = g.to_bdd(atoms, bdd);
+                bdd.not(g)
+            }
+            Guard::True => Bdd::TRUE,
+            leaf => {
+                let is = |a: &Guard<T>, g: &Guard<T>| {
+                    a == g || g.swap_operands().map_or(false, |s| &s == a)
+                };
+                let complement = !leaf.clone();
+                if let Some(k) = atoms.iter().position(|a| is(a, leaf)) {
+                    bdd.var(k)
+                } else if let Some(k) =
+                    atoms.iter().position(|a| is(a, &complement))
+                {
+                    let var = bdd.var(k);
+                    bdd.not(var)
+                } else {
+                    atoms.push(leaf.clone());
+                    bdd.var(atoms.len() - 1)
+                }
+            }
+        }
+    }
+}
+
+/// Construct guards from ports
+impl<T> From<RRC<Port>> for Guard<T> {
+    fn from(port: RRC<Port>) -> Self {
+        Guard::Port(Rc::clone(&port))
+    }
+}
+
+impl<T> PartialEq for Guard<T>
+where
+    T: Eq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Guard::Or(la, ra), Guard::Or(lb, rb))
+            | (Guard::And(la, ra), Guard::And(lb, rb))
+            | (Guard::Implies(la, ra), Guard::Implies(lb, rb)) => {
+                la == lb && ra == rb
+            }
+            (Guard::CompOp(opa, la, ra), Guard::CompOp(opb, lb, rb)) => {
+                (opa == opb)
+                    && (la.borrow().get_parent_name(), &la.borrow().name)
+                        == (lb.borrow().get_parent_name(), &lb.borrow().name)
+                    && (ra.borrow().get_parent_name(), &ra.borrow().name)
+                        == (rb.borrow().get_parent_name(), &rb.borrow().name)
+            }
+            (Guard::Not(a), Guard::Not(b)) => a == b,
+            (Guard::Port(a), Guard::Port(b)) => {
+                (a.borrow().get_parent_name(), &a.borrow().name)
+                    == (b.borrow().get_parent_name(), &b.borrow().name)
+            }
+            (Guard::True, Guard::True) => true,
+            (Guard::Info(i1), Guard::Info(i2)) => i1 == i2,
+            _ => false,
+        }
+    }
+}
+
+impl<T> Eq for Guard<T> where T: Eq {}
+
+/// Structural equality that identifies ports by identity. `==` on guards
+/// compares ports by the names of the port and its parent, so ports of
+/// distinct cells with the same name, such as the cells of two different
+/// components, are equal. `struct_eq` only considers two ports equal if
+/// they are the same port.
+pub trait StructEq {
+    fn struct_eq(&self, other: &Self) -> bool;
+}
+
+impl<T> StructEq for Guard<T>
+where
+    T: Eq,
+{
+    fn struct_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Guard::Or(la, ra), Guard::Or(lb, rb))
+            | (Guard::And(la, ra), Guard::And(lb, rb))
+            | (Guard::Implies(la, ra), Guard::Implies(lb, rb)) => {
+                la.struct_eq(lb) && ra.struct_eq(rb)
+            }
+            (Guard::CompOp(opa, la, ra), Guard::CompOp(opb, lb, rb)) => {
+                opa == opb && Rc::ptr_eq(la, lb) && Rc::ptr_eq(ra, rb)
+            }
+            (Guard::Not(a), Guard::Not(b)) => a.struct_eq(b),
+            (Guard::Port(a), Guard::Port(b)) => Rc::ptr_eq(a, b),
+            (Guard::True, Guard::True) => true,
+            (Guard::Info(i1), Guard::Info(i2)) => i1 == i2,
+            _ => false,
+        }
+    }
+}
+
+/// Define order on guards
+impl<T> PartialOrd for Guard<T>
+where
+    T: Eq,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Define an ordering on the precedence of guards. Guards are
 /// considered equal when they have the same precedence.
 impl<T> Ord for Guard<T>
 where
@@ -537,13 +2716,16 @@ where
 {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
-            (Guard::Or(..), Guard::Or(..))
+            (Guard::Implies(..), Guard::Implies(..))
+            | (Guard::Or(..), Guard::Or(..))
             | (Guard::And(..), Guard::And(..))
             | (Guard::CompOp(..), Guard::CompOp(..))
             | (Guard::Not(..), Guard::Not(..))
             | (Guard::Port(..), Guard::Port(..))
             | (Guard::Info(_), Guard::Info(_))
             | (Guard::True, Guard::True) => Ordering::Equal,
+            (Guard::Implies(..), _) => Ordering::Greater,
+            (_, Guard::Implies(..)) => Ordering::Less,
             (Guard::Or(..), _) => Ordering::Greater,
             (_, Guard::Or(..)) => Ordering::Less,
             (Guard::And(..), _) => Ordering::Greater,
@@ -603,10 +2785,14 @@ where
     }
 }
 
-/// Construct a Guard::Or:
+/// Negate a Guard:
 /// ```
 /// let not_guard = !g1;
 /// ```
+/// The negation is pushed into the guard when there is a smaller form:
+/// comparisons are flipped (`!(a < b)` is `a >= b`), double negations are
+/// removed, and the negation of an implication `a -> b` is `a & !b`.
+/// Other guards are wrapped in a `Guard::Not`.
 impl<T> Not for Guard<T> {
     type Output = Self;
 
@@ -631,6 +2817,7 @@ impl<T> Not for Guard<T> {
                 Guard::CompOp(PortComp::Gt, lhs, rhs)
             }
             Guard::Not(expr) => *expr,
+            Guard::Implies(lhs, rhs) => Guard::And(lhs, Box::new(!*rhs)),
             _ => Guard::Not(Box::new(self)),
         }
     }
@@ -661,3 +2848,1409 @@ where
         self.update(|old| old & other)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rrc, Attributes, Builder, Cell, CellType, Component, Direction, Id,
+        LibrarySignatures, PortParent, WRC,
+    };
+
+    /// Construct a cell with the given output ports.
+    fn cell_with(
+        name: &str,
+        prototype: CellType,
+        ports: &[(&str, u64)],
+    ) -> RRC<Cell> {
+        let cell = rrc(Cell::new(Id::from(name), prototype));
+        for (port, width) in ports {
+            let port = rrc(Port {
+                name: Id::from(*port),
+                width: *width,
+                direction: Direction::Output,
+                parent: PortParent::Cell(WRC::from(&cell)),
+                attributes: Attributes::default(),
+            });
+            cell.borrow_mut().ports.push(port);
+        }
+        cell
+    }
+
+    /// A primitive cell with the given output ports.
+    fn cell(name: &str, ports: &[(&str, u64)]) -> RRC<Cell> {
+        let prototype = CellType::Primitive {
+            name: Id::from("std_reg"),
+            param_binding: Box::default(),
+            is_comb: false,
+            latency: None,
+        };
+        cell_with(name, prototype, ports)
+    }
+
+    /// A constant cell with an `out` port. Ports only hold a weak reference
+    /// to their cell, so the cell must outlive the guards that read it.
+    fn constant(val: u64, width: u64) -> RRC<Cell> {
+        cell_with(
+            &Cell::constant_name(val, width).to_string(),
+            CellType::Constant { val, width },
+            &[("out", width)],
+        )
+    }
+
+    /// A guard that reads the given port of the cell.
+    fn port(cell: &RRC<Cell>, name: &str) -> Guard<Nothing> {
+        Guard::port(cell.borrow().get(name))
+    }
+
+    #[test]
+    fn count_ops_mixed() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("c", 1), ("x", 32)]);
+        let y = cell("y", &[("out", 32)]);
+        let g = (port(&c, "a") & port(&c, "b") & !port(&c, "c"))
+            | port(&c, "x").lt(port(&y, "out"))
+            | port(&c, "x").eq(port(&y, "out"));
+        let counts = g.count_ops();
+        assert_eq!(
+            counts,
+            GuardOpCounts {
+                and: 2,
+                or: 2,
+                not: 1,
+                lt: 1,
+                eq: 1,
+                ..Default::default()
+            }
+        );
+
+        let mut total = counts;
+        total += port(&c, "a").count_ops();
+        total += (!port(&c, "a")).count_ops();
+        assert_eq!(total.not, 2);
+        assert_eq!(total.and, 2);
+    }
+
+    #[test]
+    fn all_any_high() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("c", 1)]);
+        let ports = |names: &[&str]| {
+            names.iter().map(|n| c.borrow().get(*n)).collect::<Vec<_>>()
+        };
+
+        assert!(Guard::<Nothing>::all_high(ports(&[])).is_true());
+        assert!(Guard::<Nothing>::any_high(ports(&[])).is_false());
+
+        assert_eq!(Guard::all_high(ports(&["a"])), port(&c, "a"));
+        assert_eq!(Guard::any_high(ports(&["a"])), port(&c, "a"));
+
+        assert_eq!(
+            Guard::all_high(ports(&["a", "b", "c"])),
+            port(&c, "a") & port(&c, "b") & port(&c, "c")
+        );
+        assert_eq!(
+            Guard::any_high(ports(&["a", "b", "c"])),
+            port(&c, "a") | port(&c, "b") | port(&c, "c")
+        );
+    }
+
+    #[test]
+    fn priority_guards() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("x", 4)]);
+        let y = cell("y", &[("out", 4)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let lt = port(&c, "x").lt(port(&y, "out"));
+        let ordered = [a.clone() | b.clone(), b.clone(), lt.clone()];
+
+        let disjoint = Guard::priority_guards(&ordered);
+        assert_eq!(disjoint[0], a.clone() | b.clone());
+        assert_eq!(disjoint[1], b.clone() & !(a.clone() | b.clone()));
+        assert_eq!(
+            disjoint[2],
+            lt.clone() & !(a.clone() | b.clone()) & !b.clone()
+        );
+
+        let disjoint = Guard::priority_guards(&[a.clone(), Guard::True, b]);
+        assert_eq!(disjoint[0], a.clone());
+        assert_eq!(disjoint[1], !a);
+        assert!(disjoint[2].is_false());
+    }
+
+    #[test]
+    fn rebind_invoke_ports() {
+        let callee = cell_with(
+            "callee",
+            CellType::ThisComponent,
+            &[("in", 32), ("go", 1)],
+        );
+        let caller = cell_with("main", CellType::ThisComponent, &[("go", 1)]);
+        let c = cell("c", &[("in", 32)]);
+        let r = cell("r", &[("out", 32)]);
+        let mut g =
+            port(&callee, "in").eq(port(&c, "in")) & port(&callee, "go");
+
+        let bindings = HashMap::from([(Id::from("in"), r.borrow().get("out"))]);
+        g.rebind_invoke_ports(&callee, &bindings);
+        assert_eq!(g, port(&r, "out").eq(port(&c, "in")) & port(&callee, "go"));
+
+        // Only the ports of the callee are rebound, even when the caller has
+        // a port with the same name.
+        let mut g = port(&callee, "go") & port(&caller, "go");
+        let bindings = HashMap::from([(Id::from("go"), r.borrow().get("out"))]);
+        g.rebind_invoke_ports(&callee, &bindings);
+        assert_eq!(g, port(&r, "out") & port(&caller, "go"));
+    }
+
+    #[test]
+    fn rename_ports_by_suffix() {
+        let this = cell_with("main", CellType::ThisComponent, &[("go", 1)]);
+        let a = cell("a", &[("out", 8), ("done", 1)]);
+        let b = cell("b", &[("out", 8)]);
+        let c = cell("c", &[("out", 1)]);
+        let a_copy = cell("a_copy", &[("out", 8), ("done", 1)]);
+        let b_copy = cell("b_copy", &[("out", 8)]);
+        let cells = HashMap::from([
+            (Id::from("a_copy"), Rc::clone(&a_copy)),
+            (Id::from("b_copy"), Rc::clone(&b_copy)),
+        ]);
+
+        let mut g = port(&a, "out").lt(port(&b, "out"))
+            & port(&a, "done")
+            & port(&c, "out")
+            & port(&this, "go");
+        g.rename_ports_by_suffix("_copy", &cells);
+        assert_eq!(
+            g,
+            port(&a_copy, "out").lt(port(&b_copy, "out"))
+                & port(&a_copy, "done")
+                & port(&c, "out")
+                & port(&this, "go")
+        );
+    }
+
+    #[test]
+    fn minimize() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+        let g = (a.clone() & b.clone())
+            | (a.clone() & !b.clone())
+            | (!a.clone() & b.clone());
+
+        let min = g.minimize(4);
+        assert_eq!(min, a.clone() | b.clone());
+        assert!(min.size() < g.canonicalize().size());
+        let atoms = [a.clone(), b.clone()];
+        assert_eq!(min.truth_table(&atoms, 2), g.truth_table(&atoms, 2));
+
+        let g = (a.clone() & d.clone()) | (!a.clone() & d.clone()) | b.clone();
+        assert_eq!(g.minimize(4), d.clone() | b.clone());
+        assert_eq!(g.minimize(2), g.canonicalize());
+        assert!((a.clone() & !a.clone()).minimize(4).is_false());
+        assert!((a.clone() | !a).minimize(4).is_true());
+    }
+
+    #[test]
+    fn assume_port() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+        let g = (a.clone() & b.clone()) | (!a.clone() & d.clone());
+
+        assert_eq!(g.assume(&a, true), b);
+        assert_eq!(g.assume(&a, false), d);
+        assert_eq!(g.assume(&b, false), !a.clone() & d.clone());
+        assert!((a.clone() | b.clone()).assume(&a, true).is_true());
+        assert!((a.clone() & b).assume(&a, false).is_false());
+    }
+
+    #[test]
+    fn assume_comparison() {
+        let x = cell("x", &[("out", 4)]);
+        let eight = cell("eight", &[("out", 4)]);
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let lt = port(&x, "out").lt(port(&eight, "out"));
+        let ge = port(&x, "out").ge(port(&eight, "out"));
+        let gt_swapped = port(&eight, "out").gt(port(&x, "out"));
+
+        let g = (ge & a.clone()) | b.clone();
+        assert_eq!(g.assume(&lt, true), b);
+        assert_eq!(g.assume(&lt, false), a.clone() | b);
+        assert_eq!((gt_swapped & a.clone()).assume(&lt, true), a);
+    }
+
+    #[test]
+    fn is_monotone_in() {
+        let x = cell("x", &[("out", 4)]);
+        let five = cell("five", &[("out", 4)]);
+        let c = cell("c", &[("a", 1)]);
+        let out = x.borrow().get("out");
+        let (xs, five) = (port(&x, "out"), port(&five, "out"));
+        let a = port(&c, "a");
+
+        let ge = xs.clone().ge(five.clone());
+        let lt = xs.clone().lt(five.clone());
+        assert_eq!(ge.is_monotone_in(&out), Some(true));
+        assert_eq!(lt.is_monotone_in(&out), Some(false));
+        assert_eq!(xs.clone().eq(five.clone()).is_monotone_in(&out), None);
+
+        assert_eq!(five.gt(xs).is_monotone_in(&out), Some(false));
+        assert_eq!((ge.clone() & a.clone()).is_monotone_in(&out), Some(true));
+        assert_eq!((!ge.clone() | a.clone()).is_monotone_in(&out), Some(false));
+        assert_eq!((ge | lt).is_monotone_in(&out), None);
+        assert_eq!(a.is_monotone_in(&out), Some(true));
+    }
+
+    #[test]
+    fn implies_guard() {
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let atoms = [a.clone(), b.clone()];
+
+        let imp = a.clone().implies_guard(b.clone());
+        assert_eq!(imp.clone().expand_implies(), !a.clone() | b.clone());
+        assert_eq!(
+            imp.canonical_key(),
+            (!a.clone() | b.clone()).canonical_key()
+        );
+        assert_eq!(
+            imp.truth_table(&atoms, 2),
+            Some(vec![true, true, false, true])
+        );
+        assert_eq!((!imp.clone()).to_nnf(), a.clone() & !b.clone());
+        assert_eq!(
+            (!imp).truth_table(&atoms, 2),
+            (a & !b).truth_table(&atoms, 2)
+        );
+    }
+
+    #[test]
+    fn absorb() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+
+        assert_eq!((a.clone() | (a.clone() & b.clone())).absorb(), a);
+        assert_eq!(((a.clone() | b.clone()) & a.clone()).absorb(), a);
+        let nested = b.clone() | (a.clone() & (a.clone() | d.clone()));
+        assert_eq!(nested.absorb(), a.clone() | b.clone());
+
+        let g = (a.clone() & b.clone()) | (a.clone() & d.clone());
+        assert_eq!(g.absorb(), g.canonicalize());
+        assert_eq!(g.absorb().absorb(), g.absorb());
+        assert_eq!(nested.absorb().absorb(), nested.absorb());
+    }
+
+    #[test]
+    fn simplify_with_equalities() {
+        let a = cell("a", &[("out", 8)]);
+        let b = cell("b", &[("out", 8)]);
+        let c = cell("c", &[("out", 8), ("f", 1)]);
+        let (a_out, b_out) = (port(&a, "out"), port(&b, "out"));
+        let (c_out, f) = (port(&c, "out"), port(&c, "f"));
+
+        let mut eqs = UnionFind::default();
+        let key = |cell: &str| Canonical::new(Id::from(cell), Id::from("out"));
+        eqs.union(&key("a"), &key("b"));
+
+        let eq = a_out.clone().eq(b_out.clone());
+        assert!(eq.simplify_with_equalities(&eqs).is_true());
+        let lt = b_out.clone().lt(a_out.clone());
+        assert_eq!((lt | f.clone()).simplify_with_equalities(&eqs), f);
+        let other = a_out.eq(c_out);
+        assert_eq!(other.simplify_with_equalities(&eqs), other);
+        assert_eq!(eq.simplify_with_equalities(&UnionFind::default()), eq);
+    }
+
+    #[test]
+    fn not_laws() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+        let cmp = |op: PortComp| {
+            let (Guard::Port(l), Guard::Port(r)) = (a.clone(), b.clone())
+            else {
+                unreachable!()
+            };
+            Guard::CompOp(op, l, r)
+        };
+        let flips = [
+            (PortComp::Eq, PortComp::Neq),
+            (PortComp::Neq, PortComp::Eq),
+            (PortComp::Gt, PortComp::Leq),
+            (PortComp::Lt, PortComp::Geq),
+            (PortComp::Geq, PortComp::Lt),
+            (PortComp::Leq, PortComp::Gt),
+        ];
+        for (op, flipped) in flips.iter().cloned() {
+            assert_eq!(!cmp(op), cmp(flipped));
+        }
+        assert_eq!(!!a.clone(), a);
+        let imp = a.clone().implies_guard(b.clone().lt(d.clone()));
+        assert_eq!(!imp, a.clone() & b.clone().ge(d.clone()));
+        // Only guards built as implications are negated as one.
+        let or = !a.clone() | b.clone();
+        assert_eq!(!or.clone(), Guard::Not(Box::new(or)));
+
+        let mut guards: Vec<Guard<Nothing>> = vec![
+            Guard::True,
+            !Guard::True,
+            a.clone(),
+            !a.clone(),
+            a.clone() & b.clone(),
+            a.clone() | b.clone(),
+            a.clone().implies_guard(b.clone()),
+            a.clone().implies_guard(!b.clone() | d.clone()),
+            !a.clone() | (b.clone() & d.clone()),
+            (a.clone() | b.clone()).implies_guard(d.clone()),
+        ];
+        guards.extend(flips.iter().map(|(op, _)| cmp(op.clone())));
+        for g in guards {
+            let neg = !g.clone();
+            let msg = Printer::guard_str(&neg);
+            assert!(neg.size() <= g.size() + 1, "{msg}");
+            for bits in 0..8u8 {
+                let env = [
+                    ("a", bits & 1 != 0),
+                    ("b", bits & 2 != 0),
+                    ("d", bits & 4 != 0),
+                ];
+                assert_eq!(eval(&neg, &env), !eval(&g, &env));
+            }
+        }
+    }
+
+    #[test]
+    fn comparison_constants() {
+        let x = cell("x", &[("out", 4)]);
+        let y = cell("y", &[("out", 1)]);
+        let consts: Vec<_> =
+            [3, 8].into_iter().map(|v| constant(v, 4)).collect();
+        let k = |i: usize| port(&consts[i], "out");
+        let x_out = port(&x, "out");
+        let key = x.borrow().get("out").borrow().canonical();
+
+        let g = x_out.clone().lt(k(1))
+            & !k(0).eq(x_out.clone())
+            & (port(&y, "out") | x_out.lt(k(1)));
+        assert_eq!(g.comparison_constants(), vec![(key.clone(), 8), (key, 3)]);
+        assert!(port(&y, "out").comparison_constants().is_empty());
+    }
+
+    #[test]
+    fn replace_port() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+        let (old, new) = (c.borrow().get("a"), c.borrow().get("d"));
+
+        let mut g = a.clone() & (b.clone() | a.clone().lt(b.clone()));
+        g.replace_port(&old, &new);
+        assert_eq!(g, d.clone() & (b.clone() | d.lt(b.clone())));
+        let mut g = b.clone();
+        g.replace_port(&old, &new);
+        assert_eq!(g, b);
+    }
+
+    #[test]
+    fn struct_eq() {
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let other = cell("c", &[("a", 1), ("b", 1)]);
+        let g = port(&c, "a") & port(&c, "a").lt(port(&c, "b"));
+        let same = port(&c, "a") & port(&c, "a").lt(port(&c, "b"));
+        let renamed = port(&other, "a") & port(&other, "a").lt(port(&c, "b"));
+
+        assert!(g == same && g.struct_eq(&same));
+        // The ports of `other` have the same names as the ports of `c`.
+        assert!(g == renamed);
+        assert!(!g.struct_eq(&renamed));
+        let swapped = port(&c, "a").lt(port(&c, "b")) & port(&c, "a");
+        assert!(g != swapped && !g.struct_eq(&swapped));
+    }
+
+    #[test]
+    fn rewrite_with() {
+        let m = cell("m", &[("a", 1), ("b", 1)]);
+        let (a, b) = (port(&m, "a"), port(&m, "b"));
+        let c = cell("c", &[("x", 1), ("y", 1), ("z", 1)]);
+        let (x, y, z) = (port(&c, "x"), port(&c, "y"), port(&c, "z"));
+
+        let de_morgan = GuardRule {
+            pattern: Guard::Not(Box::new(a.clone() & b.clone())),
+            replacement: !a.clone() | !b.clone(),
+        };
+        let g = Guard::Not(Box::new(x.clone() & y.clone().lt(z.clone())));
+        assert_eq!(
+            g.rewrite_with(&[de_morgan.clone()]).unwrap(),
+            !x.clone() | y.clone().ge(z.clone())
+        );
+
+        let absorption = GuardRule {
+            pattern: a.clone() | (a.clone() & b.clone()),
+            replacement: a.clone(),
+        };
+        let g = (y.clone() & x.clone()) | x.clone();
+        assert_eq!(g.rewrite_with(&[absorption.clone()]).unwrap(), x);
+        // Both occurrences of `a` must match the same leaf.
+        let g = z.clone() | (x.clone() & y.clone());
+        assert_eq!(g.rewrite_with(&[absorption]).unwrap(), g.canonicalize());
+        // Metavariables only match leaves.
+        let g = Guard::Not(Box::new((x.clone() | y.clone()) & z.clone()));
+        assert_eq!(g.rewrite_with(&[de_morgan]).unwrap(), g.canonicalize());
+
+        // A comparison in the replacement needs ports for its operands.
+        let compare = GuardRule {
+            pattern: a.clone() & b.clone(),
+            replacement: a.lt(b),
+        };
+        let g = x & y.lt(z);
+        assert!(g.rewrite_with(&[compare]).is_err());
+    }
+
+    #[test]
+    fn infer_widths() {
+        let c = cell("c", &[("a", 1), ("x", 4), ("y", 4), ("z", 8)]);
+        let (a, x) = (port(&c, "a"), port(&c, "x"));
+        let (y, z) = (port(&c, "y"), port(&c, "z"));
+        let no_overrides = HashMap::new();
+
+        let cmps = x.clone().lt(y.clone()) | !x.clone().eq(y.clone());
+        let g = a.clone() & cmps;
+        let widths = g.infer_widths(&no_overrides).unwrap();
+        assert_eq!(widths.result, 1);
+        assert_eq!(widths.comparisons, vec![4, 4]);
+
+        let g = a.clone() & x.clone().lt(z);
+        let err = g.infer_widths(&no_overrides).unwrap_err();
+        let msg = err.message();
+        assert!(msg.contains("`c.x < c.z' has operands of widths 4 and 8"));
+
+        let key = c.borrow().get("y").borrow().canonical();
+        let overrides = HashMap::from([(key, 2)]);
+        assert!(x.lt(y.clone()).infer_widths(&overrides).is_err());
+        assert!(y.infer_widths(&no_overrides).is_err());
+    }
+
+    #[test]
+    fn max_port_width() {
+        let c = cell("c", &[("a", 1), ("x", 32), ("y", 32)]);
+        let (a, x, y) = (port(&c, "a"), port(&c, "x"), port(&c, "y"));
+        let no_overrides = HashMap::new();
+
+        assert_eq!(Guard::<Nothing>::True.max_port_width(&no_overrides), 1);
+        assert_eq!(a.max_port_width(&no_overrides), 1);
+        let g = a & x.lt(y);
+        assert_eq!(g.max_port_width(&no_overrides), 32);
+
+        let key = c.borrow().get("y").borrow().canonical();
+        let overrides = HashMap::from([(key, 64)]);
+        assert_eq!(g.max_port_width(&overrides), 64);
+    }
+
+    #[cfg(feature = "bdd")]
+    #[test]
+    fn exact_reasoning() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("x", 4), ("y", 4)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let (x, y) = (port(&c, "x"), port(&c, "y"));
+
+        // Case splits are beyond the conservative check.
+        let split = (a.clone() & b.clone()) | (a.clone() & !b.clone());
+        assert!(!a.implies(&split));
+        assert!(a.implies_exact(&split));
+        assert!(a.equiv_exact(&split));
+
+        let either = a.clone() | b.clone();
+        let disjoint = b.clone() | (a.clone() & !b.clone());
+        assert!(either.equiv_exact(&disjoint));
+        assert!(!either.implies_exact(&a));
+        assert!(a.implies_exact(&either));
+
+        // `y <= x` is the complement of `x < y` with its operands swapped.
+        let lt = x.clone().lt(y.clone());
+        let ge = y.clone().le(x.clone());
+        assert!(lt.exclusive_exact(&ge));
+        assert!((lt.clone() | ge).equiv_exact(&Guard::True));
+        // Other comparisons are unrelated atoms.
+        assert!(!lt.exclusive_exact(&x.eq(y)));
+
+        let both = a.clone() & b.clone();
+        assert!(both.exclusive_exact(&(!a.clone() | !b.clone())));
+        assert!(!both.exclusive_exact(&(a | !b)));
+    }
+
+    #[test]
+    fn all_ports_sorted() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("x", 4), ("y", 4)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let (x, y) = (port(&c, "x"), port(&c, "y"));
+        let cmp = x.clone().lt(y.clone());
+        let first = (a.clone() & cmp) | !b.clone() | a.clone();
+        let second = !b | (y.gt(x) & a);
+
+        let names = |g: &Guard<Nothing>| -> Vec<_> {
+            g.all_ports_sorted().iter().map(|p| p.borrow().name).collect()
+        };
+        assert_eq!(names(&first), ["a", "b", "x", "y"]);
+        assert_eq!(names(&first), names(&second));
+        assert!(Guard::<Nothing>::True.all_ports_sorted().is_empty());
+    }
+
+    #[test]
+    fn collect_ports_into() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("x", 4), ("y", 4)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let (x, y) = (port(&c, "x"), port(&c, "y"));
+        let first = a.clone() & !b.clone();
+        let second = x.lt(y) | a;
+
+        let name = |p: &RRC<Port>| p.borrow().name;
+        let mut buf = vec![];
+        first.collect_ports_into(&mut buf);
+        second.collect_ports_into(&mut buf);
+        let names: Vec<_> = buf.iter().map(name).collect();
+        assert_eq!(names, ["a", "b", "x", "y", "a"]);
+        let wrapped: Vec<_> = second.all_ports().iter().map(name).collect();
+        assert_eq!(wrapped, ["x", "y", "a"]);
+
+        buf.clear();
+        Guard::<Nothing>::True.collect_ports_into(&mut buf);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn fold_ranges() {
+        let c = cell("c", &[("a", 1), ("x", 4)]);
+        let (a, x) = (port(&c, "a"), port(&c, "x"));
+        let consts: Vec<_> =
+            [2, 3, 8].into_iter().map(|v| constant(v, 4)).collect();
+        let k = |i: usize| port(&consts[i], "out");
+
+        let pinned = x.clone().eq(k(1)).port_ranges();
+        assert!(x.clone().lt(k(2)).fold_ranges(&pinned).is_true());
+        assert!(k(0).ge(x.clone()).fold_ranges(&pinned).is_false());
+        let g = a.clone() & (x.clone().neq(k(1)) | x.clone().gt(k(0)));
+        assert_eq!(g.fold_ranges(&pinned), a);
+
+        // Ranges that do not decide the comparison leave it in place.
+        let ranges = x.clone().lt(k(2)).port_ranges();
+        let eq = x.eq(k(1));
+        assert_eq!(eq.fold_ranges(&ranges), eq);
+    }
+
+    #[test]
+    fn prune_unreachable() {
+        let fsm = cell("fsm", &[("out", 2)]);
+        let consts: Vec<_> =
+            [1, 2].into_iter().map(|v| constant(v, 2)).collect();
+        let k = |i: usize| port(&consts[i], "out");
+        let c = cell("c", &[("a", 1)]);
+        let (state, a) = (port(&fsm, "out"), port(&c, "a"));
+        let state_port = fsm.borrow().get("out").borrow().canonical();
+        // State 1 is reachable and state 2 is not.
+        let reachable = HashSet::from([0, 1]);
+
+        let g = (state.clone().eq(k(0)) & a.clone())
+            | (state.clone().eq(k(1)) & a.clone());
+        let pruned = g.prune_unreachable(&reachable, &state_port);
+        assert_eq!(pruned, state.clone().eq(k(0)) & a);
+
+        let g = k(1).neq(state.clone()) & state.clone().neq(k(0));
+        let pruned = g.prune_unreachable(&reachable, &state_port);
+        assert_eq!(pruned, state.neq(k(0)));
+    }
+
+    #[test]
+    fn balance() {
+        let names = ["a", "b", "c", "d", "e", "f"];
+        let c = cell("c", &names.map(|n| (n, 1)));
+        let ports: Vec<_> = names.iter().map(|n| port(&c, n)).collect();
+        let chain = ports
+            .iter()
+            .cloned()
+            .reduce(|acc, p| Guard::And(Box::new(acc), Box::new(p)))
+            .unwrap();
+        let balanced = chain.balance(4);
+        assert_eq!(chain.depth(), 5);
+        assert_eq!(balanced.depth(), 3);
+        assert_eq!(
+            balanced.truth_table(&ports, 6),
+            chain.truth_table(&ports, 6)
+        );
+        // Chains below the threshold keep their shape.
+        assert!(chain.balance(7).struct_eq(&chain));
+
+        // Chains are balanced below operators that are not.
+        let g = ports[0].clone() | chain;
+        let balanced = g.balance(4);
+        assert!(matches!(balanced, Guard::Or(..)));
+        assert_eq!(balanced.depth(), 4);
+    }
+
+    #[test]
+    fn depth_of_port() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1), ("x", 4), ("y", 4)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let (x, y) = (port(&c, "x"), port(&c, "y"));
+        let key = |name: &str| c.borrow().get(name).borrow().canonical();
+
+        assert_eq!(a.depth_of_port(&key("a")), Some(0));
+        // `a` is read directly below a `&` and also below `|` and `!`.
+        let g = a.clone() & (b | !a) & x.lt(y);
+        assert_eq!(g.depth_of_port(&key("a")), Some(4));
+        assert_eq!(g.depth_of_port(&key("b")), Some(3));
+        assert_eq!(g.depth_of_port(&key("x")), Some(2));
+        assert_eq!(g.depth_of_port(&key("d")), None);
+    }
+
+    #[test]
+    fn num_leaves_and_internal() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("x", 4), ("y", 4)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let (x, y) = (port(&c, "x"), port(&c, "y"));
+
+        assert_eq!(a.num_leaves(), 1);
+        assert_eq!(a.num_internal(), 0);
+        let g = (x.lt(y) & a) | !b;
+        let g = Guard::And(Box::new(g), Box::new(Guard::True));
+        assert_eq!(g.num_leaves(), 4);
+        assert_eq!(g.num_internal(), 4);
+        assert_eq!(g.num_leaves() + g.num_internal(), g.size());
+    }
+
+    #[test]
+    fn count_comparators() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("x", 4), ("y", 4)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let (x, y) = (port(&c, "x"), port(&c, "y"));
+
+        let boolean = !(a.clone() & b.clone()) | a.clone();
+        assert_eq!(boolean.count_comparators(), 0);
+        let g = (x.clone().lt(y.clone()) & a) | !x.eq(y) | b;
+        assert_eq!(g.count_comparators(), 2);
+    }
+
+    #[test]
+    fn as_single_port() {
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let a_port = c.borrow().get("a");
+
+        let single = a.clone().as_single_port().unwrap();
+        assert!(Rc::ptr_eq(&single, &a_port));
+        let padded = Guard::And(Box::new(Guard::True), Box::new(a.clone()));
+        assert!(Rc::ptr_eq(&padded.as_single_port().unwrap(), &a_port));
+
+        assert!((a.clone() & b).as_single_port().is_none());
+        assert!((!a).as_single_port().is_none());
+        assert!(Guard::<Nothing>::True.as_single_port().is_none());
+    }
+
+    #[test]
+    fn contains_comparison() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("x", 4), ("y", 4)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let (x, y) = (port(&c, "x"), port(&c, "y"));
+
+        assert!(!(!(a.clone() & b.clone()) | a.clone()).contains_comparison());
+        assert!(!Guard::<Nothing>::True.contains_comparison());
+        assert!((a & (b | !x.lt(y))).contains_comparison());
+    }
+
+    #[test]
+    fn canonicalize_orders_comparison_operands() {
+        let c = cell("c", &[("x", 4)]);
+        let x = port(&c, "x");
+        let five = constant(5, 4);
+        let k = port(&five, "out");
+        type Cmp = fn(Guard<Nothing>, Guard<Nothing>) -> Guard<Nothing>;
+        let flips: [(Cmp, Cmp); 6] = [
+            (Guard::eq, Guard::eq),
+            (Guard::neq, Guard::neq),
+            (Guard::lt, Guard::gt),
+            (Guard::gt, Guard::lt),
+            (Guard::le, Guard::ge),
+            (Guard::ge, Guard::le),
+        ];
+        let eval = |g: &Guard<Nothing>, v: u64| {
+            let (op, l, r) = g.as_cmp().unwrap();
+            let value =
+                |p: &RRC<Port>| p.borrow().constant_value().unwrap_or(v);
+            let (l, r) = (value(l), value(r));
+            match op {
+                PortComp::Eq => l == r,
+                PortComp::Neq => l != r,
+                PortComp::Gt => l > r,
+                PortComp::Lt => l < r,
+                PortComp::Geq => l >= r,
+                PortComp::Leq => l <= r,
+            }
+        };
+        for (op, flipped) in flips {
+            let g = op(k.clone(), x.clone());
+            let canon = g.canonicalize();
+            assert_eq!(canon, flipped(x.clone(), k.clone()));
+            for v in 0..16 {
+                assert_eq!(eval(&canon, v), eval(&g, v));
+            }
+            // Comparisons with the port on the left are unchanged.
+            assert_eq!(canon.canonicalize(), canon);
+        }
+    }
+
+    #[test]
+    fn is_mutually_exclusive() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("x", 4), ("y", 4)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let (x, y) = (port(&c, "x"), port(&c, "y"));
+        let consts: Vec<_> =
+            [1, 2, 8].into_iter().map(|v| constant(v, 4)).collect();
+        let k = |i: usize| port(&consts[i], "out");
+
+        assert!(a.is_mutually_exclusive(&!a.clone()));
+        assert!((a.clone() & b.clone()).is_mutually_exclusive(&!b.clone()));
+        assert!(!a.is_mutually_exclusive(&b));
+        let one = x.clone().eq(k(0));
+        assert!(one.is_mutually_exclusive(&x.clone().eq(k(1))));
+        assert!(one.is_mutually_exclusive(&x.clone().gt(k(1))));
+        assert!(x.clone().lt(k(1)).is_mutually_exclusive(&x.clone().ge(k(2))));
+        assert!(!x.clone().lt(k(2)).is_mutually_exclusive(&x.clone().ge(k(0))));
+        let either = x.clone().eq(k(1)) | (x.clone().eq(k(2)) & a.clone());
+        assert!(one.is_mutually_exclusive(&either));
+        // Comparisons of different ports are not exclusive.
+        assert!(!one.is_mutually_exclusive(&y.eq(k(0))));
+    }
+
+    #[test]
+    fn simplify_reporting() {
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let not_not_a = Guard::Not(Box::new(Guard::Not(Box::new(a.clone()))));
+        let absorbed = a.clone() | (a.clone() & b.clone());
+        let g = Guard::Or(Box::new(not_not_a.clone()), Box::new(a.clone() & b));
+        let (simple, steps) = g.simplify_reporting();
+        assert_eq!(simple, a);
+        let steps: Vec<_> =
+            steps.into_iter().map(|s| (s.rule, s.subtree)).collect();
+        assert_eq!(
+            steps,
+            vec![
+                (SimplifyRule::DoubleNegation, not_not_a),
+                (SimplifyRule::Absorption, absorbed),
+            ]
+        );
+        let (_, steps) = a.clone().simplify_reporting();
+        assert!(steps.is_empty());
+    }
+
+    #[test]
+    fn simplify_idempotence() {
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        // The operators already skip a repeated operand, so build the
+        // nodes directly.
+        let a_and_a = Guard::And(Box::new(a.clone()), Box::new(a.clone()));
+        let and = Guard::And(Box::new(a_and_a), Box::new(b.clone()));
+        let (simple, steps) = and.clone().simplify_reporting();
+        assert_eq!(simple, a.clone() & b.clone());
+        assert_eq!(steps[0].rule, SimplifyRule::Idempotence);
+        assert_eq!(and.canonicalize(), a.clone() & b.clone());
+
+        let or = Guard::Or(Box::new(a.clone()), Box::new(a.clone()));
+        let (simple, steps) = or.clone().simplify_reporting();
+        assert_eq!(simple, a);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(or.canonicalize(), a);
+
+        // Repeats that are not adjacent are removed too.
+        let spread = Guard::And(
+            Box::new(Guard::And(Box::new(a.clone()), Box::new(b.clone()))),
+            Box::new(a.clone()),
+        );
+        let (simple, _) = spread.simplify_reporting();
+        assert_eq!(simple, a.clone() & b.clone());
+        let (again, steps) = simple.clone().simplify_reporting();
+        assert_eq!(again, simple);
+        assert!(steps.is_empty());
+    }
+
+    #[test]
+    fn to_aig() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("c", 1)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let atoms = [a.clone(), b.clone()];
+
+        // `!a` is a negated input and `|` is a negated `&` of negations.
+        let g = (a.clone() & b.clone()) | !a.clone();
+        let aig = g.to_aig(&atoms).unwrap();
+        assert_eq!(aig.inputs, 2);
+        assert_eq!(aig.ands, vec![(4, 2), (7, 2)]);
+        assert_eq!(aig.output, 9);
+        assert_eq!(aig.to_string(), "aag 4 2 0 1 2\n2\n4\n9\n6 4 2\n8 7 2\n");
+
+        // Equal subgraphs share their gates.
+        let both = Guard::Or(
+            Box::new(a.clone() & b.clone()),
+            Box::new(b.clone() & a.clone()),
+        );
+        assert_eq!(both.to_aig(&atoms).unwrap().ands.len(), 1);
+        assert_eq!(Guard::<Nothing>::True.to_aig(&[]).unwrap().output, 1);
+        assert!(port(&c, "c").to_aig(&atoms).is_none());
+    }
+
+    #[test]
+    fn expand_const_ports() {
+        let c = cell("c", &[("x", 8)]);
+        let k = cell("k", &[("out", 8)]);
+        let f = cell("f", &[("out", 1)]);
+        let (x, k_out) = (c.borrow().get("x"), k.borrow().get("out"));
+        let const_drivers: HashMap<_, _> = [
+            (k_out.borrow().canonical(), 5),
+            (f.borrow().get("out").borrow().canonical(), 0),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut comp = Component::new("main", vec![], false, false, None);
+        let sigs = LibrarySignatures::default();
+        let mut builder = Builder::new(&mut comp, &sigs);
+        let g = Guard::CompOp(PortComp::Lt, Rc::clone(&x), k_out)
+            & port(&f, "out");
+        let expanded = g.expand_const_ports(&const_drivers, &mut builder);
+        let five = builder.add_constant(5, 8).borrow().get("out");
+        assert_eq!(
+            expanded,
+            Guard::And(
+                Box::new(Guard::CompOp(PortComp::Lt, x, five)),
+                Box::new(Guard::Not(Box::new(Guard::True))),
+            )
+        );
+        let plain = port(&c, "x");
+        let same = plain.expand_const_ports(&const_drivers, &mut builder);
+        assert_eq!(same, plain);
+    }
+
+    #[test]
+    fn and_all_or_all() {
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let guards = || vec![Some(a.clone()), None, Some(b.clone()), None];
+
+        assert_eq!(Guard::and_all(guards()), a.clone() & b.clone());
+        assert_eq!(Guard::or_all(guards()), a.clone() | b.clone());
+        assert_eq!(Guard::and_all(vec![None, Some(a.clone())]), a);
+
+        let nones = || vec![None::<Guard<Nothing>>, None];
+        assert!(Guard::and_all(nones()).is_true());
+        assert!(Guard::or_all(nones()).is_false());
+        assert!(Guard::<Nothing>::or_all(vec![]).is_false());
+    }
+
+    #[test]
+    fn eq_modulo_true() {
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let pad = |g: Guard<Nothing>| {
+            Guard::And(Box::new(g), Box::new(Guard::True))
+        };
+
+        assert!(pad(a.clone()).eq_modulo_true(&a));
+        assert!(a.eq_modulo_true(&pad(pad(a.clone()))));
+        assert!(pad(a.clone() | b.clone())
+            .eq_modulo_true(&(pad(a.clone()) | b.clone())));
+        assert!(!(a.clone() & b.clone()).eq_modulo_true(&a));
+        assert!(!pad(a.clone()).eq_modulo_true(&b));
+
+        // Ports with the same name on different cells are not equal.
+        let d = cell("d", &[("a", 1)]);
+        assert!(!pad(a).eq_modulo_true(&port(&d, "a")));
+    }
+
+    #[test]
+    fn guard_macro() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("c", 1), ("x", 32)]);
+        let y = cell("y", &[("out", 32)]);
+        let a = c.borrow().get("a");
+        let b = c.borrow().get("b");
+        let x = c.borrow().get("x");
+
+        assert_eq!(
+            crate::guard!(a & b | !c["c"]),
+            (port(&c, "a") & port(&c, "b")) | !port(&c, "c")
+        );
+        assert_eq!(
+            crate::guard!(x < y["out"]),
+            port(&c, "x").lt(port(&y, "out"))
+        );
+        assert_eq!(
+            crate::guard!(a & (b | x >= y["out"])),
+            port(&c, "a") & (port(&c, "b") | port(&c, "x").ge(port(&y, "out")))
+        );
+
+        let mut comp = Component::new("main", vec![], false, false, None);
+        let sigs = LibrarySignatures::default();
+        let mut builder = Builder::new(&mut comp, &sigs);
+        let g: Guard<Nothing> = crate::guard!(builder; x < 8 & 3 != x);
+        let eight = builder.add_constant(8, 32).borrow().get("out");
+        let three = builder.add_constant(3, 32).borrow().get("out");
+        assert_eq!(
+            g,
+            Guard::CompOp(PortComp::Lt, Rc::clone(&x), eight)
+                & Guard::CompOp(PortComp::Neq, three, x)
+        );
+    }
+
+    #[test]
+    fn implies() {
+        let fsm = cell("fsm", &[("out", 2)]);
+        let x = cell("x", &[("out", 1)]);
+        let k = |v| {
+            let c = constant(v, 2);
+            let out = c.borrow().get("out");
+            (out, c)
+        };
+        let (one, _c1) = k(1);
+        let (two, _c2) = k(2);
+        let fsm_out = fsm.borrow().get("out");
+        let is_one = Guard::CompOp(PortComp::Eq, Rc::clone(&fsm_out), one);
+        let act = !port(&x, "out") & is_one.clone();
+
+        assert!(act.implies(&is_one));
+        assert!(act.implies(&Guard::CompOp(
+            PortComp::Neq,
+            Rc::clone(&fsm_out),
+            Rc::clone(&two)
+        )));
+        assert!(act.implies(&Guard::CompOp(PortComp::Gt, two, fsm_out)));
+        assert!(act.implies(&(is_one.clone() | port(&x, "out"))));
+        assert!(!act.implies(&port(&x, "out")));
+        assert!(!is_one.implies(&act));
+    }
+
+    #[test]
+    fn replace_subtree() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1), ("x", 32)]);
+        let y = cell("y", &[("out", 32)]);
+        let lt = || port(&c, "x").lt(port(&y, "out"));
+        let mut g = (port(&c, "a") & lt()) | !(lt() | port(&c, "b"));
+
+        assert_eq!(g.replace_subtree(&lt(), &port(&c, "d")), 2);
+        assert_eq!(
+            g,
+            (port(&c, "a") & port(&c, "d"))
+                | !(port(&c, "d") | port(&c, "b"))
+        );
+
+        // Comparisons with different operands don't match.
+        let gt = port(&c, "x").gt(port(&y, "out"));
+        assert_eq!(g.replace_subtree(&gt, &Guard::True), 0);
+        assert_eq!(g.replace_subtree(&port(&y, "out"), &Guard::True), 0);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn to_json() {
+        let c = cell("c", &[("a", 1), ("x", 32)]);
+        let k = constant(4, 32);
+        let lt = port(&c, "x").lt(port(&k, "out"));
+        let g = port(&c, "a") & !lt;
+        assert_eq!(
+            g.to_json(),
+            serde_json::json!({
+                "op": "and",
+                "children": [
+                    { "op": "port", "port": { "cell": "c", "port": "a" } },
+                    {
+                        "op": "geq",
+                        "operands": [
+                            { "cell": "c", "port": "x" },
+                            { "value": 4, "width": 32 }
+                        ]
+                    }
+                ]
+            })
+        );
+        assert_eq!(
+            (!Guard::<Nothing>::True).to_json(),
+            serde_json::json!({ "op": "not", "children": [{ "op": "true" }] })
+        );
+    }
+
+    /// Evaluate a guard over 1-bit ports, looking up each port's value by
+    /// name in `env`.
+    fn eval(g: &Guard<Nothing>, env: &[(&str, bool)]) -> bool {
+        let lookup = |p: &RRC<Port>| {
+            let name = p.borrow().name;
+            env.iter().find(|(n, _)| name == *n).unwrap().1 as u8
+        };
+        match g {
+            Guard::And(l, r) => eval(l, env) && eval(r, env),
+            Guard::Or(l, r) => eval(l, env) || eval(r, env),
+            Guard::Implies(l, r) => !eval(l, env) || eval(r, env),
+            Guard::Not(g) => !eval(g, env),
+            Guard::True => true,
+            Guard::Port(p) => lookup(p) == 1,
+            Guard::CompOp(op, l, r) => {
+                let (l, r) = (lookup(l), lookup(r));
+                match op {
+                    PortComp::Eq => l == r,
+                    PortComp::Neq => l != r,
+                    PortComp::Gt => l > r,
+                    PortComp::Lt => l < r,
+                    PortComp::Geq => l >= r,
+                    PortComp::Leq => l <= r,
+                }
+            }
+            Guard::Info(_) => unreachable!(),
+        }
+    }
+
+    /// Returns true if `!` is only applied to leaves of the guard.
+    fn is_nnf(g: &Guard<Nothing>) -> bool {
+        match g {
+            Guard::And(l, r) | Guard::Or(l, r) => is_nnf(l) && is_nnf(r),
+            Guard::Not(g) => matches!(**g, Guard::Port(_) | Guard::True),
+            Guard::Implies(..) => false,
+            _ => true,
+        }
+    }
+
+    #[test]
+    fn to_nnf() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+        let guards = vec![
+            Guard::Not(Box::new(a.clone() & b.clone())),
+            Guard::Not(Box::new(a.clone() | b.clone().lt(d.clone()))),
+            Guard::Not(Box::new(Guard::Not(Box::new(
+                Guard::Not(Box::new(a.clone())) & (b.clone() | !d.clone()),
+            )))),
+            !(a.clone() & !(b.clone() | d.clone().eq(a.clone()))),
+            Guard::Not(Box::new(a.clone().implies_guard(!b.clone()))),
+            (a.clone() & b.clone()).implies_guard(d.clone()),
+        ];
+        for g in guards {
+            let nnf = g.clone().to_nnf();
+            assert!(is_nnf(&nnf), "{}", Printer::guard_str(&nnf));
+            assert_eq!(nnf.clone().to_nnf(), nnf);
+            for bits in 0..8u8 {
+                let env = [
+                    ("a", bits & 1 != 0),
+                    ("b", bits & 2 != 0),
+                    ("d", bits & 4 != 0),
+                ];
+                assert_eq!(eval(&g, &env), eval(&nnf, &env));
+            }
+        }
+        assert_eq!(
+            Guard::Not(Box::new(a.clone() & b.clone())).to_nnf(),
+            Guard::Or(Box::new(!a), Box::new(!b))
+        );
+    }
+
+    #[test]
+    fn port_ranges() {
+        let x = cell("x", &[("out", 4)]);
+        let y = cell("y", &[("out", 4)]);
+        let consts: Vec<_> =
+            [2, 5, 8].into_iter().map(|v| constant(v, 4)).collect();
+        let k = |i: usize| port(&consts[i], "out");
+        let x_out = port(&x, "out");
+        let y_out = port(&y, "out");
+        let key = |c: &RRC<Cell>| c.borrow().get("out").borrow().canonical();
+
+        let g = x_out.clone().lt(k(2)) & x_out.clone().ge(k(0)) & y_out.clone();
+        let ranges = g.port_ranges();
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[&key(&x)], Some((2, 7)));
+
+        let g = x_out.clone().lt(k(0)) & k(1).lt(x_out) & y_out.ge(k(1));
+        let ranges = g.port_ranges();
+        assert_eq!(ranges[&key(&x)], None);
+        assert_eq!(ranges[&key(&y)], Some((5, 15)));
+    }
+
+    #[test]
+    fn remove_port_conjuncts() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+        let a_port = c.borrow().get("a");
+
+        let mut g =
+            a.clone() & b.clone() & !a.clone() & d.clone().lt(a.clone());
+        assert!(g.remove_port_conjuncts(&a_port));
+        assert_eq!(g, b);
+
+        let mut g = !a.clone();
+        assert!(g.remove_port_conjuncts(&a_port));
+        assert!(g.is_true());
+
+        let mut g = b.clone() & d.clone();
+        assert!(g.remove_port_conjuncts(&a_port));
+        assert_eq!(g, b.clone() & d.clone());
+    }
+
+    #[test]
+    fn remove_port_conjuncts_unsound_or() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+        let a_port = c.borrow().get("a");
+
+        let mut g = (a.clone() | b.clone()) & a.clone() & d.clone();
+        assert!(!g.remove_port_conjuncts(&a_port));
+        assert_eq!(g, (a | b) & d);
+    }
+
+    #[test]
+    fn canonical_key() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+
+        let g1 = (a.clone() & b.clone()) | !d.clone() & Guard::True;
+        let g2 = Guard::Or(
+            Box::new(Guard::Not(Box::new(d.clone()))),
+            Box::new(b.clone() & a.clone()),
+        );
+        assert_eq!(g1.canonical_key(), g2.canonical_key());
+        assert_eq!(
+            (a.clone() & (b.clone() & d.clone())).canonical_key(),
+            (d.clone() & a.clone() & b.clone()).canonical_key()
+        );
+
+        assert_ne!(
+            (a.clone() & b.clone()).canonical_key(),
+            (a.clone() | b.clone()).canonical_key()
+        );
+        assert_ne!(
+            (a.clone() & (b.clone() | d.clone())).canonical_key(),
+            ((a & b) | d).canonical_key()
+        );
+    }
+
+    #[test]
+    fn as_accessors() {
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let (a, b) = (port(&c, "a"), port(&c, "b"));
+        let and = a.clone() & b.clone();
+        let or = a.clone() | b.clone();
+        let cmp = a.clone().lt(b.clone());
+
+        assert_eq!(and.as_and(), Some((&a, &b)));
+        assert_eq!(or.as_or(), Some((&a, &b)));
+        let (op, l, r) = cmp.as_cmp().unwrap();
+        assert_eq!(*op, PortComp::Lt);
+        assert!(Rc::ptr_eq(l, &c.borrow().get("a")));
+        assert!(Rc::ptr_eq(r, &c.borrow().get("b")));
+
+        assert!(or.as_and().is_none());
+        assert!(cmp.as_and().is_none());
+        assert!(and.as_or().is_none());
+        assert!(a.as_or().is_none());
+        assert!(and.as_cmp().is_none());
+        assert!(Guard::<Nothing>::True.as_cmp().is_none());
+    }
+
+    #[test]
+    fn truth_table() {
+        let c = cell("c", &[("a", 1), ("b", 1), ("d", 1)]);
+        let (a, b, d) = (port(&c, "a"), port(&c, "b"), port(&c, "d"));
+        let atoms = [a.clone(), b.clone()];
+
+        assert_eq!(
+            (a.clone() & !b.clone()).truth_table(&atoms, 2),
+            Some(vec![false, false, true, false])
+        );
+        assert_eq!(
+            (a.clone() | b.clone()).truth_table(&atoms, 2),
+            Some(vec![false, true, true, true])
+        );
+        assert_eq!((a.clone() | b.clone()).truth_table(&atoms, 1), None);
+        assert_eq!((a & d).truth_table(&atoms, 2), None);
+    }
+
+    #[test]
+    fn references_interface() {
+        let comp = Component::new("main", vec![], true, false, None);
+        let c = cell("c", &[("a", 1), ("b", 1)]);
+        let go = Guard::port(comp.signature.borrow().get("go"));
+
+        let g = go & port(&c, "a");
+        assert!(g.references_interface(&comp));
+        let cells = g.cells();
+        assert_eq!(cells.len(), 1);
+        assert!(Rc::ptr_eq(&cells[0], &c));
+
+        let g = port(&c, "a") | port(&c, "b");
+        assert!(!g.references_interface(&comp));
+        assert_eq!(g.cells().len(), 1);
+    }
+}
+
+/// Property tests checking that the guard normalizations preserve the value
+/// of the guard. They are slow, so they only run with the `fuzz` feature:
+/// ```
+/// cargo test -p calyx-ir --features fuzz
+/// ```
+#[cfg(all(test, feature = "fuzz"))]
+mod fuzz {
+    use super::*;
+    use crate::{rrc, Attributes, Cell, CellType, Direction, Id, WRC};
+    use proptest::prelude::*;
+
+    /// The ports guards are generated over. `a` and `b` are used as
+    /// booleans and `x` and `y` are compared.
+    const PORTS: [(&str, u64); 4] = [("a", 1), ("b", 1), ("x", 2), ("y", 2)];
+
+    /// The number of bits needed to give every port a value.
+    const BITS: u32 = 6;
+
+    /// The most atoms [Guard::minimize] minimizes; larger guards are only
+    /// canonicalized, which keeps the property fast.
+    const MAX_ATOMS: usize = 8;
+
+    /// The structure of a generated guard. Guards hold references to ports,
+    /// so the structure is generated and shrunk instead of the guard itself.
+    #[derive(Debug, Clone)]
+    enum Shape {
+        True,
+        Port(usize),
+        /// Compares `x` and `y`, or `y` and `x` if the flag is set.
+        Cmp(PortComp, bool),
+        Not(Box<Shape>),
+        And(Box<Shape>, Box<Shape>),
+        Or(Box<Shape>, Box<Shape>),
+    }
+
+    fn shape() -> impl Strategy<Value = Shape> {
+        let comp = prop_oneof![
+            Just(PortComp::Eq),
+            Just(PortComp::Neq),
+            Just(PortComp::Gt),
+            Just(PortComp::Lt),
+            Just(PortComp::Geq),
+            Just(PortComp::Leq),
+        ];
+        let leaf = prop_oneof![
+            Just(Shape::True),
+            (0..2usize).prop_map(Shape::Port),
+            (comp, any::<bool>()).prop_map(|(op, flip)| Shape::Cmp(op, flip)),
+        ];
+        leaf.prop_recursive(5, 32, 2, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|g| Shape::Not(Box::new(g))),
+                (inner.clone(), inner.clone())
+                    .prop_map(|(l, r)| Shape::And(Box::new(l), Box::new(r))),
+                (inner.clone(), inner)
+                    .prop_map(|(l, r)| Shape::Or(Box::new(l), Box::new(r))),
+            ]
+        })
+    }
+
+    fn cell() -> RRC<Cell> {
+        let prototype = CellType::Primitive {
+            name: Id::from("std_reg"),
+            param_binding: Box::default(),
+            is_comb: false,
+            latency: None,
+        };
+        let cell = rrc(Cell::new(Id::from("c"), prototype));
+        for (port, width) in PORTS {
+            let port = rrc(Port {
+                name: Id::from(port),
+                width,
+                direction: Direction::Output,
+                parent: PortParent::Cell(WRC::from(&cell)),
+                attributes: Attributes::default(),
+            });
+            cell.borrow_mut().ports.push(port);
+        }
+        cell
+    }
+
+    /// Build the guard with the given shape. If `simplify` is set, the
+    /// simplifying constructors are used instead of the raw variants.
+    fn build(
+        shape: &Shape,
+        cell: &RRC<Cell>,
+        simplify: bool,
+    ) -> Guard<Nothing> {
+        let port = |name: &str| cell.borrow().get(name);
+        let sub = |g: &Shape| build(g, cell, simplify);
+        match shape {
+            Shape::True => Guard::True,
+            Shape::Port(idx) => Guard::port(port(PORTS[*idx].0)),
+            Shape::Cmp(op, flip) => {
+                let (l, r) = if *flip { ("y", "x") } else { ("x", "y") };
+                Guard::CompOp(op.clone(), port(l), port(r))
+            }
+            Shape::Not(g) if simplify => !sub(g),
+            Shape::Not(g) => Guard::Not(Box::new(sub(g))),
+            Shape::And(l, r) if simplify => sub(l).and(sub(r)),
+            Shape::And(l, r) => Guard::And(Box::new(sub(l)), Box::new(sub(r))),
+            Shape::Or(l, r) if simplify => sub(l).or(sub(r)),
+            Shape::Or(l, r) => Guard::Or(Box::new(sub(l)), Box::new(sub(r))),
+        }
+    }
+
+    /// Evaluate the guard when the ports have the values packed into `row`,
+    /// with `a` in the lowest bits.
+    fn eval(guard: &Guard<Nothing>, row: u64) -> bool {
+        let value = |port: &RRC<Port>| {
+            let port = port.borrow();
+            let mut offset = 0;
+            for (name, width) in PORTS {
+                if port.name == name {
+                    return (row >> offset) & ((1 << width) - 1);
+                }
+                offset += width;
+            }
+            unreachable!("unknown port `{}'", port.name)
+        };
+        match guard {
+            Guard::True => true,
+            Guard::Port(p) => value(p) == 1,
+            Guard::Not(g) => !eval(g, row),
+            Guard::And(l, r) => eval(l, row) && eval(r, row),
+            Guard::Or(l, r) => eval(l, row) || eval(r, row),
+            Guard::Implies(l, r) => !eval(l, row) || eval(r, row),
+            Guard::CompOp(op, l, r) => {
+                let (l, r) = (value(l), value(r));
+                match op {
+                    PortComp::Eq => l == r,
+                    PortComp::Neq => l != r,
+                    PortComp::Gt => l > r,
+                    PortComp::Lt => l < r,
+                    PortComp::Geq => l >= r,
+                    PortComp::Leq => l <= r,
+                }
+            }
+            Guard::Info(_) => unreachable!("fuzzed guards have no infos"),
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10000))]
+        #[test]
+        fn normalizations_preserve_value(shape in shape()) {
+            let cell = cell();
+            let guard = build(&shape, &cell, false);
+            let forms = [
+                ("simplifying constructors", build(&shape, &cell, true)),
+                ("canonicalize", guard.canonicalize()),
+                ("to_nnf", guard.clone().to_nnf()),
+                ("simplify_reporting", guard.clone().simplify_reporting().0),
+                ("absorb", guard.absorb()),
+                ("minimize", guard.minimize(MAX_ATOMS)),
+                ("balance", guard.balance(2)),
+            ];
+            for row in 0..1 << BITS {
+                let expected = eval(&guard, row);
+                for (name, form) in &forms {
+                    prop_assert_eq!(
+                        eval(form, row),
+                        expected,
+                        "{} changed the value of `{}' in row {}",
+                        name,
+                        Printer::guard_str(&guard),
+                        row
+                    );
+                }
+            }
+        }
+    }
+}
@@ -7,6 +7,9 @@
 //! 2. The IR attempts to represent similar concepts in a homogeneous manner.
 
 // Modules defining internal structures.
+mod aig;
+#[cfg(feature = "bdd")]
+mod bdd;
 mod builder;
 mod common;
 mod component;
@@ -22,18 +25,24 @@ mod structure;
 pub mod rewriter;
 
 // Re-export types at the module level.
+pub use aig::Aig;
 pub use builder::Builder;
 pub use calyx_utils::{GetName, Id};
 pub use common::{rrc, RRC, WRC};
 pub use component::{Component, IdList};
-pub use context::{BackendConf, Context};
+pub use context::{BackendConf, Context, GuardDefault};
 pub use control::{
     Cloner, Control, Empty, Enable, GenericControl, If, Invoke, Par, Repeat,
     Seq, StaticControl, StaticEnable, StaticIf, StaticInvoke, StaticPar,
     StaticRepeat, StaticSeq, While,
 };
 pub use flat_guard::{FlatGuard, GuardPool, GuardRef};
-pub use guard::{Guard, Nothing, PortComp, StaticTiming};
+#[cfg(feature = "serialize")]
+pub use guard::GUARD_JSON_SCHEMA;
+pub use guard::{
+    Guard, GuardOpCounts, GuardRule, GuardWidths, Nothing, PortComp,
+    SimplifyRule, SimplifyStep, StaticTiming, StructEq,
+};
 pub use printer::Printer;
 pub use reserved_names::RESERVED_NAMES;
 pub use rewriter::Rewriter;
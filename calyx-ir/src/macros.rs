@@ -1,66 +1,133 @@
 /// Parse guard expression into [`ir::Guard`](crate::Guard).
 ///
-/// The identifier should either be a [`ir::Group`](crate::Group) or an
-/// [`ir::Cell`](crate::Cell).
+/// Operands are either port accesses, `node[port]`, where the identifier is
+/// a [`ir::Group`](crate::Group) or an [`ir::Cell`](crate::Cell), or bare
+/// names bound to an [`RRC<ir::Port>`](crate::Port) or an
+/// [`ir::Guard`](crate::Guard). Bare names are cloned.
 /// Example:
 /// ```
 /// let fsm_out = guard!(fsm["out"] == lb["out"] & g);
 /// ```
 ///
+/// Integer literals can be used as operands of comparisons when a
+/// [`ir::Builder`](crate::Builder) is provided. They are added as constants
+/// with the width of the other operand:
+/// ```
+/// let in_range = guard!(builder; idx < 8 & !done);
+/// ```
+///
 /// The macro supports constructing guards using the following operators:
 /// - Port access: `node[port]`
-/// - Comparison operators: `==`, `>=`, `<=`, `>`, `<`
-/// - Logical operators: `&`, `|`
+/// - Comparison operators: `==`, `!=`, `>=`, `<=`, `>`, `<`
+/// - Logical operators: `!`, `&`, `|`
 /// - Parentheses: `()`
+///
+/// Comparisons bind tighter than logical operators and the logical operators
+/// have the same precedence as in Rust.
 #[macro_export]
 macro_rules! guard {
-    // Base
-    // Port access
-    ($node:ident[$port:expr]) => {
-        $crate::Guard::from($node.borrow().get($port))
-    };
-    // Parentheses
-    ( ( $($head:tt)* ) ) => {
-        guard!($($head)*)
+    // With a builder to construct constants
+    ($b:ident; $($e:tt)+) => {
+        $crate::guard!(@munch [$b] [] $($e)+)
     };
-    // A bare name
-    ($e:ident) => { $e };
 
-    // Comparison operators
-    ($node:ident[$port:expr] >= $($tail:tt)*) => {
-        $crate::Guard::from($node.borrow().get($port)).ge(guard!($($tail)*))
+    // Translate the expression from left to right, accumulating the output
+    // in the second bracket.
+    (@munch [$($b:tt)*] [$($out:tt)*]) => { $($out)* };
+    (@munch [$($b:tt)*] [$($out:tt)*] ! $($rest:tt)*) => {
+        $crate::guard!(@munch [$($b)*] [$($out)* !] $($rest)*)
+    };
+    (@munch [$($b:tt)*] [$($out:tt)*] & $($rest:tt)*) => {
+        $crate::guard!(@munch [$($b)*] [$($out)* &] $($rest)*)
     };
-    ($node:ident[$port:expr] <= $($tail:tt)*) => {
-        $crate::Guard::from($node.borrow().get($port)).le(guard!($($tail)*))
+    (@munch [$($b:tt)*] [$($out:tt)*] | $($rest:tt)*) => {
+        $crate::guard!(@munch [$($b)*] [$($out)* |] $($rest)*)
     };
-    ($node:ident[$port:expr] < $($tail:tt)*) => {
-        $crate::Guard::from($node.borrow().get($port)).lt(guard!($($tail)*))
+    (@munch [$($b:tt)*] [$($out:tt)*] ( $($inner:tt)* ) $($rest:tt)*) => {
+        $crate::guard!(@munch [$($b)*]
+            [$($out)* ($crate::guard!(@munch [$($b)*] [] $($inner)*))]
+            $($rest)*)
     };
-    ($node:ident[$port:expr] > $($tail:tt)*) => {
-        $crate::Guard::from($node.borrow().get($port)).gt(guard!($($tail)*))
+    // Operands
+    (@munch [$($b:tt)*] [$($out:tt)*] $n:ident[$p:expr]) => {
+        $crate::guard!(@munch [$($b)*] [$($out)* $crate::guard!(@leaf $n[$p])])
     };
-    ($node:ident[$port:expr] == $($tail:tt)*) => {
-        $crate::Guard::from($node.borrow().get($port)).eq(guard!($($tail)*))
+    (@munch [$($b:tt)*] [$($out:tt)*] $n:ident[$p:expr] $op:tt $($rest:tt)*) => {
+        $crate::guard!(@operand [$($b)*] [$($out)*] ($n[$p]) $op $($rest)*)
     };
-    ($node:ident[$port:expr] != $($tail:tt)*) => {
-        $crate::Guard::from($node.borrow().get($port)).neq(guard!($($tail)*))
+    (@munch [$($b:tt)*] [$($out:tt)*] $x:tt) => {
+        $crate::guard!(@munch [$($b)*] [$($out)* $crate::guard!(@leaf $x)])
+    };
+    (@munch [$($b:tt)*] [$($out:tt)*] $x:tt $op:tt $($rest:tt)*) => {
+        $crate::guard!(@operand [$($b)*] [$($out)*] ($x) $op $($rest)*)
+    };
+
+    // An operand followed by a logical operator is a leaf, otherwise it is
+    // the left side of a comparison.
+    (@operand [$($b:tt)*] [$($out:tt)*] ($($l:tt)*) & $($rest:tt)*) => {
+        $crate::guard!(@munch [$($b)*]
+            [$($out)* $crate::guard!(@leaf $($l)*)] & $($rest)*)
+    };
+    (@operand [$($b:tt)*] [$($out:tt)*] ($($l:tt)*) | $($rest:tt)*) => {
+        $crate::guard!(@munch [$($b)*]
+            [$($out)* $crate::guard!(@leaf $($l)*)] | $($rest)*)
+    };
+    (@operand [$($b:tt)*] [$($out:tt)*] ($($l:tt)*) $op:tt
+        $m:ident[$q:expr] $($rest:tt)*) => {
+        $crate::guard!(@munch [$($b)*]
+            [$($out)* $crate::guard!(@cmp [$($b)*] $op ($($l)*) ($m[$q]))]
+            $($rest)*)
+    };
+    (@operand [$($b:tt)*] [$($out:tt)*] ($($l:tt)*) $op:tt
+        $y:tt $($rest:tt)*) => {
+        $crate::guard!(@munch [$($b)*]
+            [$($out)* $crate::guard!(@cmp [$($b)*] $op ($($l)*) ($y))]
+            $($rest)*)
     };
 
-    // Logical operators
-    // AND
-    ($node:ident[$port:expr] & $($tail:tt)*) => {
-        $crate::Guard::from($node.borrow().get($port)) & guard!($($tail)*)
+    (@leaf $n:ident[$p:expr]) => {
+        $crate::Guard::from($n.borrow().get($p))
     };
-    ( ( $($head:tt)* ) & $($tail:tt)*) => {
-        guard!($($head)*) & guard!($($tail)*)
+    (@leaf $x:ident) => {
+        $crate::Guard::from($x.clone())
     };
 
-    // OR
-    ($node:ident[$port:expr] | $($tail:tt)*) => {
-        $crate::Guard::from($node.borrow().get($port)) | guard!($($tail)*)
+    (@cmp [$($b:tt)*] $op:tt ($($l:tt)*) ($($r:tt)*)) => {{
+        let (l, r) = $crate::guard!(@ports [$($b)*] ($($l)*) ($($r)*));
+        $crate::Guard::CompOp($crate::guard!(@op $op), l, r)
+    }};
+    (@op ==) => { $crate::PortComp::Eq };
+    (@op !=) => { $crate::PortComp::Neq };
+    (@op >) => { $crate::PortComp::Gt };
+    (@op <) => { $crate::PortComp::Lt };
+    (@op >=) => { $crate::PortComp::Geq };
+    (@op <=) => { $crate::PortComp::Leq };
+
+    // Constants take the width of the other operand
+    (@ports [$($b:tt)*] ($l:literal) ($($r:tt)*)) => {{
+        let r = $crate::guard!(@port $($r)*);
+        let l = $crate::guard!(@const [$($b)*] $l, r.borrow().width);
+        (l, r)
+    }};
+    (@ports [$($b:tt)*] ($($l:tt)*) ($r:literal)) => {{
+        let l = $crate::guard!(@port $($l)*);
+        let r = $crate::guard!(@const [$($b)*] $r, l.borrow().width);
+        (l, r)
+    }};
+    (@ports [$($b:tt)*] ($($l:tt)*) ($($r:tt)*)) => {
+        ($crate::guard!(@port $($l)*), $crate::guard!(@port $($r)*))
+    };
+    (@port $n:ident[$p:expr]) => { $n.borrow().get($p) };
+    (@port $x:ident) => { ::std::rc::Rc::clone(&$x) };
+    (@const [$b:ident] $v:literal, $w:expr) => {
+        $b.add_constant($v, $w).borrow().get("out")
+    };
+    (@const [] $v:literal, $w:expr) => {
+        compile_error!("constants in `guard!` require a builder: `guard!(builder; ...)`")
     };
-    ( ( $($head:tt)* ) | $($tail:tt)*) => {
-        guard!($($head)*) | guard!($($tail)*)
+
+    ($($e:tt)+) => {
+        $crate::guard!(@munch [] [] $($e)+)
     };
 }
 
@@ -739,6 +739,21 @@ impl Printer {
                 };
                 format!("{} {} {}", left, &guard.op_str(), right)
             }
+            // `->` groups to the right, so an implication on the left needs
+            // parentheses.
+            ir::Guard::Implies(l, r) => {
+                let left = if &**l >= guard {
+                    format!("({})", Self::guard_str(l))
+                } else {
+                    Self::guard_str(l)
+                };
+                let right = if &**r > guard {
+                    format!("({})", Self::guard_str(r))
+                } else {
+                    Self::guard_str(r)
+                };
+                format!("{} -> {}", left, right)
+            }
             ir::Guard::CompOp(_, l, r) => {
                 format!(
                     "{} {} {}",
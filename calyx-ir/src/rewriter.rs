@@ -93,7 +93,9 @@ impl Rewriter {
     /// Rewrite assignments in a guard
     pub fn rewrite_guard<T>(&self, guard: &mut ir::Guard<T>) {
         match guard {
-            ir::Guard::And(l, r) | ir::Guard::Or(l, r) => {
+            ir::Guard::And(l, r)
+            | ir::Guard::Or(l, r)
+            | ir::Guard::Implies(l, r) => {
                 self.rewrite_guard(l.borrow_mut());
                 self.rewrite_guard(r.borrow_mut())
             }
@@ -10,6 +10,7 @@ use calyx_frontend::Attribute;
 use calyx_utils::{CalyxResult, Error, GetName};
 use itertools::Itertools;
 use smallvec::{smallvec, SmallVec};
+use std::collections::HashMap;
 use std::hash::Hash;
 use std::rc::Rc;
 
@@ -86,6 +87,18 @@ impl Port {
         }
     }
 
+    /// Returns the value of this port if it is the output of a constant.
+    pub fn constant_value(&self) -> Option<u64> {
+        if let PortParent::Cell(cell) = &self.parent {
+            if let CellType::Constant { val, .. } =
+                cell.upgrade().borrow().prototype
+            {
+                return Some(val);
+            }
+        }
+        None
+    }
+
     /// Gets name of parent object.
     pub fn get_parent_name(&self) -> Id {
         match &self.parent {
@@ -549,6 +562,33 @@ impl<T> Assignment<T> {
     }
 }
 
+impl<T: Clone> Assignment<T> {
+    /// Clone the assignment, replacing the ports of cells in `map` with the
+    /// ports of the same name on the corresponding new cells. Ports of other
+    /// cells and of groups are kept as-is.
+    pub fn clone_with_cell_map(
+        &self,
+        map: &HashMap<Id, RRC<Cell>>,
+    ) -> Assignment<T> {
+        let mut remap = |port: &RRC<Port>| {
+            let p = port.borrow();
+            if let PortParent::Cell(cell) = &p.parent {
+                let name = cell.upgrade().borrow().name();
+                if let Some(new_cell) = map.get(&name) {
+                    return new_cell.borrow().get(p.name);
+                }
+            }
+            Rc::clone(port)
+        };
+        Assignment {
+            dst: remap(&self.dst),
+            src: remap(&self.src),
+            guard: Box::new(self.guard.map_ports(&mut remap)),
+            attributes: self.attributes.clone(),
+        }
+    }
+}
+
 impl From<Assignment<Nothing>> for Assignment<StaticTiming> {
     /// Turns a normal assignment into a static assignment
     fn from(assgn: Assignment<Nothing>) -> Assignment<StaticTiming> {
@@ -700,6 +740,17 @@ impl StaticGroup {
         self.latency
     }
 
+    /// The smallest latency the group can have so that every cycle its
+    /// assignments are gated on happens while the group is running. This is
+    /// the end of the latest interval mentioned by any guard, and at least
+    /// one cycle.
+    pub fn min_latency(&self) -> u64 {
+        self.assignments
+            .iter()
+            .filter_map(|assign| assign.guard.max_cycle())
+            .fold(1, u64::max)
+    }
+
     /// Get a reference to the named hole if it exists.
     pub fn find<S>(&self, name: S) -> Option<RRC<Port>>
     where
@@ -792,3 +843,82 @@ impl GetName for StaticGroup {
         self.name()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rrc;
+
+    /// A primitive cell with the given output ports.
+    fn cell(name: &str, ports: &[(&str, u64)]) -> RRC<Cell> {
+        let prototype = CellType::Primitive {
+            name: Id::from("std_reg"),
+            param_binding: Box::default(),
+            is_comb: false,
+            latency: None,
+        };
+        let cell = rrc(Cell::new(Id::from(name), prototype));
+        for (port, width) in ports {
+            let port = rrc(Port {
+                name: Id::from(*port),
+                width: *width,
+                direction: Direction::Output,
+                parent: PortParent::Cell(WRC::from(&cell)),
+                attributes: Attributes::default(),
+            });
+            cell.borrow_mut().ports.push(port);
+        }
+        cell
+    }
+
+    /// A guard that reads the given port of the cell.
+    fn port(cell: &RRC<Cell>, name: &str) -> Guard<Nothing> {
+        Guard::port(cell.borrow().get(name))
+    }
+
+    #[test]
+    fn clone_with_cell_map() {
+        let a = cell("a", &[("in", 32), ("done", 1)]);
+        let b = cell("b", &[("out", 32), ("done", 1)]);
+        let a2 = cell("a2", &[("in", 32), ("done", 1)]);
+        let assign = Assignment {
+            dst: a.borrow().get("in"),
+            src: b.borrow().get("out"),
+            guard: Box::new(port(&a, "done") & !port(&b, "done")),
+            attributes: Attributes::default(),
+        };
+        let map = HashMap::from([(Id::from("a"), Rc::clone(&a2))]);
+        let cloned: Assignment<Nothing> = assign.clone_with_cell_map(&map);
+
+        assert!(Rc::ptr_eq(&cloned.dst, &a2.borrow().get("in")));
+        assert!(Rc::ptr_eq(&cloned.src, &assign.src));
+        assert_eq!(*cloned.guard, port(&a2, "done") & !port(&b, "done"));
+        // The original assignment still refers to the old cell.
+        assert_eq!(*assign.guard, port(&a, "done") & !port(&b, "done"));
+    }
+
+    #[test]
+    fn static_group_min_latency() {
+        let r = cell("r", &[("in", 32), ("out", 32), ("done", 1)]);
+        let done: Guard<StaticTiming> = port(&r, "done").into();
+        let at = |beg, end| Guard::Info(StaticTiming::new((beg, end)));
+        let mut group = StaticGroup::new(Id::from("g"), 1);
+        let assign = |guard: Guard<StaticTiming>| Assignment {
+            dst: r.borrow().get("in"),
+            src: r.borrow().get("out"),
+            guard: Box::new(guard),
+            attributes: Attributes::default(),
+        };
+
+        group.assignments.push(assign(done.clone()));
+        assert_eq!(group.min_latency(), 1);
+
+        group.assignments.push(assign(done.clone() & at(1, 3)));
+        assert_eq!(group.min_latency(), 3);
+
+        group
+            .assignments
+            .push(assign(!(at(0, 1) | done & at(4, 6))));
+        assert_eq!(group.min_latency(), 6);
+    }
+}
@@ -1,6 +1,11 @@
 //! Helpers used to examine calyx programs. Used in Xilinx and Yxi backends among others.
-use super::{BoolAttr, Cell, Component, RRC};
+use super::{
+    Assignment, BoolAttr, Canonical, Cell, Component, Guard, Nothing, Port,
+    Printer, RRC,
+};
 use calyx_utils::Id;
+use linked_hash_map::LinkedHashMap;
+use std::collections::{HashMap, HashSet};
 #[cfg(feature = "serialize")]
 use serde::Serialize;
 
@@ -118,3 +123,320 @@ fn dimension_count(mem_id: Id) -> u64 {
         panic!("Cell {} does not seem to be a memory primitive. Memory primitives are expected to have 1-4 dimensions inclusive.", mem_name);
     }
 }
+
+/// Guard sizes of the assignments in one part of a component.
+struct GuardSizes {
+    location: String,
+    total: usize,
+    assigns: Vec<(usize, String)>,
+}
+
+impl GuardSizes {
+    fn new<T>(location: String, assigns: &[Assignment<T>]) -> Self
+    where
+        T: ToString + Clone + Eq,
+    {
+        let assigns: Vec<_> = assigns
+            .iter()
+            .filter(|assign| !assign.guard.is_true())
+            .map(|assign| {
+                (
+                    assign.guard.size(),
+                    Printer::assignment_to_str(assign).trim().to_string(),
+                )
+            })
+            .collect();
+        GuardSizes {
+            location,
+            total: assigns.iter().map(|(size, _)| size).sum(),
+            assigns,
+        }
+    }
+}
+
+/// Panics if the sum of [Guard::size](super::Guard::size) over all guarded
+/// assignments in the component exceeds `max_total_size`. Used by tests to
+/// check that a transformation does not blow up guards. The panic message
+/// breaks the total down by group and assignment.
+pub fn assert_guard_budget(comp: &Component, max_total_size: usize) {
+    let mut parts = vec![];
+    for group in comp.get_groups() {
+        let group = group.borrow();
+        let loc = format!("group {}", group.name());
+        parts.push(GuardSizes::new(loc, &group.assignments));
+    }
+    for group in comp.get_static_groups() {
+        let group = group.borrow();
+        let loc = format!("static group {}", group.name());
+        parts.push(GuardSizes::new(loc, &group.assignments));
+    }
+    for group in &comp.comb_groups {
+        let group = group.borrow();
+        let loc = format!("comb group {}", group.name());
+        parts.push(GuardSizes::new(loc, &group.assignments));
+    }
+    parts.push(GuardSizes::new(
+        "continuous assignments".to_string(),
+        &comp.continuous_assignments,
+    ));
+
+    let total: usize = parts.iter().map(|part| part.total).sum();
+    if total > max_total_size {
+        let mut msg = format!(
+            "total guard size {} exceeds budget of {} in component `{}':",
+            total, max_total_size, comp.name
+        );
+        for part in parts.iter().filter(|part| part.total > 0) {
+            msg.push_str(&format!("\n  {}: {}", part.location, part.total));
+            for (size, assign) in &part.assigns {
+                msg.push_str(&format!("\n    {}: {}", size, assign));
+            }
+        }
+        panic!("{}", msg);
+    }
+}
+
+/// Sub-guards of the non-static assignments in the component that occur in
+/// at least `min_uses` assignments, together with the number of assignments
+/// they occur in, most used first. Guards are compared in their
+/// [canonical](Guard::canonicalize) form, so `a & b` and `b & a` are the
+/// same sub-guard. Sub-guards are counted once per assignment and `true` is
+/// never reported.
+pub fn shared_subguards(
+    comp: &Component,
+    min_uses: usize,
+) -> Vec<(Guard<Nothing>, usize)> {
+    let mut counts: LinkedHashMap<String, (Guard<Nothing>, usize)> =
+        LinkedHashMap::new();
+    comp.iter_assignments(|assign| {
+        let mut subguards = LinkedHashMap::new();
+        collect_subguards(&assign.guard.canonicalize(), &mut subguards);
+        for (key, guard) in subguards {
+            counts.entry(key).or_insert((guard, 0)).1 += 1;
+        }
+    });
+    let mut shared: Vec<_> = counts
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .filter(|(_, count)| *count >= min_uses)
+        .collect();
+    shared.sort_by(|(_, l), (_, r)| r.cmp(l));
+    shared
+}
+
+/// The number of distinct guards in the component that read each port, as
+/// a measure of its fan-out. Guards are compared by their
+/// [canonical key](Guard::canonical_key), so a guard used by several
+/// assignments is counted once. Ports with a high fan-out are candidates
+/// for buffering.
+pub fn guard_port_fanout(comp: &Component) -> HashMap<Canonical, usize> {
+    let mut guards: HashMap<Canonical, HashSet<String>> = HashMap::new();
+    let mut add = |key: String, ports: Vec<RRC<Port>>| {
+        for port in ports {
+            guards
+                .entry(port.borrow().canonical())
+                .or_default()
+                .insert(key.clone());
+        }
+    };
+    comp.iter_assignments(|assign| {
+        add(assign.guard.canonical_key(), assign.guard.all_ports())
+    });
+    comp.iter_static_assignments(|assign| {
+        add(assign.guard.canonical_key(), assign.guard.all_ports())
+    });
+    guards
+        .into_iter()
+        .map(|(port, keys)| (port, keys.len()))
+        .collect()
+}
+
+/// Add every non-true sub-guard of `guard` to `out`, keyed by its printed
+/// form.
+fn collect_subguards(
+    guard: &Guard<Nothing>,
+    out: &mut LinkedHashMap<String, Guard<Nothing>>,
+) {
+    if guard.is_true() {
+        return;
+    }
+    out.entry(Printer::guard_str(guard))
+        .or_insert_with(|| guard.clone());
+    match guard {
+        Guard::And(l, r) | Guard::Or(l, r) | Guard::Implies(l, r) => {
+            collect_subguards(l, out);
+            collect_subguards(r, out);
+        }
+        Guard::Not(g) => collect_subguards(g, out),
+        Guard::True
+        | Guard::CompOp(..)
+        | Guard::Port(_)
+        | Guard::Info(_) => {}
+    }
+}
+
+/// Partition assignments by their guards, with guards compared by their
+/// [canonical key](Guard::canonical_key) so that `a & b` and `b & a` fall in
+/// the same class. Each class is paired with the guard of its first
+/// assignment, and classes and assignments keep their original order.
+pub fn group_by_guard<T>(
+    assigns: Vec<Assignment<T>>,
+) -> Vec<(Guard<T>, Vec<Assignment<T>>)>
+where
+    T: Clone + Eq + ToString,
+{
+    let mut classes: LinkedHashMap<String, (Guard<T>, Vec<Assignment<T>>)> =
+        LinkedHashMap::new();
+    for assign in assigns {
+        let key = assign.guard.canonical_key();
+        classes
+            .entry(key)
+            .or_insert_with(|| ((*assign.guard).clone(), vec![]))
+            .1
+            .push(assign);
+    }
+    classes.into_iter().map(|(_, class)| class).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Builder, LibrarySignatures};
+
+    /// A component with a guard of size 4 in a group and a guard of size 3
+    /// in a continuous assignment.
+    fn component() -> Component {
+        let mut comp = Component::new("main", vec![], true, false, None);
+        let done = comp.signature.borrow().get("done");
+        let sigs = LibrarySignatures::default();
+        let mut builder = Builder::new(&mut comp, &sigs);
+        let a = builder.add_constant(1, 1).borrow().get("out");
+        let b = builder.add_constant(0, 1).borrow().get("out");
+
+        let group = builder.add_group("g");
+        let guard = !(Guard::port(a.clone()) & Guard::port(b.clone()));
+        let g_done = group.borrow().get("done");
+        let assign = builder.build_assignment(g_done, a.clone(), guard);
+        group.borrow_mut().assignments.push(assign);
+
+        let guard = Guard::port(a.clone()) | Guard::port(b);
+        let assign = builder.build_assignment(done, a, guard);
+        builder.add_continuous_assignments(vec![assign]);
+        comp
+    }
+
+    #[test]
+    fn guard_budget_within() {
+        assert_guard_budget(&component(), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "total guard size 7 exceeds budget of 6")]
+    fn guard_budget_exceeded() {
+        assert_guard_budget(&component(), 6);
+    }
+
+    #[test]
+    fn shared_subguards_counts() {
+        let mut comp = Component::new("main", vec![], true, false, None);
+        let go = comp.signature.borrow().get("go");
+        let reset = comp.signature.borrow().get("reset");
+        let done = comp.signature.borrow().get("done");
+        let sigs = LibrarySignatures::default();
+        let mut builder = Builder::new(&mut comp, &sigs);
+        let valid = builder.add_constant(1, 1).borrow().get("out");
+        let a = builder.add_constant(0, 1).borrow().get("out");
+
+        let guards = vec![
+            Guard::port(reset.clone()) & Guard::port(a.clone()),
+            Guard::port(a.clone()) & Guard::port(reset.clone()),
+            Guard::port(reset.clone()) | Guard::port(go),
+        ];
+        assert_eq!(guards[0].canonicalize(), guards[1].canonicalize());
+        let assigns = guards
+            .into_iter()
+            .map(|g| builder.build_assignment(done.clone(), valid.clone(), g))
+            .collect();
+        builder.add_continuous_assignments(assigns);
+
+        let shared = shared_subguards(&comp, 2);
+        let shared: Vec<_> = shared
+            .iter()
+            .map(|(g, count)| (Printer::guard_str(g), *count))
+            .collect();
+        assert_eq!(
+            shared,
+            vec![
+                ("reset".to_string(), 3),
+                ("1'd0 & reset".to_string(), 2),
+                ("1'd0".to_string(), 2)
+            ]
+        );
+        assert_eq!(shared_subguards(&comp, 3).len(), 1);
+    }
+
+    #[test]
+    fn guard_port_fanout_counts() {
+        let mut comp = Component::new("main", vec![], true, false, None);
+        let go = comp.signature.borrow().get("go");
+        let reset = comp.signature.borrow().get("reset");
+        let done = comp.signature.borrow().get("done");
+        let sigs = LibrarySignatures::default();
+        let mut builder = Builder::new(&mut comp, &sigs);
+        let valid = builder.add_constant(1, 1).borrow().get("out");
+        let a = builder.add_constant(0, 1).borrow().get("out");
+
+        let group = builder.add_group("g");
+        let g_done = group.borrow().get("done");
+        let guard = Guard::port(reset.clone()) & Guard::port(a.clone());
+        let assign = builder.build_assignment(g_done, valid.clone(), guard);
+        group.borrow_mut().assignments.push(assign);
+
+        // The same guard as the group's, with its operands swapped.
+        let guards = vec![
+            Guard::port(a.clone()) & Guard::port(reset.clone()),
+            Guard::port(reset.clone()) | Guard::port(go.clone()),
+        ];
+        let assigns = guards
+            .into_iter()
+            .map(|g| builder.build_assignment(done.clone(), valid.clone(), g))
+            .collect();
+        builder.add_continuous_assignments(assigns);
+
+        let fanout = guard_port_fanout(&comp);
+        assert_eq!(fanout.len(), 3);
+        assert_eq!(fanout[&reset.borrow().canonical()], 2);
+        assert_eq!(fanout[&a.borrow().canonical()], 1);
+        assert_eq!(fanout[&go.borrow().canonical()], 1);
+    }
+
+    #[test]
+    fn group_by_guard_classes() {
+        let mut comp = Component::new("main", vec![], true, false, None);
+        let go = comp.signature.borrow().get("go");
+        let reset = comp.signature.borrow().get("reset");
+        let done = comp.signature.borrow().get("done");
+        let sigs = LibrarySignatures::default();
+        let builder = Builder::new(&mut comp, &sigs);
+
+        let guards = vec![
+            Guard::port(reset.clone()) & Guard::port(go.clone()),
+            Guard::port(reset.clone()) | Guard::port(go.clone()),
+            Guard::port(go.clone()) & Guard::port(reset.clone()),
+        ];
+        let assigns = guards
+            .into_iter()
+            .map(|g| builder.build_assignment(done.clone(), go.clone(), g))
+            .collect();
+
+        let classes = group_by_guard(assigns);
+        assert_eq!(classes.len(), 2);
+        let (and, and_assigns) = &classes[0];
+        assert_eq!(Printer::guard_str(and), "reset & go");
+        assert_eq!(and_assigns.len(), 2);
+        assert_eq!(Printer::guard_str(&and_assigns[1].guard), "go & reset");
+        let (or, or_assigns) = &classes[1];
+        assert_eq!(Printer::guard_str(or), "reset | go");
+        assert_eq!(or_assigns.len(), 1);
+    }
+}
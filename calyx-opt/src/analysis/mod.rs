@@ -18,6 +18,7 @@ mod port_interface;
 mod promotion_analysis;
 pub mod reaching_defns;
 mod read_write_set;
+mod register_writes;
 mod schedule_conflicts;
 mod share_set;
 mod static_fsm;
@@ -41,6 +42,7 @@ pub use live_range_analysis::LiveRangeAnalysis;
 pub use port_interface::PortInterface;
 pub use promotion_analysis::PromotionAnalysis;
 pub use read_write_set::{AssignmentAnalysis, ReadWriteSet};
+pub use register_writes::RegWrites;
 pub use schedule_conflicts::ScheduleConflicts;
 pub use share_set::ShareSet;
 pub use static_fsm::{FSMEncoding, StaticFSM};
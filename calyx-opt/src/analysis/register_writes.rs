@@ -0,0 +1,99 @@
+use super::ReadWriteSet;
+use calyx_ir::{self as ir, RRC};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// The indices of the assignments to the ports of a register in an
+/// assignment list.
+#[derive(Default)]
+pub struct RegWrites {
+    /// Assignments to `in` of a value other than the register's own output.
+    pub data: Vec<usize>,
+    /// Assignments of the register's own output to `in`.
+    pub feedback: Vec<usize>,
+    /// Assignments to `write_en`.
+    pub write_en: Vec<usize>,
+}
+
+/// If the port is an input of a `std_reg` that is not a `ref` cell, return
+/// the register.
+fn reg_of(port: &ir::Port) -> Option<RRC<ir::Cell>> {
+    let ir::PortParent::Cell(cell) = &port.parent else {
+        return None;
+    };
+    let cell = cell.upgrade();
+    let is_reg = {
+        let cell = cell.borrow();
+        cell.is_primitive(Some("std_reg")) && !cell.is_reference()
+    };
+    is_reg.then_some(cell)
+}
+
+impl RegWrites {
+    /// The writes to each register in the assignment list that is not a
+    /// `ref` cell. Registers in `skip` are left out.
+    pub fn collect<T>(
+        assigns: &[ir::Assignment<T>],
+        skip: &HashSet<ir::Id>,
+    ) -> HashMap<ir::Id, RegWrites> {
+        let mut regs: HashMap<ir::Id, RegWrites> = HashMap::new();
+        for (idx, assign) in assigns.iter().enumerate() {
+            let dst = assign.dst.borrow();
+            let Some(reg) = reg_of(&dst) else {
+                continue;
+            };
+            let name = reg.borrow().name();
+            if skip.contains(&name) {
+                continue;
+            }
+            let writes = regs.entry(name).or_default();
+            if dst.name == "write_en" {
+                writes.write_en.push(idx);
+            } else if dst.name == "in" {
+                if Rc::ptr_eq(&assign.src, &reg.borrow().get("out")) {
+                    writes.feedback.push(idx);
+                } else {
+                    writes.data.push(idx);
+                }
+            }
+        }
+        regs
+    }
+
+    /// The cells whose `done` port is read anywhere in the component,
+    /// including by the control program. Changing when such a register is
+    /// enabled changes when its `done` signal is high.
+    pub fn done_read(comp: &ir::Component) -> HashSet<ir::Id> {
+        let mut done_read = HashSet::new();
+        let mut read = |port: RRC<ir::Port>| {
+            let port = port.borrow();
+            if port.name == "done" && !port.is_hole() {
+                done_read.insert(port.get_parent_name());
+            }
+        };
+        comp.iter_assignments(|assign| {
+            ReadWriteSet::port_reads(assign).for_each(&mut read)
+        });
+        comp.iter_static_assignments(|assign| {
+            ReadWriteSet::port_reads(assign).for_each(&mut read)
+        });
+        let (control_reads, _) =
+            ReadWriteSet::control_port_read_write_set::<true>(
+                &comp.control.borrow(),
+            );
+        control_reads.into_iter().for_each(read);
+        done_read
+    }
+
+    /// Remove the assignments at the given indices.
+    pub fn remove<T>(
+        assigns: &mut Vec<ir::Assignment<T>>,
+        removed: &HashSet<usize>,
+    ) {
+        let mut idx = 0;
+        assigns.retain(|_| {
+            idx += 1;
+            !removed.contains(&(idx - 1))
+        });
+    }
+}
@@ -245,6 +245,15 @@ impl Node {
                     Self::preprocess_static_interface_guard(*r, comp_sig);
                 ir::Guard::or(left, right)
             }
+            ir::Guard::Implies(l, r) => {
+                let left = Self::preprocess_static_interface_guard(
+                    *l,
+                    Rc::clone(&comp_sig),
+                );
+                let right =
+                    Self::preprocess_static_interface_guard(*r, comp_sig);
+                left.implies_guard(right)
+            }
             ir::Guard::Not(g) => {
                 let a = Self::preprocess_static_interface_guard(*g, comp_sig);
                 ir::Guard::Not(Box::new(a))
@@ -1234,6 +1243,10 @@ impl SingleNode {
                 self.make_guard_dyn(*l, global_view, ignore_timing, builder),
                 self.make_guard_dyn(*r, global_view, ignore_timing, builder),
             )),
+            ir::Guard::Implies(l, r) => Box::new(ir::Guard::Implies(
+                self.make_guard_dyn(*l, global_view, ignore_timing, builder),
+                self.make_guard_dyn(*r, global_view, ignore_timing, builder),
+            )),
             ir::Guard::Not(g) => Box::new(ir::Guard::Not(self.make_guard_dyn(
                 *g,
                 global_view,
@@ -1,14 +1,20 @@
 //! Defines the default passes available to [PassManager].
 use crate::pass_manager::PassResult;
 use crate::passes::{
-    AddGuard, Canonicalize, CellShare, ClkInsertion, CollapseControl, CombProp,
-    CompileInvoke, CompileRepeat, CompileStatic, ComponentInliner,
-    DataPathInfer, DeadAssignmentRemoval, DeadCellRemoval, DeadGroupRemoval,
-    DefaultAssigns, Externalize, GoInsertion, GroupToInvoke, GroupToSeq,
-    InferShare, LowerGuards, MergeAssign, Papercut, RemoveIds, ResetInsertion,
-    SimplifyStaticGuards, SimplifyWithControl, StaticFSMOpts, StaticInference,
-    StaticInliner, StaticPromotion, SynthesisPapercut, TopDownCompileControl,
-    UnrollBounded, WellFormed, WireInliner, WrapMain,
+    AddGuard, BalanceGuards, BypassGuardWires, Canonicalize, CellShare,
+    ClkInsertion, CollapseControl, CombProp, CompileInvoke, CompileRepeat,
+    CompileStatic, ComponentInliner, DataPathInfer, DeadAssignmentRemoval,
+    DeadCellRemoval, DeadGroupRemoval, DeadWrites, DecodeGuards, DefaultAssigns,
+    Externalize, FeedbackToEnable, FoldDegenerateGuards, FoldNeqRanges,
+    FoldRangeComparisons, FoldSubComparisons, FuseEqChains, GoInsertion,
+    GroupToInvoke, GroupToSeq, GuardNnf, HoistInvariantComparisons, InferShare,
+    LiftParGuards, LowerGuards, MergeAssign, MergeConstants, OneHotSelectors,
+    Papercut, PruneUnreachableStates, RemoveIds, RemoveImpliedGuards,
+    RemoveRetentionWrites, ResetInsertion, SelfComparison, ShareComparisons,
+    SimplifyDontCare, SimplifyStaticGuards, SimplifyWithControl,
+    SpecializeBranches, StaticFSMOpts, StaticInference, StaticInliner,
+    StaticPromotion, StripActivationGuards, SynthesisPapercut,
+    TopDownCompileControl, UnrollBounded, WellFormed, WireInliner, WrapMain,
 };
 use crate::passes_experimental::{
     CompileSync, CompileSyncWithoutSyncReg, DiscoverExternal, ExternalToRef,
@@ -74,6 +80,29 @@ impl PassManager {
         pm.register_pass::<GroupToInvoke>()?;
         pm.register_pass::<ParToSeq>()?;
         pm.register_pass::<LowerGuards>()?;
+        pm.register_pass::<DecodeGuards>()?;
+        pm.register_pass::<FoldDegenerateGuards>()?;
+        pm.register_pass::<FoldNeqRanges>()?;
+        pm.register_pass::<GuardNnf>()?;
+        pm.register_pass::<RemoveImpliedGuards>()?;
+        pm.register_pass::<SimplifyDontCare>()?;
+        pm.register_pass::<ShareComparisons>()?;
+        pm.register_pass::<PruneUnreachableStates>()?;
+        pm.register_pass::<FeedbackToEnable>()?;
+        pm.register_pass::<MergeConstants>()?;
+        pm.register_pass::<BypassGuardWires>()?;
+        pm.register_pass::<SpecializeBranches>()?;
+        pm.register_pass::<FoldRangeComparisons>()?;
+        pm.register_pass::<BalanceGuards>()?;
+        pm.register_pass::<HoistInvariantComparisons>()?;
+        pm.register_pass::<RemoveRetentionWrites>()?;
+        pm.register_pass::<OneHotSelectors>()?;
+        pm.register_pass::<FoldSubComparisons>()?;
+        pm.register_pass::<LiftParGuards>()?;
+        pm.register_pass::<FuseEqChains>()?;
+        pm.register_pass::<StripActivationGuards>()?;
+        pm.register_diagnostic::<SelfComparison>()?;
+        pm.register_diagnostic::<DeadWrites>()?;
         pm.register_pass::<HoleInliner>()?;
         pm.register_pass::<RemoveIds>()?;
         pm.register_pass::<ExternalToRef>()?;
@@ -81,7 +110,16 @@ impl PassManager {
         //add metadata
         pm.register_pass::<Metadata>()?;
 
-        register_alias!(pm, "validate", [WellFormed, Papercut, Canonicalize]);
+        register_alias!(
+            pm,
+            "validate",
+            [
+                SimplifyDontCare, // Removes @dont_care assignments before they are checked as writes
+                WellFormed,
+                Papercut,
+                Canonicalize
+            ]
+        );
         register_alias!(
             pm,
             "pre-opt",
@@ -0,0 +1,75 @@
+use crate::traversal::{
+    Action, ConstructVisitor, Named, ParseVal, PassOpt, VisResult, Visitor,
+};
+use calyx_ir as ir;
+
+/// Rebuilds long chains of `&` or `|` in guards as balanced trees to reduce
+/// the depth of the logic they synthesize to. For example, the chain:
+/// ```
+/// ((a.out & b.out) & c.out) & d.out
+/// ```
+/// becomes:
+/// ```
+/// (a.out & b.out) & (c.out & d.out)
+/// ```
+/// which is two gates deep instead of three. Chains with fewer operands than
+/// the `threshold` option are left as-is. See [ir::Guard::balance].
+pub struct BalanceGuards {
+    /// The number of operands a chain needs to be balanced.
+    threshold: usize,
+}
+
+impl ConstructVisitor for BalanceGuards {
+    fn from(ctx: &ir::Context) -> calyx_utils::CalyxResult<Self>
+    where
+        Self: Sized,
+    {
+        let opts = Self::get_opts(ctx);
+        Ok(BalanceGuards {
+            threshold: opts[&"threshold"].pos_num().unwrap_or(4) as usize,
+        })
+    }
+
+    fn clear_data(&mut self) {
+        /* do nothing */
+    }
+}
+
+impl Named for BalanceGuards {
+    fn name() -> &'static str {
+        "balance-guards"
+    }
+
+    fn description() -> &'static str {
+        "rebuild long chains of & and | in guards as balanced trees"
+    }
+
+    fn opts() -> Vec<PassOpt> {
+        vec![PassOpt::new(
+            "threshold",
+            "the number of operands a chain needs to be balanced",
+            ParseVal::Num(4),
+            PassOpt::parse_num,
+        )]
+    }
+}
+
+impl Visitor for BalanceGuards {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let threshold = self.threshold;
+        comp.for_each_assignment(|assign| {
+            assign.guard.update(|g| g.balance(threshold))
+        });
+        comp.for_each_static_assignment(|assign| {
+            assign.guard.update(|g| g.balance(threshold))
+        });
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
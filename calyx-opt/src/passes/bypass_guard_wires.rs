@@ -0,0 +1,158 @@
+use crate::analysis;
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Rewrites guards to read the source of a `std_wire` instead of the wire.
+///
+/// A wire whose only driver is an unguarded continuous assignment always
+/// has the value of its source, so guards reading it can read the source
+/// directly. Chains of such wires are followed to the first port that is not
+/// the output of one. For example:
+/// ```
+/// w0.in = r.out;
+/// w1.in = w0.out;
+/// x.in = w1.out ? 8'd1;
+/// ```
+/// becomes:
+/// ```
+/// w0.in = r.out;
+/// w1.in = w0.out;
+/// x.in = r.out ? 8'd1;
+/// ```
+/// Only wires are bypassed, so guards never read through registers or
+/// other stateful cells. Wires that are external, `ref` cells, or bound by
+/// an invoke are left alone. The wires themselves are not removed, which
+/// can be done by `dead-cell-removal` once nothing reads them.
+#[derive(Default)]
+pub struct BypassGuardWires;
+
+impl Named for BypassGuardWires {
+    fn name() -> &'static str {
+        "bypass-guard-wires"
+    }
+
+    fn description() -> &'static str {
+        "make guards read the sources of std_wire passthroughs"
+    }
+}
+
+/// If the port is the input of one of the `wires`, return the wire's name.
+fn wire_in(port: &ir::Port, wires: &HashSet<ir::Id>) -> Option<ir::Id> {
+    if port.name != "in" {
+        return None;
+    }
+    let ir::PortParent::Cell(cell) = &port.parent else {
+        return None;
+    };
+    let name = cell.upgrade().borrow().name();
+    wires.contains(&name).then_some(name)
+}
+
+/// Follow the wire passthroughs starting at `port` to the port driving
+/// them.
+fn ultimate_source(
+    port: &ir::RRC<ir::Port>,
+    sources: &HashMap<ir::Id, ir::RRC<ir::Port>>,
+) -> ir::RRC<ir::Port> {
+    let mut cur = Rc::clone(port);
+    // A longer chain would be a combinational loop.
+    for _ in 0..sources.len() {
+        let next = {
+            let cur = cur.borrow();
+            match &cur.parent {
+                ir::PortParent::Cell(cell) if cur.name == "out" => {
+                    sources.get(&cell.upgrade().borrow().name()).cloned()
+                }
+                _ => None,
+            }
+        };
+        let Some(next) = next else {
+            break;
+        };
+        cur = next;
+    }
+    cur
+}
+
+impl Visitor for BypassGuardWires {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let mut wires: HashSet<ir::Id> = comp
+            .cells
+            .iter()
+            .filter(|cell_ref| {
+                let cell = cell_ref.borrow();
+                cell.is_primitive(Some("std_wire"))
+                    && !cell.is_reference()
+                    && !cell.attributes.has(ir::BoolAttr::External)
+            })
+            .map(|cell| cell.borrow().name())
+            .collect();
+        let bindings =
+            analysis::ControlPorts::<true>::from(&*comp.control.borrow())
+                .get_all_bindings();
+        for (cells, ports) in bindings.into_values().flatten() {
+            for (_, cell) in cells {
+                wires.remove(&cell.borrow().name());
+            }
+            for (_, port) in ports {
+                if let Some(parent) = wire_in(&port.borrow(), &wires) {
+                    wires.remove(&parent);
+                }
+            }
+        }
+
+        let mut writes: HashMap<ir::Id, usize> = HashMap::new();
+        let mut count = |dst: &ir::RRC<ir::Port>| {
+            if let Some(wire) = wire_in(&dst.borrow(), &wires) {
+                *writes.entry(wire).or_default() += 1;
+            }
+        };
+        comp.iter_assignments(|assign| count(&assign.dst));
+        comp.iter_static_assignments(|assign| count(&assign.dst));
+
+        let mut sources: HashMap<ir::Id, ir::RRC<ir::Port>> = HashMap::new();
+        for assign in &comp.continuous_assignments {
+            let Some(wire) = wire_in(&assign.dst.borrow(), &wires) else {
+                continue;
+            };
+            if writes[&wire] == 1
+                && assign.guard.is_true()
+                && !assign.src.borrow().is_hole()
+            {
+                sources.insert(wire, Rc::clone(&assign.src));
+            }
+        }
+        if sources.is_empty() {
+            return Ok(Action::Stop);
+        }
+
+        let bypass: Vec<_> = sources
+            .keys()
+            .map(|wire| {
+                let out = comp.find_cell(*wire).unwrap().borrow().get("out");
+                let src = ultimate_source(&out, &sources);
+                (out, src)
+            })
+            .collect();
+        comp.for_each_assignment(|assign| {
+            for (out, src) in &bypass {
+                assign.guard.replace_port(out, src);
+            }
+        });
+        comp.for_each_static_assignment(|assign| {
+            for (out, src) in &bypass {
+                assign.guard.replace_port(out, src);
+            }
+        });
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
@@ -512,6 +512,7 @@ impl CompileStatic {
 
             }
             ir::Guard::Or(_, _) => unreachable!("Shouldn't try to get interval from guard if there is an 'or' in the guard"),
+            ir::Guard::Implies(_, _) => unreachable!("Shouldn't try to get interval from guard if there is an '->' in the guard"),
         }
     }
 
@@ -837,6 +838,11 @@ impl CompileStatic {
             let right = Self::make_guard_dyn_one_cycle_static_comp(*r, Rc::clone(&comp_sig));
             ir::Guard::and(left, right)
         }
+        ir::Guard::Implies(l, r) => {
+            let left = Self::make_guard_dyn_one_cycle_static_comp(*l, Rc::clone(&comp_sig));
+            let right = Self::make_guard_dyn_one_cycle_static_comp(*r, Rc::clone(&comp_sig));
+            left.implies_guard(right)
+        }
         ir::Guard::Not(g) => {
             let f = Self::make_guard_dyn_one_cycle_static_comp(*g, Rc::clone(&comp_sig));
             ir::Guard::Not(Box::new(f))
@@ -0,0 +1,184 @@
+use crate::traversal::{
+    Action, DiagnosticContext, DiagnosticPass, Named, VisResult, Visitor,
+};
+use calyx_ir::{self as ir, LibrarySignatures};
+use calyx_utils::{Error, WithPos};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Warns about writes to stateful cells whose guard can never be true at the
+/// same time as the cell's write enable:
+/// ```
+/// r.in = s.out == 2'd0 ? 8'd1; // dead
+/// r.write_en = s.out == 2'd1 ? 1'd1;
+/// ```
+/// The data port of a primitive is only read when its `write_en` port is
+/// high, so such an assignment has no effect. A write is reported when its
+/// guard is proven exclusive with the guard of every assignment to the
+/// enable by [ir::Guard::is_mutually_exclusive]. Cells that are invoked,
+/// passed to an invoke, or enabled by a static group are not checked.
+#[derive(Default)]
+pub struct DeadWrites {
+    /// Diagnostic context for reporting warnings
+    diag: DiagnosticContext,
+}
+
+impl Named for DeadWrites {
+    fn name() -> &'static str {
+        "dead-writes"
+    }
+
+    fn description() -> &'static str {
+        "warn about writes that can never happen while the enable is high"
+    }
+}
+
+impl DiagnosticPass for DeadWrites {
+    fn diagnostics(&self) -> &DiagnosticContext {
+        &self.diag
+    }
+}
+
+/// If the port is an input of a primitive with a `write_en` port that is not
+/// a `ref` cell, return the name of the cell.
+fn enabled_cell(port: &ir::Port) -> Option<ir::Id> {
+    let ir::PortParent::Cell(cell) = &port.parent else {
+        return None;
+    };
+    let cell = cell.upgrade();
+    let cell = cell.borrow();
+    (cell.is_primitive::<&str>(None)
+        && !cell.is_reference()
+        && cell.find("write_en").is_some())
+    .then(|| cell.name())
+}
+
+/// Collect the cells that are invoked or passed to an invoke as `ref` cells.
+fn invoked_cells(con: &ir::Control, acc: &mut HashSet<ir::Id>) {
+    match con {
+        ir::Control::Seq(ir::Seq { stmts, .. })
+        | ir::Control::Par(ir::Par { stmts, .. }) => {
+            stmts.iter().for_each(|stmt| invoked_cells(stmt, acc))
+        }
+        ir::Control::If(ir::If {
+            tbranch, fbranch, ..
+        }) => {
+            invoked_cells(tbranch, acc);
+            invoked_cells(fbranch, acc);
+        }
+        ir::Control::While(ir::While { body, .. })
+        | ir::Control::Repeat(ir::Repeat { body, .. }) => {
+            invoked_cells(body, acc)
+        }
+        ir::Control::Invoke(ir::Invoke {
+            comp, ref_cells, ..
+        }) => {
+            acc.insert(comp.borrow().name());
+            acc.extend(ref_cells.iter().map(|(_, cell)| cell.borrow().name()));
+        }
+        ir::Control::Enable(_) | ir::Control::Empty(_) => {}
+        ir::Control::Static(sc) => static_invoked_cells(sc, acc),
+    }
+}
+
+fn static_invoked_cells(con: &ir::StaticControl, acc: &mut HashSet<ir::Id>) {
+    match con {
+        ir::StaticControl::Seq(ir::StaticSeq { stmts, .. })
+        | ir::StaticControl::Par(ir::StaticPar { stmts, .. }) => stmts
+            .iter()
+            .for_each(|stmt| static_invoked_cells(stmt, acc)),
+        ir::StaticControl::If(ir::StaticIf {
+            tbranch, fbranch, ..
+        }) => {
+            static_invoked_cells(tbranch, acc);
+            static_invoked_cells(fbranch, acc);
+        }
+        ir::StaticControl::Repeat(ir::StaticRepeat { body, .. }) => {
+            static_invoked_cells(body, acc)
+        }
+        ir::StaticControl::Invoke(ir::StaticInvoke {
+            comp, ref_cells, ..
+        }) => {
+            acc.insert(comp.borrow().name());
+            acc.extend(ref_cells.iter().map(|(_, cell)| cell.borrow().name()));
+        }
+        ir::StaticControl::Enable(_) | ir::StaticControl::Empty(_) => {}
+    }
+}
+
+/// The condition under which an assignment to a `write_en` port drives it
+/// high, or `None` if it never does.
+fn enable_of(
+    assign: &ir::Assignment<ir::Nothing>,
+) -> Option<ir::Guard<ir::Nothing>> {
+    let guard = (*assign.guard).clone();
+    match assign.src.borrow().constant_value() {
+        Some(0) => None,
+        Some(_) => Some(guard),
+        None => Some(guard & ir::Guard::port(Rc::clone(&assign.src))),
+    }
+}
+
+impl Visitor for DeadWrites {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let mut skipped = HashSet::new();
+        invoked_cells(&comp.control.borrow(), &mut skipped);
+        comp.iter_static_assignments(|assign| {
+            let dst = assign.dst.borrow();
+            if let Some(cell) = enabled_cell(&dst) {
+                skipped.insert(cell);
+            }
+        });
+
+        let mut enables: HashMap<ir::Id, Vec<ir::Guard<ir::Nothing>>> =
+            HashMap::new();
+        comp.iter_assignments(|assign| {
+            let dst = assign.dst.borrow();
+            if dst.name != "write_en" {
+                return;
+            }
+            if let Some(cell) = enabled_cell(&dst) {
+                let guards = enables.entry(cell).or_default();
+                guards.extend(enable_of(assign));
+            }
+        });
+
+        comp.iter_assignments(|assign| {
+            let dst = assign.dst.borrow();
+            if dst.name != "in" && dst.name != "write_data" {
+                return;
+            }
+            let Some(cell) = enabled_cell(&dst) else {
+                return;
+            };
+            if skipped.contains(&cell) {
+                return;
+            }
+            let Some(guards) = enables.get(&cell) else {
+                return;
+            };
+            let dead = !guards.is_empty()
+                && guards
+                    .iter()
+                    .all(|en| assign.guard.is_mutually_exclusive(en));
+            if !dead {
+                return;
+            }
+            self.diag.warning(
+                Error::misc(format!(
+                    "Assignment to `{}' is dead: its guard is never true while `{cell}.write_en' is high",
+                    dst.canonical(),
+                ))
+                .with_pos(&assign.attributes),
+            );
+        });
+
+        // This pass doesn't need to traverse the control program.
+        Ok(Action::Stop)
+    }
+}
@@ -0,0 +1,306 @@
+use crate::traversal::{
+    Action, ConstructVisitor, Named, ParseVal, PassOpt, VisResult, Visitor,
+};
+use calyx_ir::{self as ir, RRC};
+use itertools::Itertools;
+use linked_hash_map::LinkedHashMap;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Replaces a disjunction of many equalities between a port and constants,
+/// such as the FSM state checks generated by control compilation:
+/// ```
+/// fsm.out == 0 | fsm.out == 1 | fsm.out == 4 | ...
+/// ```
+/// with a single check against a one-hot decoding of the port:
+/// ```
+/// onehot.left = 1'd1; onehot.right = fsm.out; // onehot.out = 1 << fsm.out
+/// decode.left = onehot.out; decode.right = 0b10011;
+/// x.in = decode.out != 0 ? ...
+/// ```
+/// This replaces a wide OR tree of comparators with a shifter, a bitwise AND,
+/// and a single comparison. Only ports with at least `threshold` equalities in
+/// a disjunction are decoded. Decoders are shared by all guards that read the
+/// same port.
+pub struct DecodeGuards {
+    /// Minimum number of equalities on a port needed to decode a disjunction.
+    threshold: u64,
+    /// One-hot decoding of a port at a given width.
+    onehot: HashMap<(ir::Canonical, u64), RRC<ir::Port>>,
+    /// Membership check of a decoded port against a mask.
+    members: HashMap<(ir::Canonical, u64), RRC<ir::Port>>,
+}
+
+impl Named for DecodeGuards {
+    fn name() -> &'static str {
+        "decode-guards"
+    }
+
+    fn description() -> &'static str {
+        "replace disjunctions of equalities with a one-hot decoder"
+    }
+
+    fn opts() -> Vec<PassOpt> {
+        vec![PassOpt::new(
+            "threshold",
+            "minimum number of equalities on a port needed to use a decoder",
+            ParseVal::Num(4),
+            PassOpt::parse_num,
+        )]
+    }
+}
+
+impl ConstructVisitor for DecodeGuards {
+    fn from(ctx: &ir::Context) -> calyx_utils::CalyxResult<Self>
+    where
+        Self: Sized,
+    {
+        let opts = Self::get_opts(ctx);
+        Ok(DecodeGuards {
+            threshold: opts[&"threshold"].pos_num().unwrap_or(4),
+            onehot: HashMap::new(),
+            members: HashMap::new(),
+        })
+    }
+
+    fn clear_data(&mut self) {
+        self.onehot.clear();
+        self.members.clear();
+    }
+}
+
+/// If the guard is an equality between a port and a constant, return the
+/// port and the value of the constant.
+fn const_eq<T>(guard: &ir::Guard<T>) -> Option<(RRC<ir::Port>, u64)> {
+    if let ir::Guard::CompOp(ir::PortComp::Eq, l, r) = guard {
+        match (l.borrow().constant_value(), r.borrow().constant_value()) {
+            (None, Some(v)) => return Some((Rc::clone(l), v)),
+            (Some(v), None) => return Some((Rc::clone(r), v)),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Collect the disjuncts of a tree of `Or` nodes.
+fn flatten_or<T>(guard: ir::Guard<T>, acc: &mut Vec<ir::Guard<T>>) {
+    match guard {
+        ir::Guard::Or(l, r) => {
+            flatten_or(*l, acc);
+            flatten_or(*r, acc);
+        }
+        g => acc.push(g),
+    }
+}
+
+impl DecodeGuards {
+    /// Returns a 1-bit guard that is true iff `port` is equal to one of the
+    /// values in `vals`, building the decoder for the port if needed.
+    fn member<T>(
+        &mut self,
+        port: RRC<ir::Port>,
+        vals: &[u64],
+        builder: &mut ir::Builder,
+    ) -> ir::Guard<T> {
+        let mask = vals.iter().fold(0u64, |mask, v| mask | (1 << v));
+        let port_width = port.borrow().width;
+        let width = port_width.max((64 - mask.leading_zeros()) as u64);
+        let canon = port.borrow().canonical();
+
+        let onehot = if let Some(out) = self.onehot.get(&(canon.clone(), width))
+        {
+            Rc::clone(out)
+        } else {
+            let mut assigns = Vec::with_capacity(3);
+            let amount = if width > port_width {
+                let pad = builder.add_primitive(
+                    "pad",
+                    "std_pad",
+                    &[port_width, width],
+                );
+                assigns.push(builder.build_assignment(
+                    pad.borrow().get("in"),
+                    Rc::clone(&port),
+                    ir::Guard::True,
+                ));
+                let out = pad.borrow().get("out");
+                out
+            } else {
+                Rc::clone(&port)
+            };
+            let one = builder.add_constant(1, width);
+            let shift = builder.add_primitive("onehot", "std_lsh", &[width]);
+            assigns.push(builder.build_assignment(
+                shift.borrow().get("left"),
+                one.borrow().get("out"),
+                ir::Guard::True,
+            ));
+            assigns.push(builder.build_assignment(
+                shift.borrow().get("right"),
+                amount,
+                ir::Guard::True,
+            ));
+            builder.add_continuous_assignments(assigns);
+            let out = shift.borrow().get("out");
+            self.onehot.insert((canon.clone(), width), Rc::clone(&out));
+            out
+        };
+
+        let member = if let Some(out) = self.members.get(&(canon.clone(), mask))
+        {
+            Rc::clone(out)
+        } else {
+            let mask_const = builder.add_constant(mask, width);
+            let decode = builder.add_primitive("decode", "std_and", &[width]);
+            let assigns = vec![
+                builder.build_assignment(
+                    decode.borrow().get("left"),
+                    onehot,
+                    ir::Guard::True,
+                ),
+                builder.build_assignment(
+                    decode.borrow().get("right"),
+                    mask_const.borrow().get("out"),
+                    ir::Guard::True,
+                ),
+            ];
+            builder.add_continuous_assignments(assigns);
+            let out = decode.borrow().get("out");
+            self.members.insert((canon, mask), Rc::clone(&out));
+            out
+        };
+
+        let zero = builder.add_constant(0, width);
+        let zero_out = zero.borrow().get("out");
+        ir::Guard::CompOp(ir::PortComp::Neq, member, zero_out)
+    }
+
+    /// Decode all disjunctions in the guard that contain enough equalities.
+    fn decode<T>(
+        &mut self,
+        guard: ir::Guard<T>,
+        builder: &mut ir::Builder,
+    ) -> ir::Guard<T> {
+        match guard {
+            ir::Guard::Or(..) => {
+                let mut disjuncts = vec![];
+                flatten_or(guard, &mut disjuncts);
+
+                // Group the values compared against each port.
+                let mut eqs: LinkedHashMap<
+                    ir::Canonical,
+                    (RRC<ir::Port>, Vec<u64>),
+                > = LinkedHashMap::new();
+                for (port, val) in disjuncts.iter().filter_map(const_eq) {
+                    let canon = port.borrow().canonical();
+                    eqs.entry(canon)
+                        .or_insert_with(|| (port, vec![]))
+                        .1
+                        .push(val);
+                }
+                // Masks can only be represented using 64-bit constants.
+                let decoded: HashSet<ir::Canonical> = eqs
+                    .iter()
+                    .filter(|(_, (_, vals))| {
+                        vals.len() as u64 >= self.threshold
+                            && vals.iter().all(|v| *v < 64)
+                    })
+                    .map(|(canon, _)| canon.clone())
+                    .collect();
+
+                let mut out = Vec::with_capacity(disjuncts.len());
+                for (canon, (port, vals)) in eqs {
+                    if decoded.contains(&canon) {
+                        out.push(self.member(port, &vals, builder));
+                    }
+                }
+                for d in disjuncts {
+                    let is_decoded = const_eq(&d).map_or(false, |(p, _)| {
+                        decoded.contains(&p.borrow().canonical())
+                    });
+                    if !is_decoded {
+                        out.push(self.decode(d, builder));
+                    }
+                }
+                out.into_iter()
+                    .reduce(|l, r| ir::Guard::Or(Box::new(l), Box::new(r)))
+                    .unwrap()
+            }
+            ir::Guard::And(l, r) => {
+                let l = self.decode(*l, builder);
+                let r = self.decode(*r, builder);
+                ir::Guard::And(Box::new(l), Box::new(r))
+            }
+            ir::Guard::Implies(l, r) => {
+                let l = self.decode(*l, builder);
+                let r = self.decode(*r, builder);
+                ir::Guard::Implies(Box::new(l), Box::new(r))
+            }
+            ir::Guard::Not(g) => {
+                ir::Guard::Not(Box::new(self.decode(*g, builder)))
+            }
+            g => g,
+        }
+    }
+
+    fn decode_assigns<T>(
+        &mut self,
+        assigns: &mut [ir::Assignment<T>],
+        builder: &mut ir::Builder,
+    ) {
+        for assign in assigns {
+            assign.guard.update(|g| self.decode(g, builder));
+        }
+    }
+}
+
+impl Visitor for DecodeGuards {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let mut builder = ir::Builder::new(comp, sigs);
+
+        // Decoders are added to the continuous assignments so we detach
+        // the existing ones first.
+        let mut conts =
+            std::mem::take(&mut builder.component.continuous_assignments);
+        self.decode_assigns(&mut conts, &mut builder);
+        conts.append(&mut builder.component.continuous_assignments);
+        builder.component.continuous_assignments = conts;
+
+        let groups =
+            builder.component.get_groups().iter().cloned().collect_vec();
+        for group in groups {
+            let mut assigns =
+                group.borrow_mut().assignments.drain(..).collect_vec();
+            self.decode_assigns(&mut assigns, &mut builder);
+            group.borrow_mut().assignments = assigns;
+        }
+        let groups = builder
+            .component
+            .get_static_groups()
+            .iter()
+            .cloned()
+            .collect_vec();
+        for group in groups {
+            let mut assigns =
+                group.borrow_mut().assignments.drain(..).collect_vec();
+            self.decode_assigns(&mut assigns, &mut builder);
+            group.borrow_mut().assignments = assigns;
+        }
+        let groups =
+            builder.component.comb_groups.iter().cloned().collect_vec();
+        for group in groups {
+            let mut assigns =
+                group.borrow_mut().assignments.drain(..).collect_vec();
+            self.decode_assigns(&mut assigns, &mut builder);
+            group.borrow_mut().assignments = assigns;
+        }
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
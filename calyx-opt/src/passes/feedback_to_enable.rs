@@ -0,0 +1,102 @@
+use crate::analysis::RegWrites;
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+use std::collections::HashSet;
+
+/// Replaces feedback muxes on registers with the register's write enable.
+///
+/// A register that holds its value by writing its own output back is
+/// rewritten to only be enabled when it is written a new value:
+/// ```
+/// r.in = g ? x.out;
+/// r.in = !g ? r.out;
+/// r.write_en = 1'd1;
+/// ```
+/// becomes:
+/// ```
+/// r.in = g ? x.out;
+/// r.write_en = g ? 1'd1;
+/// ```
+/// This is sound when the feedback assignment is enabled exactly when none
+/// of the other assignments to `r.in` are, so that the register keeps its
+/// value when `g` is false. Feedback assignments are also removed when the
+/// write enable is already guarded by the conditions of the other
+/// assignments. Enabling a register less often also changes when its `done`
+/// signal is high, so registers whose `done` port is read anywhere in the
+/// component, including by the control program, are left alone, as are
+/// `ref` registers. Each group and the continuous assignments are rewritten
+/// separately.
+#[derive(Default)]
+pub struct FeedbackToEnable;
+
+impl Named for FeedbackToEnable {
+    fn name() -> &'static str {
+        "feedback-to-enable"
+    }
+
+    fn description() -> &'static str {
+        "drive register write enables instead of feedback muxes"
+    }
+}
+
+fn rewrite<T>(
+    assigns: &mut Vec<ir::Assignment<T>>,
+    done_read: &HashSet<ir::Id>,
+) where
+    T: Clone + Eq + ToString,
+{
+    let regs = RegWrites::collect(assigns, done_read);
+    let mut removed = HashSet::new();
+    for writes in regs.into_values() {
+        let [en] = writes.write_en[..] else {
+            continue;
+        };
+        if writes.feedback.is_empty()
+            || writes.data.is_empty()
+            || !assigns[en].src.borrow().is_constant(1, 1)
+        {
+            continue;
+        }
+        let written = ir::Guard::or_all(
+            writes.data.iter().map(|idx| Some(*assigns[*idx].guard.clone())),
+        );
+        let en_guard = &assigns[en].guard;
+        if en_guard.is_true() {
+            // The feedback must be enabled exactly when no new value is.
+            let [fb] = writes.feedback[..] else {
+                continue;
+            };
+            let held = !written.clone();
+            if assigns[fb].guard.canonical_key() != held.canonical_key() {
+                continue;
+            }
+            assigns[en].guard = Box::new(written);
+        } else if en_guard.canonical_key() != written.canonical_key() {
+            continue;
+        }
+        removed.extend(writes.feedback);
+    }
+
+    RegWrites::remove(assigns, &removed);
+}
+
+impl Visitor for FeedbackToEnable {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let done_read = RegWrites::done_read(comp);
+        for group in comp.get_groups() {
+            rewrite(&mut group.borrow_mut().assignments, &done_read);
+        }
+        for group in comp.get_static_groups() {
+            rewrite(&mut group.borrow_mut().assignments, &done_read);
+        }
+        rewrite(&mut comp.continuous_assignments, &done_read);
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+
+/// Returns a description of why comparing against the port is meaningless,
+/// if it is either zero-width or the output of an `undef` cell.
+pub(super) fn degenerate_operand(port: &ir::Port) -> Option<&'static str> {
+    if port.width == 0 {
+        return Some("zero-width port");
+    }
+    if let ir::PortParent::Cell(cell) = &port.parent {
+        if cell.upgrade().borrow().is_primitive(Some("undef")) {
+            return Some("undefined value");
+        }
+    }
+    None
+}
+
+/// Replaces guard comparisons against zero-width ports or undefined values
+/// with `false`.
+///
+/// Such comparisons have no meaningful value and the backends cannot
+/// generate sensible code for them. The `well-formed` pass rejects them; this
+/// pass can be run before it to repair programs that contain them. Folding to
+/// `false` ensures that assignments are never enabled by a degenerate
+/// comparison.
+#[derive(Default)]
+pub struct FoldDegenerateGuards;
+
+impl Named for FoldDegenerateGuards {
+    fn name() -> &'static str {
+        "fold-degenerate-guards"
+    }
+
+    fn description() -> &'static str {
+        "replace guard comparisons against zero-width or undefined ports with false"
+    }
+}
+
+fn fold<T>(guard: ir::Guard<T>) -> ir::Guard<T> {
+    match guard {
+        ir::Guard::CompOp(_, ref l, ref r)
+            if degenerate_operand(&l.borrow()).is_some()
+                || degenerate_operand(&r.borrow()).is_some() =>
+        {
+            ir::Guard::Not(Box::new(ir::Guard::True))
+        }
+        ir::Guard::And(l, r) => {
+            ir::Guard::And(Box::new(fold(*l)), Box::new(fold(*r)))
+        }
+        ir::Guard::Or(l, r) => {
+            ir::Guard::Or(Box::new(fold(*l)), Box::new(fold(*r)))
+        }
+        ir::Guard::Implies(l, r) => {
+            ir::Guard::Implies(Box::new(fold(*l)), Box::new(fold(*r)))
+        }
+        ir::Guard::Not(g) => ir::Guard::Not(Box::new(fold(*g))),
+        g => g,
+    }
+}
+
+impl Visitor for FoldDegenerateGuards {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        comp.for_each_assignment(|assign| assign.guard.update(fold));
+        comp.for_each_static_assignment(|assign| assign.guard.update(fold));
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
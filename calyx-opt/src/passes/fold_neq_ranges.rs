@@ -0,0 +1,182 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir::{self as ir, RRC};
+use itertools::Itertools;
+use linked_hash_map::LinkedHashMap;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Replaces conjunctions that exclude a contiguous range of low values from a
+/// port, such as:
+/// ```
+/// !(x.out == 2'd0) & x.out != 2'd1
+/// ```
+/// with a single range comparison:
+/// ```
+/// x.out >= 2'd2
+/// ```
+/// Excluded values that do not form a range starting at zero are left as-is.
+#[derive(Default)]
+pub struct FoldNeqRanges;
+
+impl Named for FoldNeqRanges {
+    fn name() -> &'static str {
+        "fold-neq-ranges"
+    }
+
+    fn description() -> &'static str {
+        "replace conjunctions of inequalities over low values with a range check"
+    }
+}
+
+/// If the guard is `x != c` or `!(x == c)` for a constant `c`, return the
+/// port and the value of the constant.
+fn const_neq<T>(guard: &ir::Guard<T>) -> Option<(RRC<ir::Port>, u64)> {
+    let (l, r) = match guard {
+        ir::Guard::CompOp(ir::PortComp::Neq, l, r) => (l, r),
+        ir::Guard::Not(g) => match &**g {
+            ir::Guard::CompOp(ir::PortComp::Eq, l, r) => (l, r),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    match (l.borrow().constant_value(), r.borrow().constant_value()) {
+        (None, Some(v)) => Some((Rc::clone(l), v)),
+        (Some(v), None) => Some((Rc::clone(r), v)),
+        _ => None,
+    }
+}
+
+/// Collect the conjuncts of a tree of `And` nodes.
+fn flatten_and<T>(guard: ir::Guard<T>, acc: &mut Vec<ir::Guard<T>>) {
+    match guard {
+        ir::Guard::And(l, r) => {
+            flatten_and(*l, acc);
+            flatten_and(*r, acc);
+        }
+        g => acc.push(g),
+    }
+}
+
+/// If `vals` are exactly the values `0..k` for some `k >= 2` smaller than
+/// the largest value of a port with the given width, return `k`.
+fn low_range(mut vals: Vec<u64>, width: u64) -> Option<u64> {
+    vals.sort_unstable();
+    vals.dedup();
+    let k = vals.len() as u64;
+    let contiguous = vals.iter().enumerate().all(|(i, v)| i as u64 == *v);
+    let fits = width >= 64 || k < (1 << width);
+    (k >= 2 && contiguous && fits).then_some(k)
+}
+
+fn fold<T>(guard: ir::Guard<T>, builder: &mut ir::Builder) -> ir::Guard<T> {
+    match guard {
+        ir::Guard::And(..) => {
+            let mut conjuncts = vec![];
+            flatten_and(guard, &mut conjuncts);
+            let conjuncts =
+                conjuncts.into_iter().map(|g| fold(g, builder)).collect_vec();
+
+            // Group the values excluded from each port.
+            let mut neqs: LinkedHashMap<
+                ir::Canonical,
+                (RRC<ir::Port>, Vec<u64>),
+            > = LinkedHashMap::new();
+            for (port, val) in conjuncts.iter().filter_map(const_neq) {
+                let canon = port.borrow().canonical();
+                neqs.entry(canon)
+                    .or_insert_with(|| (port, vec![]))
+                    .1
+                    .push(val);
+            }
+            let mut ranges: HashMap<ir::Canonical, ir::Guard<T>> =
+                HashMap::new();
+            for (canon, (port, vals)) in neqs {
+                let width = port.borrow().width;
+                if let Some(k) = low_range(vals, width) {
+                    let k = builder.add_constant(k, width);
+                    let k_out = k.borrow().get("out");
+                    ranges.insert(
+                        canon,
+                        ir::Guard::CompOp(ir::PortComp::Geq, port, k_out),
+                    );
+                }
+            }
+            let folded: HashSet<_> = ranges.keys().cloned().collect();
+
+            // Replace the first inequality on each folded port with the
+            // range check and drop the rest.
+            let mut out = Vec::with_capacity(conjuncts.len());
+            for g in conjuncts {
+                match const_neq(&g).map(|(p, _)| p.borrow().canonical()) {
+                    Some(canon) if folded.contains(&canon) => {
+                        out.extend(ranges.remove(&canon));
+                    }
+                    _ => out.push(g),
+                }
+            }
+            out.into_iter()
+                .reduce(|l, r| ir::Guard::And(Box::new(l), Box::new(r)))
+                .unwrap()
+        }
+        ir::Guard::Or(l, r) => {
+            let l = fold(*l, builder);
+            let r = fold(*r, builder);
+            ir::Guard::Or(Box::new(l), Box::new(r))
+        }
+        ir::Guard::Implies(l, r) => {
+            let l = fold(*l, builder);
+            let r = fold(*r, builder);
+            ir::Guard::Implies(Box::new(l), Box::new(r))
+        }
+        ir::Guard::Not(g) => ir::Guard::Not(Box::new(fold(*g, builder))),
+        g => g,
+    }
+}
+
+fn fold_assigns<T>(
+    assigns: &mut [ir::Assignment<T>],
+    builder: &mut ir::Builder,
+) {
+    for assign in assigns {
+        assign.guard.update(|g| fold(g, builder));
+    }
+}
+
+impl Visitor for FoldNeqRanges {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let mut builder = ir::Builder::new(comp, sigs);
+
+        let mut conts =
+            std::mem::take(&mut builder.component.continuous_assignments);
+        fold_assigns(&mut conts, &mut builder);
+        builder.component.continuous_assignments = conts;
+
+        let groups =
+            builder.component.get_groups().iter().cloned().collect_vec();
+        for group in groups {
+            fold_assigns(&mut group.borrow_mut().assignments, &mut builder);
+        }
+        let groups = builder
+            .component
+            .get_static_groups()
+            .iter()
+            .cloned()
+            .collect_vec();
+        for group in groups {
+            fold_assigns(&mut group.borrow_mut().assignments, &mut builder);
+        }
+        let groups =
+            builder.component.comb_groups.iter().cloned().collect_vec();
+        for group in groups {
+            fold_assigns(&mut group.borrow_mut().assignments, &mut builder);
+        }
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
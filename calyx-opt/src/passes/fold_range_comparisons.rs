@@ -0,0 +1,75 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+
+/// Folds comparisons against constants whose outcome is determined by the
+/// range of values the other conjuncts of the guard allow for the port.
+/// For example:
+/// ```
+/// r.in = x.out == 4'd3 & x.out < 4'd8 ? 1'd1;
+/// ```
+/// becomes:
+/// ```
+/// r.in = x.out == 4'd3 ? 1'd1;
+/// ```
+/// The ranges are computed with [ir::Guard::port_ranges] and the
+/// comparisons folded with [ir::Guard::fold_ranges]. Each conjunct is
+/// folded using the conjuncts after it and the already folded conjuncts
+/// before it, so a guard is never simplified using a fact derived from the
+/// comparison being folded.
+#[derive(Default)]
+pub struct FoldRangeComparisons;
+
+impl Named for FoldRangeComparisons {
+    fn name() -> &'static str {
+        "fold-range-comparisons"
+    }
+
+    fn description() -> &'static str {
+        "fold guard comparisons decided by the ranges of their ports"
+    }
+}
+
+/// Collect the conjuncts of a tree of `And` nodes.
+fn flatten_and<T>(guard: ir::Guard<T>, acc: &mut Vec<ir::Guard<T>>) {
+    match guard {
+        ir::Guard::And(l, r) => {
+            flatten_and(*l, acc);
+            flatten_and(*r, acc);
+        }
+        g => acc.push(g),
+    }
+}
+
+fn fold<T: Clone + Eq>(guard: ir::Guard<T>) -> ir::Guard<T> {
+    let mut conjuncts = vec![];
+    flatten_and(guard, &mut conjuncts);
+    if conjuncts.len() < 2 {
+        return conjuncts.pop().unwrap();
+    }
+    for idx in 0..conjuncts.len() {
+        let others = ir::Guard::and_all(
+            conjuncts
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != idx)
+                .map(|(_, g)| Some(g.clone())),
+        );
+        conjuncts[idx] = conjuncts[idx].fold_ranges(&others.port_ranges());
+    }
+    ir::Guard::and_all(conjuncts.into_iter().map(Some))
+}
+
+impl Visitor for FoldRangeComparisons {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        comp.for_each_assignment(|assign| assign.guard.update(fold));
+        comp.for_each_static_assignment(|assign| assign.guard.update(fold));
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
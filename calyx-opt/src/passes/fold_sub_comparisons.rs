@@ -0,0 +1,250 @@
+use crate::analysis::ReadWriteSet;
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir::{self as ir, RRC};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Rewrites guards that compare the difference of two values against zero
+/// into comparisons of the values themselves.
+///
+/// Subtraction wraps around, so `a - b` is zero exactly when `a == b`:
+/// ```
+/// sub.left = a.out;
+/// sub.right = b.out;
+/// x.in = sub.out == 8'd0 ? 8'd1;
+/// ```
+/// becomes:
+/// ```
+/// x.in = a.out == b.out ? 8'd1;
+/// ```
+/// Only `==` and `!=` comparisons are rewritten, since the ordering of the
+/// difference says nothing about the ordering of the operands. Each input
+/// of the `std_sub` must be driven by exactly one unguarded assignment.
+/// When both are continuous assignments every guard in the component is
+/// rewritten, and when both are in the same group only the guards of that
+/// group are. Subtractors whose output is no longer read are removed along
+/// with the assignments to their inputs.
+#[derive(Default)]
+pub struct FoldSubComparisons;
+
+impl Named for FoldSubComparisons {
+    fn name() -> &'static str {
+        "fold-sub-comparisons"
+    }
+
+    fn description() -> &'static str {
+        "compare subtraction operands instead of their difference with zero"
+    }
+}
+
+/// Where the inputs of a subtractor are driven.
+#[derive(Clone, PartialEq)]
+enum Scope {
+    Continuous,
+    Group(ir::Id),
+    /// A static or combinational group.
+    Other,
+}
+
+/// The source of an assignment to an input of a subtractor, if it is
+/// unguarded.
+type Driver = Option<RRC<ir::Port>>;
+
+/// The drivers of the inputs of a subtractor.
+#[derive(Default)]
+struct Operands {
+    left: Vec<(Scope, Driver)>,
+    right: Vec<(Scope, Driver)>,
+}
+
+/// If the port is an input of a `std_sub` that is not a `ref` cell, return
+/// the name of the subtractor.
+fn sub_of(port: &ir::Port) -> Option<ir::Id> {
+    let ir::PortParent::Cell(cell) = &port.parent else {
+        return None;
+    };
+    let cell = cell.upgrade();
+    let cell = cell.borrow();
+    (cell.is_primitive(Some("std_sub")) && !cell.is_reference())
+        .then(|| cell.name())
+}
+
+fn unguarded_src<T>(assign: &ir::Assignment<T>) -> Driver {
+    assign.guard.is_true().then(|| Rc::clone(&assign.src))
+}
+
+/// Replace each comparison of the output of a subtractor in `operands`
+/// against zero with a comparison of its operands.
+fn fold<T>(
+    guard: &mut ir::Guard<T>,
+    operands: &HashMap<ir::Id, (RRC<ir::Port>, RRC<ir::Port>)>,
+) {
+    match guard {
+        ir::Guard::And(l, r)
+        | ir::Guard::Or(l, r)
+        | ir::Guard::Implies(l, r) => {
+            fold(l, operands);
+            fold(r, operands);
+        }
+        ir::Guard::Not(g) => fold(g, operands),
+        ir::Guard::CompOp(op, l, r) => {
+            if !matches!(op, ir::PortComp::Eq | ir::PortComp::Neq) {
+                return;
+            }
+            let diff = if r.borrow().constant_value() == Some(0) {
+                Rc::clone(l)
+            } else if l.borrow().constant_value() == Some(0) {
+                Rc::clone(r)
+            } else {
+                return;
+            };
+            let diff = diff.borrow();
+            if diff.name != "out" {
+                return;
+            }
+            let Some((a, b)) =
+                sub_of(&diff).and_then(|sub| operands.get(&sub))
+            else {
+                return;
+            };
+            let folded =
+                ir::Guard::CompOp(op.clone(), Rc::clone(a), Rc::clone(b));
+            *guard = folded;
+        }
+        ir::Guard::True | ir::Guard::Port(_) | ir::Guard::Info(_) => {}
+    }
+}
+
+impl Visitor for FoldSubComparisons {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let mut drivers: HashMap<ir::Id, Operands> = HashMap::new();
+        let mut record = |scope: Scope, dst: &RRC<ir::Port>, driver| {
+            let dst = dst.borrow();
+            let Some(sub) = sub_of(&dst) else {
+                return;
+            };
+            let operands = drivers.entry(sub).or_default();
+            let driver = (scope, driver);
+            if dst.name == "left" {
+                operands.left.push(driver);
+            } else if dst.name == "right" {
+                operands.right.push(driver);
+            }
+        };
+        for assign in &comp.continuous_assignments {
+            record(Scope::Continuous, &assign.dst, unguarded_src(assign));
+        }
+        for group in comp.get_groups() {
+            let scope = Scope::Group(group.borrow().name());
+            for assign in &group.borrow().assignments {
+                record(scope.clone(), &assign.dst, unguarded_src(assign));
+            }
+        }
+        for group in comp.get_static_groups() {
+            for assign in &group.borrow().assignments {
+                record(Scope::Other, &assign.dst, unguarded_src(assign));
+            }
+        }
+        for group in comp.comb_groups.iter() {
+            for assign in &group.borrow().assignments {
+                record(Scope::Other, &assign.dst, unguarded_src(assign));
+            }
+        }
+
+        let mut continuous = HashMap::new();
+        let mut grouped: HashMap<ir::Id, HashMap<_, _>> = HashMap::new();
+        for (sub, operands) in drivers {
+            let ([(ls, Some(a))], [(rs, Some(b))]) =
+                (&operands.left[..], &operands.right[..])
+            else {
+                continue;
+            };
+            if ls != rs || a.borrow().is_hole() || b.borrow().is_hole() {
+                continue;
+            }
+            let pair = (Rc::clone(a), Rc::clone(b));
+            match ls {
+                Scope::Continuous => {
+                    continuous.insert(sub, pair);
+                }
+                Scope::Group(group) => {
+                    grouped.entry(*group).or_default().insert(sub, pair);
+                }
+                Scope::Other => {}
+            }
+        }
+        if continuous.is_empty() && grouped.is_empty() {
+            return Ok(Action::Stop);
+        }
+        let folded: HashSet<ir::Id> = continuous
+            .keys()
+            .chain(grouped.values().flat_map(|subs| subs.keys()))
+            .cloned()
+            .collect();
+
+        for assign in comp.continuous_assignments.iter_mut() {
+            fold(&mut assign.guard, &continuous);
+        }
+        for group in comp.get_groups() {
+            let mut operands = continuous.clone();
+            if let Some(subs) = grouped.get(&group.borrow().name()) {
+                operands.extend(subs.clone());
+            }
+            for assign in group.borrow_mut().assignments.iter_mut() {
+                fold(&mut assign.guard, &operands);
+            }
+        }
+        for group in comp.get_static_groups() {
+            for assign in group.borrow_mut().assignments.iter_mut() {
+                fold(&mut assign.guard, &continuous);
+            }
+        }
+        for group in comp.comb_groups.iter() {
+            for assign in group.borrow_mut().assignments.iter_mut() {
+                fold(&mut assign.guard, &continuous);
+            }
+        }
+
+        // Remove the subtractors whose difference is no longer read.
+        let mut read = HashSet::new();
+        let mut add_read = |port: RRC<ir::Port>| {
+            let port = port.borrow();
+            if port.name == "out" && !port.is_hole() {
+                read.insert(port.get_parent_name());
+            }
+        };
+        comp.iter_assignments(|assign| {
+            ReadWriteSet::port_reads(assign).for_each(&mut add_read)
+        });
+        comp.iter_static_assignments(|assign| {
+            ReadWriteSet::port_reads(assign).for_each(&mut add_read)
+        });
+        let (control_reads, _) =
+            ReadWriteSet::control_port_read_write_set::<true>(
+                &comp.control.borrow(),
+            );
+        control_reads.into_iter().for_each(add_read);
+        let dead: HashSet<ir::Id> =
+            folded.into_iter().filter(|sub| !read.contains(sub)).collect();
+        if dead.is_empty() {
+            return Ok(Action::Stop);
+        }
+        let live = |assign: &ir::Assignment<ir::Nothing>| {
+            let dst = assign.dst.borrow();
+            dst.is_hole() || !dead.contains(&dst.get_parent_name())
+        };
+        comp.continuous_assignments.retain(live);
+        for group in comp.get_groups() {
+            group.borrow_mut().assignments.retain(live);
+        }
+        comp.cells.retain(|cell| !dead.contains(&cell.borrow().name()));
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
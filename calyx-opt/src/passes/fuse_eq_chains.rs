@@ -0,0 +1,242 @@
+use crate::traversal::{
+    Action, ConstructVisitor, Named, ParseVal, PassOpt, VisResult, Visitor,
+};
+use calyx_ir::{self as ir, RRC};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Fuses conjunctions of equalities between distinct ports and constants
+/// into a single equality between the concatenation of the ports and the
+/// concatenation of the constants:
+/// ```
+/// x.in = a.out == 4'd3 & b.out == 4'd5 & c.out ? 8'd1;
+/// ```
+/// becomes:
+/// ```
+/// cat.left = a.out;
+/// cat.right = b.out;
+/// x.in = cat.out == 8'd53 & c.out ? 8'd1;
+/// ```
+/// so the tuple is tested by one comparator. The concatenations are built
+/// from `std_cat` cells driven by continuous assignments and are shared by
+/// every guard that tests the same ports in the same order. Conjunctions
+/// with fewer equalities than the `threshold` option, or whose ports are
+/// wider than 64 bits together, are left as-is.
+pub struct FuseEqChains {
+    /// The number of equalities a conjunction needs to be fused.
+    threshold: usize,
+}
+
+impl ConstructVisitor for FuseEqChains {
+    fn from(ctx: &ir::Context) -> calyx_utils::CalyxResult<Self>
+    where
+        Self: Sized,
+    {
+        let opts = Self::get_opts(ctx);
+        Ok(FuseEqChains {
+            threshold: opts[&"threshold"].pos_num().unwrap_or(2) as usize,
+        })
+    }
+
+    fn clear_data(&mut self) {
+        /* do nothing */
+    }
+}
+
+impl Named for FuseEqChains {
+    fn name() -> &'static str {
+        "fuse-eq-chains"
+    }
+
+    fn description() -> &'static str {
+        "test conjunctions of equalities with one comparison of concatenations"
+    }
+
+    fn opts() -> Vec<PassOpt> {
+        vec![PassOpt::new(
+            "threshold",
+            "the number of equalities a conjunction needs to be fused",
+            ParseVal::Num(2),
+            PassOpt::parse_num,
+        )]
+    }
+}
+
+/// If the guard compares a port that is not a constant or a hole for
+/// equality with a constant, return the port and the constant.
+fn eq_constant<T>(guard: &ir::Guard<T>) -> Option<(RRC<ir::Port>, u64)> {
+    let ir::Guard::CompOp(ir::PortComp::Eq, l, r) = guard else {
+        return None;
+    };
+    let (port, val) = match (
+        l.borrow().constant_value(),
+        r.borrow().constant_value(),
+    ) {
+        (None, Some(val)) => (l, val),
+        (Some(val), None) => (r, val),
+        _ => return None,
+    };
+    (!port.borrow().is_hole()).then(|| (Rc::clone(port), val))
+}
+
+/// Collect the conjuncts of a tree of `And` nodes.
+fn conjuncts<'a, T>(guard: &'a ir::Guard<T>, acc: &mut Vec<&'a ir::Guard<T>>) {
+    match guard {
+        ir::Guard::And(l, r) => {
+            conjuncts(l, acc);
+            conjuncts(r, acc);
+        }
+        g => acc.push(g),
+    }
+}
+
+struct Fuser<'b, 'a> {
+    threshold: usize,
+    builder: &'b mut ir::Builder<'a>,
+    /// The output of the concatenation of each sequence of ports.
+    cats: HashMap<Vec<ir::Canonical>, RRC<ir::Port>>,
+    /// The assignments to the inputs of the concatenations.
+    assigns: Vec<ir::Assignment<ir::Nothing>>,
+}
+
+impl Fuser<'_, '_> {
+    /// The output of the concatenation of `ports`, from the most significant
+    /// to the least.
+    fn concat(&mut self, ports: &[RRC<ir::Port>]) -> RRC<ir::Port> {
+        let mut out = Rc::clone(&ports[0]);
+        for len in 2..=ports.len() {
+            let key = ports[..len]
+                .iter()
+                .map(|port| port.borrow().canonical())
+                .collect_vec();
+            let right = &ports[len - 1];
+            out = match self.cats.get(&key) {
+                Some(cat) => Rc::clone(cat),
+                None => {
+                    let (lw, rw) = (out.borrow().width, right.borrow().width);
+                    let cat = self.builder.add_primitive(
+                        "cat",
+                        "std_cat",
+                        &[lw, rw, lw + rw],
+                    );
+                    let cat = cat.borrow();
+                    self.assigns.push(self.builder.build_assignment(
+                        cat.get("left"),
+                        out,
+                        ir::Guard::True,
+                    ));
+                    self.assigns.push(self.builder.build_assignment(
+                        cat.get("right"),
+                        Rc::clone(right),
+                        ir::Guard::True,
+                    ));
+                    let cat_out = cat.get("out");
+                    self.cats.insert(key, Rc::clone(&cat_out));
+                    cat_out
+                }
+            };
+        }
+        out
+    }
+
+    fn fuse<T: Clone + Eq>(&mut self, guard: &ir::Guard<T>) -> ir::Guard<T> {
+        match guard {
+            ir::Guard::And(..) => {
+                let mut operands = vec![];
+                conjuncts(guard, &mut operands);
+                let mut eqs: Vec<(usize, RRC<ir::Port>, u64)> = vec![];
+                for (idx, operand) in operands.iter().enumerate() {
+                    let Some((port, val)) = eq_constant(operand) else {
+                        continue;
+                    };
+                    if !eqs.iter().any(|(_, p, _)| Rc::ptr_eq(p, &port)) {
+                        eqs.push((idx, port, val));
+                    }
+                }
+                let width: u64 =
+                    eqs.iter().map(|(_, port, _)| port.borrow().width).sum();
+                if eqs.len() < self.threshold.max(2) || width > 64 {
+                    return operands
+                        .into_iter()
+                        .map(|operand| self.fuse(operand))
+                        .reduce(|acc, g| acc & g)
+                        .unwrap();
+                }
+                let ports = eqs.iter().map(|(_, port, _)| Rc::clone(port));
+                let cat = self.concat(&ports.collect_vec());
+                let val = eqs.iter().fold(0, |acc, (_, port, val)| {
+                    (acc << port.borrow().width) | val
+                });
+                let constant = self.builder.add_constant(val, width);
+                let fused = ir::Guard::CompOp(
+                    ir::PortComp::Eq,
+                    cat,
+                    constant.borrow().get("out"),
+                );
+                let mut fused = Some(fused);
+                operands
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(idx, operand)| {
+                        match eqs.iter().position(|(i, _, _)| *i == idx) {
+                            Some(0) => fused.take(),
+                            Some(_) => None,
+                            None => Some(self.fuse(operand)),
+                        }
+                    })
+                    .reduce(|acc, g| acc & g)
+                    .unwrap()
+            }
+            ir::Guard::Or(l, r) => self.fuse(l) | self.fuse(r),
+            ir::Guard::Implies(l, r) => self.fuse(l).implies_guard(self.fuse(r)),
+            ir::Guard::Not(g) => !self.fuse(g),
+            g => g.clone(),
+        }
+    }
+}
+
+impl Visitor for FuseEqChains {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let groups = comp.get_groups().iter().cloned().collect_vec();
+        let static_groups =
+            comp.get_static_groups().iter().cloned().collect_vec();
+        let comb_groups = comp.comb_groups.iter().cloned().collect_vec();
+        let mut continuous = std::mem::take(&mut comp.continuous_assignments);
+        let mut builder = ir::Builder::new(comp, sigs);
+        let mut fuser = Fuser {
+            threshold: self.threshold,
+            builder: &mut builder,
+            cats: HashMap::new(),
+            assigns: vec![],
+        };
+        for group in groups {
+            for assign in group.borrow_mut().assignments.iter_mut() {
+                assign.guard.update(|g| fuser.fuse(&g));
+            }
+        }
+        for group in comb_groups {
+            for assign in group.borrow_mut().assignments.iter_mut() {
+                assign.guard.update(|g| fuser.fuse(&g));
+            }
+        }
+        for group in static_groups {
+            for assign in group.borrow_mut().assignments.iter_mut() {
+                assign.guard.update(|g| fuser.fuse(&g));
+            }
+        }
+        for assign in continuous.iter_mut() {
+            assign.guard.update(|g| fuser.fuse(&g));
+        }
+        continuous.extend(fuser.assigns);
+        comp.continuous_assignments = continuous;
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
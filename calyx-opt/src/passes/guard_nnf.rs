@@ -0,0 +1,42 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+
+/// Converts every guard into negation normal form using [ir::Guard::to_nnf].
+///
+/// Pushing negations to the leaves lets the backend emit guards with the
+/// fewest inverters, which downstream synthesis tools handle better. Other
+/// passes may freely reintroduce negated subexpressions, so this pass should
+/// run after all other optimizations, right before emitting code (for
+/// example, `-p all -p guard-nnf`). Running the pass more than once has no
+/// further effect.
+#[derive(Default)]
+pub struct GuardNnf;
+
+impl Named for GuardNnf {
+    fn name() -> &'static str {
+        "guard-nnf"
+    }
+
+    fn description() -> &'static str {
+        "convert guards to negation normal form before emitting code"
+    }
+}
+
+impl Visitor for GuardNnf {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        comp.for_each_assignment(|assign| {
+            assign.guard.update(ir::Guard::to_nnf)
+        });
+        comp.for_each_static_assignment(|assign| {
+            assign.guard.update(ir::Guard::to_nnf)
+        });
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
@@ -0,0 +1,292 @@
+use crate::analysis::ReadWriteSet;
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir::{self as ir, RRC};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Computes comparisons in the guards of a `while` loop's body that are
+/// invariant in the loop once, before the loop is entered.
+///
+/// A comparison is invariant if each operand is a constant or the output of
+/// a `std_reg` that is not written by the loop body or by continuous
+/// assignments. Each invariant comparison is computed into a 1-bit register
+/// by a new group enabled before the loop, and the guards in the body read
+/// the register instead. For example, if `incr` writes neither `n` nor
+/// `limit`:
+/// ```
+/// group body { x.in = n.out < limit.out ? 1'd1; ... }
+/// while c.out { seq { body; incr; } }
+/// ```
+/// becomes:
+/// ```
+/// group invariant {
+///   lt.left = n.out; lt.right = limit.out;
+///   cmp.in = lt.out; cmp.write_en = 1'd1;
+///   invariant[done] = cmp.done;
+/// }
+/// group body { x.in = cmp.out ? 1'd1; ... }
+/// seq { invariant; while c.out { seq { body; incr; } } }
+/// ```
+/// Comparisons with an operand written in the loop, like `i.out < limit.out`
+/// when `incr` increments `i`, stay inline. Only groups that are enabled once
+/// and whose `go` hole is not driven by an assignment are rewritten. Loops
+/// inside a `par` are left alone because other threads may write the
+/// registers.
+#[derive(Default)]
+pub struct HoistInvariantComparisons {
+    /// The number of times each group is enabled by the control program.
+    enables: HashMap<ir::Id, usize>,
+    /// Groups whose `go` hole is driven by an assignment.
+    driven: HashSet<ir::Id>,
+    /// Cells written by continuous assignments.
+    continuous: HashSet<ir::Id>,
+    /// The number of `par` statements enclosing the current statement.
+    par_depth: usize,
+}
+
+impl Named for HoistInvariantComparisons {
+    fn name() -> &'static str {
+        "hoist-invariant-comparisons"
+    }
+
+    fn description() -> &'static str {
+        "compute loop-invariant guard comparisons once before the loop"
+    }
+}
+
+/// Count the number of times each group is enabled and collect the groups
+/// in the order they are enabled.
+fn enabled_groups(
+    con: &ir::Control,
+    counts: &mut HashMap<ir::Id, usize>,
+    groups: &mut Vec<RRC<ir::Group>>,
+) {
+    match con {
+        ir::Control::Enable(ir::Enable { group, .. }) => {
+            *counts.entry(group.borrow().name()).or_default() += 1;
+            groups.push(Rc::clone(group));
+        }
+        ir::Control::Seq(ir::Seq { stmts, .. })
+        | ir::Control::Par(ir::Par { stmts, .. }) => {
+            for stmt in stmts {
+                enabled_groups(stmt, counts, groups);
+            }
+        }
+        ir::Control::If(ir::If {
+            tbranch, fbranch, ..
+        }) => {
+            enabled_groups(tbranch, counts, groups);
+            enabled_groups(fbranch, counts, groups);
+        }
+        ir::Control::While(ir::While { body, .. })
+        | ir::Control::Repeat(ir::Repeat { body, .. }) => {
+            enabled_groups(body, counts, groups)
+        }
+        ir::Control::Invoke(_)
+        | ir::Control::Empty(_)
+        | ir::Control::Static(_) => {}
+    }
+}
+
+/// Returns true if the port is a constant or the output of a `std_reg` that
+/// is not in `written`.
+fn invariant(port: &ir::Port, written: &HashSet<ir::Id>) -> bool {
+    if port.constant_value().is_some() {
+        return true;
+    }
+    let ir::PortParent::Cell(cell) = &port.parent else {
+        return false;
+    };
+    let cell = cell.upgrade();
+    let cell = cell.borrow();
+    port.name == "out"
+        && cell.is_primitive(Some("std_reg"))
+        && !cell.is_reference()
+        && !written.contains(&cell.name())
+}
+
+/// Collect the comparisons in the guard whose operands are invariant and
+/// not both constants.
+fn invariant_comparisons(
+    guard: &ir::Guard<ir::Nothing>,
+    written: &HashSet<ir::Id>,
+    acc: &mut Vec<ir::Guard<ir::Nothing>>,
+) {
+    match guard {
+        ir::Guard::And(l, r)
+        | ir::Guard::Or(l, r)
+        | ir::Guard::Implies(l, r) => {
+            invariant_comparisons(l, written, acc);
+            invariant_comparisons(r, written, acc);
+        }
+        ir::Guard::Not(g) => invariant_comparisons(g, written, acc),
+        ir::Guard::CompOp(_, l, r) => {
+            let (l, r) = (l.borrow(), r.borrow());
+            let constants =
+                l.constant_value().is_some() && r.constant_value().is_some();
+            if !constants
+                && invariant(&l, written)
+                && invariant(&r, written)
+                && !acc.contains(guard)
+            {
+                acc.push(guard.clone());
+            }
+        }
+        ir::Guard::True | ir::Guard::Port(_) | ir::Guard::Info(_) => {}
+    }
+}
+
+/// Add a group that computes the comparison into a new register, and
+/// return the group and the output of the register.
+fn precompute(
+    cmp: &ir::Guard<ir::Nothing>,
+    builder: &mut ir::Builder,
+) -> (RRC<ir::Group>, RRC<ir::Port>) {
+    let (op, l, r) = cmp.as_cmp().unwrap();
+    let prefix = match op {
+        ir::PortComp::Eq => "eq",
+        ir::PortComp::Neq => "neq",
+        ir::PortComp::Gt => "gt",
+        ir::PortComp::Lt => "lt",
+        ir::PortComp::Geq => "ge",
+        ir::PortComp::Leq => "le",
+    };
+    let width = l.borrow().width;
+    let cmp_cell =
+        builder.add_primitive(prefix, format!("std_{prefix}"), &[width]);
+    let reg_cell = builder.add_primitive("cmp", "std_reg", &[1]);
+    let one = builder.add_constant(1, 1);
+    let group = builder.add_group("invariant");
+    let (cmp, reg) = (cmp_cell.borrow(), reg_cell.borrow());
+    let assigns = vec![
+        builder.build_assignment(
+            cmp.get("left"),
+            Rc::clone(l),
+            ir::Guard::True,
+        ),
+        builder.build_assignment(
+            cmp.get("right"),
+            Rc::clone(r),
+            ir::Guard::True,
+        ),
+        builder.build_assignment(
+            reg.get("in"),
+            cmp.get("out"),
+            ir::Guard::True,
+        ),
+        builder.build_assignment(
+            reg.get("write_en"),
+            one.borrow().get("out"),
+            ir::Guard::True,
+        ),
+        builder.build_assignment(
+            group.borrow().get("done"),
+            reg.get("done"),
+            ir::Guard::True,
+        ),
+    ];
+    group.borrow_mut().assignments = assigns;
+    let out = reg.get("out");
+    (group, out)
+}
+
+impl Visitor for HoistInvariantComparisons {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        enabled_groups(&comp.control.borrow(), &mut self.enables, &mut vec![]);
+        comp.iter_assignments(|assign| {
+            let dst = assign.dst.borrow();
+            if dst.is_hole() && dst.name == "go" {
+                self.driven.insert(dst.get_parent_name());
+            }
+        });
+        self.continuous = comp
+            .continuous_assignments
+            .iter()
+            .filter(|assign| !assign.dst.borrow().is_hole())
+            .map(|assign| assign.dst.borrow().get_parent_name())
+            .collect();
+        Ok(Action::Continue)
+    }
+
+    fn start_par(
+        &mut self,
+        _s: &mut ir::Par,
+        _comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        self.par_depth += 1;
+        Ok(Action::Continue)
+    }
+
+    fn finish_par(
+        &mut self,
+        _s: &mut ir::Par,
+        _comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        self.par_depth -= 1;
+        Ok(Action::Continue)
+    }
+
+    fn finish_while(
+        &mut self,
+        s: &mut ir::While,
+        comp: &mut ir::Component,
+        sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        if self.par_depth > 0 {
+            return Ok(Action::Continue);
+        }
+        let mut written: HashSet<ir::Id> =
+            ReadWriteSet::control_read_write_set::<false>(&s.body)
+                .1
+                .into_iter()
+                .map(|cell| cell.borrow().name())
+                .collect();
+        written.extend(self.continuous.iter().cloned());
+
+        let mut groups = vec![];
+        enabled_groups(&s.body, &mut HashMap::new(), &mut groups);
+        groups.retain(|group| {
+            let name = group.borrow().name();
+            self.enables.get(&name) == Some(&1) && !self.driven.contains(&name)
+        });
+        let mut cmps = vec![];
+        for group in &groups {
+            for assign in &group.borrow().assignments {
+                invariant_comparisons(&assign.guard, &written, &mut cmps);
+            }
+        }
+        if cmps.is_empty() {
+            return Ok(Action::Continue);
+        }
+
+        let mut builder = ir::Builder::new(comp, sigs);
+        let mut stmts = Vec::with_capacity(cmps.len() + 1);
+        for cmp in &cmps {
+            let (hoisted, out) = precompute(cmp, &mut builder);
+            let replacement = ir::Guard::port(out);
+            for group in &groups {
+                for assign in group.borrow_mut().assignments.iter_mut() {
+                    assign.guard.replace_subtree(cmp, &replacement);
+                }
+            }
+            stmts.push(ir::Control::enable(hoisted));
+        }
+        stmts.push(ir::Control::While(ir::While {
+            port: Rc::clone(&s.port),
+            cond: s.cond.take(),
+            body: Box::new(s.body.take_control()),
+            attributes: std::mem::take(&mut s.attributes),
+        }));
+        Ok(Action::change(ir::Control::seq(stmts)))
+    }
+}
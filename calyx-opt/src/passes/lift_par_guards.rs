@@ -0,0 +1,156 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir::{self as ir, RRC};
+use itertools::Itertools;
+use std::rc::Rc;
+
+/// Computes guard conjuncts shared by every assignment of an arm of a `par`
+/// once for the whole arm.
+///
+/// When every assignment in the groups enabled by an arm has the same
+/// conjunct, the conjunct is computed into a 1-bit wire by a continuous
+/// assignment and the groups read the wire instead:
+/// ```
+/// group a { x.in = c.out & i.out < 8'd4 ? 8'd1; ... }
+/// group b { y.in = i.out < 8'd4 ? 8'd2; ... }
+/// par { seq { a; b; } ... }
+/// ```
+/// becomes:
+/// ```
+/// group a { x.in = c.out & lift.out ? 8'd1; ... }
+/// group b { y.in = lift.out ? 8'd2; ... }
+/// lift.in = i.out < 8'd4 ? 1'd1;
+/// ```
+/// The wire always carries the value of the conjunct, so the rewrite does
+/// not depend on when the groups run. The conjunct is not latched when the
+/// arm is activated: that would only be correct if the ports it reads do not
+/// change while the arm runs, which the pass does not check. The pass only
+/// shares the logic computing the conjunct between the groups of the arm.
+///
+/// Assignments to holes, like the `done` condition of a group, are not
+/// required to have the conjunct. Conjuncts that are a single port or read a
+/// hole are left alone, and arms that enable fewer than two groups are not
+/// rewritten.
+#[derive(Default)]
+pub struct LiftParGuards;
+
+impl Named for LiftParGuards {
+    fn name() -> &'static str {
+        "lift-par-guards"
+    }
+
+    fn description() -> &'static str {
+        "compute guard conjuncts shared by a par arm's groups with one continuous wire"
+    }
+}
+
+/// Collect the groups enabled by the control program, once each.
+fn enabled_groups(con: &ir::Control, acc: &mut Vec<RRC<ir::Group>>) {
+    match con {
+        ir::Control::Enable(ir::Enable { group, .. }) => {
+            if !acc.iter().any(|g| Rc::ptr_eq(g, group)) {
+                acc.push(Rc::clone(group));
+            }
+        }
+        ir::Control::Seq(ir::Seq { stmts, .. })
+        | ir::Control::Par(ir::Par { stmts, .. }) => {
+            for stmt in stmts {
+                enabled_groups(stmt, acc);
+            }
+        }
+        ir::Control::If(ir::If {
+            tbranch, fbranch, ..
+        }) => {
+            enabled_groups(tbranch, acc);
+            enabled_groups(fbranch, acc);
+        }
+        ir::Control::While(ir::While { body, .. })
+        | ir::Control::Repeat(ir::Repeat { body, .. }) => {
+            enabled_groups(body, acc)
+        }
+        ir::Control::Invoke(_)
+        | ir::Control::Empty(_)
+        | ir::Control::Static(_) => {}
+    }
+}
+
+/// Collect the conjuncts of a tree of `And` nodes.
+fn conjuncts<'a>(
+    guard: &'a ir::Guard<ir::Nothing>,
+    acc: &mut Vec<&'a ir::Guard<ir::Nothing>>,
+) {
+    match guard {
+        ir::Guard::And(l, r) => {
+            conjuncts(l, acc);
+            conjuncts(r, acc);
+        }
+        g => acc.push(g),
+    }
+}
+
+/// The conjuncts that every assignment to a port in the groups has.
+fn common_conjuncts(groups: &[RRC<ir::Group>]) -> Vec<ir::Guard<ir::Nothing>> {
+    let groups = groups.iter().map(|group| group.borrow()).collect_vec();
+    let mut guards = groups
+        .iter()
+        .flat_map(|group| group.assignments.iter())
+        .filter(|assign| !assign.dst.borrow().is_hole())
+        .map(|assign| &*assign.guard);
+    let Some(first) = guards.next() else {
+        return vec![];
+    };
+    let mut common = vec![];
+    conjuncts(first, &mut common);
+    common.retain(|c| {
+        !matches!(c, ir::Guard::Port(_) | ir::Guard::True)
+            && c.all_ports().iter().all(|port| !port.borrow().is_hole())
+    });
+    for guard in guards {
+        let mut other = vec![];
+        conjuncts(guard, &mut other);
+        common.retain(|c| other.contains(c));
+    }
+    let mut unique: Vec<ir::Guard<ir::Nothing>> = vec![];
+    for c in common {
+        if !unique.contains(c) {
+            unique.push(c.clone());
+        }
+    }
+    unique
+}
+
+impl Visitor for LiftParGuards {
+    fn start_par(
+        &mut self,
+        s: &mut ir::Par,
+        comp: &mut ir::Component,
+        sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        for stmt in &s.stmts {
+            let mut groups = vec![];
+            enabled_groups(stmt, &mut groups);
+            if groups.len() < 2 {
+                continue;
+            }
+            let common = common_conjuncts(&groups);
+            let mut builder = ir::Builder::new(comp, sigs);
+            for conjunct in common {
+                let wire = builder.add_primitive("lift", "std_wire", &[1]);
+                let one = builder.add_constant(1, 1);
+                let assign = builder.build_assignment(
+                    wire.borrow().get("in"),
+                    one.borrow().get("out"),
+                    conjunct.clone(),
+                );
+                builder.add_continuous_assignments(vec![assign]);
+                let replacement = ir::Guard::port(wire.borrow().get("out"));
+                for group in &groups {
+                    for assign in group.borrow_mut().assignments.iter_mut() {
+                        assign.guard.replace_subtree(&conjunct, &replacement);
+                    }
+                }
+            }
+        }
+        Ok(Action::Continue)
+    }
+}
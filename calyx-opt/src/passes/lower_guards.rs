@@ -1,11 +1,38 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{
+    Action, ConstructVisitor, Named, ParseVal, PassOpt, VisResult, Visitor,
+};
 use calyx_ir::{self as ir, RRC};
 use ir::Nothing;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 /// Lowers guards into a purely structural representation. After this pass,
 /// all guards are guaranteed to be either [ir::Guard::True] or [ir::Guard::Port].
-#[derive(Default)]
-pub struct LowerGuards;
+///
+/// Sub-guards that occur more than once in the same group are lowered to a
+/// single tree of primitives. With the `wires` option, each lowered guard
+/// drives a 1-bit `std_wire` and the assignment is guarded by the wire's
+/// output instead of the output of the primitive tree.
+pub struct LowerGuards {
+    /// Drive each lowered guard through a `std_wire`.
+    wires: bool,
+}
+
+impl ConstructVisitor for LowerGuards {
+    fn from(ctx: &ir::Context) -> calyx_utils::CalyxResult<Self>
+    where
+        Self: Sized,
+    {
+        let opts = Self::get_opts(ctx);
+        Ok(LowerGuards {
+            wires: opts[&"wires"].bool(),
+        })
+    }
+
+    fn clear_data(&mut self) {
+        /* do nothing */
+    }
+}
 
 impl Named for LowerGuards {
     fn name() -> &'static str {
@@ -15,8 +42,22 @@ impl Named for LowerGuards {
     fn description() -> &'static str {
         "lower guards to a purely structural representation"
     }
+
+    fn opts() -> Vec<PassOpt> {
+        vec![PassOpt::new(
+            "wires",
+            "drive each lowered guard through a 1-bit wire",
+            ParseVal::Bool(false),
+            PassOpt::parse_bool,
+        )]
+    }
 }
 
+/// Ports driven by already lowered guards, keyed by the printed guard.
+/// Primitives are only driven by the assignments of the group they were
+/// lowered in, so a cache is never shared across groups.
+type Lowered = HashMap<String, RRC<ir::Port>>;
+
 fn guard_to_prim(guard: &ir::Guard<ir::Nothing>) -> Option<String> {
     let var_name = match guard {
         ir::Guard::Or(..) => "or",
@@ -29,7 +70,10 @@ fn guard_to_prim(guard: &ir::Guard<ir::Nothing>) -> Option<String> {
             ir::PortComp::Geq => "ge",
             ir::PortComp::Leq => "le",
         },
-        ir::Guard::True | ir::Guard::Not(_) | ir::Guard::Port(_) => {
+        ir::Guard::True
+        | ir::Guard::Not(_)
+        | ir::Guard::Implies(..)
+        | ir::Guard::Port(_) => {
             return None;
         }
         ir::Guard::Info(_) => {
@@ -43,12 +87,18 @@ fn lower_guard(
     guard: ir::Guard<Nothing>,
     assigns: &mut Vec<ir::Assignment<Nothing>>,
     builder: &mut ir::Builder,
+    lowered: &mut Lowered,
 ) -> RRC<ir::Port> {
+    let key = (!matches!(guard, ir::Guard::True | ir::Guard::Port(_)))
+        .then(|| ir::Printer::guard_str(&guard));
+    if let Some(port) = key.as_ref().and_then(|key| lowered.get(key)) {
+        return Rc::clone(port);
+    }
     let maybe_prim = guard_to_prim(&guard);
-    match guard {
+    let port = match guard {
         ir::Guard::And(l, r) | ir::Guard::Or(l, r) => {
-            let l_low = lower_guard(*l, assigns, builder);
-            let r_low = lower_guard(*r, assigns, builder);
+            let l_low = lower_guard(*l, assigns, builder, lowered);
+            let r_low = lower_guard(*r, assigns, builder, lowered);
 
             let prim = maybe_prim.unwrap();
             let prim_name = format!("std_{}", prim);
@@ -89,7 +139,7 @@ fn lower_guard(
             prim.get("out")
         }
         ir::Guard::Not(g) => {
-            let g_low = lower_guard(*g, assigns, builder);
+            let g_low = lower_guard(*g, assigns, builder, lowered);
             let not_prim = builder.add_primitive(
                 "not",
                 "std_not",
@@ -103,26 +153,60 @@ fn lower_guard(
             ));
             not.get("out")
         }
+        // Lowered as `!l | r`.
+        g @ ir::Guard::Implies(..) => {
+            lower_guard(g.expand_implies(), assigns, builder, lowered)
+        }
         ir::Guard::True => builder.add_constant(1, 1).borrow().get("out"),
         ir::Guard::Port(p) => p,
         ir::Guard::Info(_) => panic!("shouldn't have info ports at this point"),
+    };
+    if let Some(key) = key {
+        lowered.insert(key, Rc::clone(&port));
     }
+    port
 }
 
-fn lower_assigns(
-    assigns: Vec<ir::Assignment<Nothing>>,
-    builder: &mut ir::Builder,
-) -> Vec<ir::Assignment<Nothing>> {
-    let mut new_assigns = Vec::with_capacity(assigns.len() * 2);
-    for mut assign in assigns {
-        let g = std::mem::take(&mut assign.guard);
-        let mut assigns = vec![];
-        let port = lower_guard(*g, &mut assigns, builder);
-        assign.guard = Box::new(port.into());
-        new_assigns.extend(assigns);
-        new_assigns.push(assign);
+impl LowerGuards {
+    fn lower_assigns(
+        &self,
+        assigns: Vec<ir::Assignment<Nothing>>,
+        builder: &mut ir::Builder,
+    ) -> Vec<ir::Assignment<Nothing>> {
+        let mut new_assigns = Vec::with_capacity(assigns.len() * 2);
+        let mut lowered = Lowered::new();
+        // Wires driven by lowered guards, keyed by the printed guard.
+        let mut wires = Lowered::new();
+        for mut assign in assigns {
+            let g = *std::mem::take(&mut assign.guard);
+            let materialize = self.wires
+                && !matches!(g, ir::Guard::True | ir::Guard::Port(_));
+            let key = materialize.then(|| ir::Printer::guard_str(&g));
+            let mut assigns = vec![];
+            let mut port = lower_guard(g, &mut assigns, builder, &mut lowered);
+            if let Some(key) = key {
+                port = match wires.get(&key) {
+                    Some(wire_out) => Rc::clone(wire_out),
+                    None => {
+                        let wire_cell =
+                            builder.add_primitive("guard", "std_wire", &[1]);
+                        let wire = wire_cell.borrow();
+                        assigns.push(builder.build_assignment(
+                            wire.get("in"),
+                            port,
+                            ir::Guard::True,
+                        ));
+                        wires.insert(key, wire.get("out"));
+                        wire.get("out")
+                    }
+                };
+            }
+            assign.guard = Box::new(port.into());
+            new_assigns.extend(assigns);
+            new_assigns.push(assign);
+        }
+        new_assigns
     }
-    new_assigns
 }
 
 impl Visitor for LowerGuards {
@@ -137,7 +221,7 @@ impl Visitor for LowerGuards {
         // Transform continuous assignments
         let conts: Vec<_> =
             builder.component.continuous_assignments.drain(..).collect();
-        let new_conts = lower_assigns(conts, &mut builder);
+        let new_conts = self.lower_assigns(conts, &mut builder);
         builder.component.continuous_assignments = new_conts;
 
         // Transform group assignments
@@ -148,7 +232,7 @@ impl Visitor for LowerGuards {
             .map(|group| {
                 let assigns =
                     group.borrow_mut().assignments.drain(..).collect();
-                let new_assigns = lower_assigns(assigns, &mut builder);
+                let new_assigns = self.lower_assigns(assigns, &mut builder);
                 group.borrow_mut().assignments = new_assigns;
                 group
             })
@@ -162,7 +246,7 @@ impl Visitor for LowerGuards {
             .map(|group| {
                 let assigns =
                     group.borrow_mut().assignments.drain(..).collect();
-                let new_assigns = lower_assigns(assigns, &mut builder);
+                let new_assigns = self.lower_assigns(assigns, &mut builder);
                 group.borrow_mut().assignments = new_assigns;
                 group
             })
@@ -177,7 +261,7 @@ impl Visitor for LowerGuards {
             .map(|group| {
                 let assigns =
                     group.borrow_mut().assignments.drain(..).collect();
-                let new_assigns = lower_assigns(assigns, &mut builder);
+                let new_assigns = self.lower_assigns(assigns, &mut builder);
                 group.borrow_mut().assignments = new_assigns;
                 group
             })
@@ -0,0 +1,77 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Merges `std_const` cells that have the same width and value.
+///
+/// Every use of a duplicate constant, in guards, assignments and control, is
+/// replaced with the first cell with that width and value, and the duplicate
+/// cells are removed. For example:
+/// ```
+/// c0 = std_const(4, 8);
+/// c1 = std_const(4, 8);
+/// ...
+/// r.in = x.out < c0.out | y.out < c1.out ? 1'd1;
+/// ```
+/// becomes:
+/// ```
+/// c0 = std_const(4, 8);
+/// ...
+/// r.in = x.out < c0.out | y.out < c0.out ? 1'd1;
+/// ```
+#[derive(Default)]
+pub struct MergeConstants;
+
+impl Named for MergeConstants {
+    fn name() -> &'static str {
+        "merge-constants"
+    }
+
+    fn description() -> &'static str {
+        "merge std_const cells with the same width and value"
+    }
+}
+
+impl Visitor for MergeConstants {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let mut canonical: HashMap<(u64, u64), ir::RRC<ir::Cell>> =
+            HashMap::new();
+        let mut rewriter = ir::Rewriter::default();
+        for cell_ref in comp.cells.iter() {
+            let cell = cell_ref.borrow();
+            if !cell.is_primitive(Some("std_const"))
+                || cell.is_reference()
+                || cell.attributes.has(ir::BoolAttr::External)
+            {
+                continue;
+            }
+            let key = (
+                cell.get_parameter("WIDTH").unwrap(),
+                cell.get_parameter("VALUE").unwrap(),
+            );
+            match canonical.get(&key) {
+                Some(first) => {
+                    rewriter.cell_map.insert(cell.name(), Rc::clone(first));
+                }
+                None => {
+                    canonical.insert(key, Rc::clone(cell_ref));
+                }
+            }
+        }
+        if rewriter.cell_map.is_empty() {
+            return Ok(Action::Stop);
+        }
+
+        rewriter.rewrite(comp);
+        let merged = &rewriter.cell_map;
+        comp.cells.retain(|cell| !merged.contains_key(&cell.borrow().name()));
+
+        Ok(Action::Stop)
+    }
+}
@@ -1,4 +1,6 @@
 //! Passes for the Calyx compiler.
+mod balance_guards;
+mod bypass_guard_wires;
 mod canonical;
 mod cell_share;
 mod clk_insertion;
@@ -11,27 +13,48 @@ mod component_iniliner;
 mod dead_assignment_removal;
 mod dead_cell_removal;
 mod dead_group_removal;
+mod dead_writes;
+mod decode_guards;
 mod externalize;
+mod feedback_to_enable;
+mod fold_degenerate_guards;
+mod fold_neq_ranges;
+mod fold_range_comparisons;
+mod fold_sub_comparisons;
+mod fuse_eq_chains;
 mod go_insertion;
+mod guard_nnf;
 mod group_to_invoke;
 mod group_to_seq;
+mod hoist_invariant_comparisons;
 mod infer_share;
+mod lift_par_guards;
 mod lower_guards;
 pub mod math_utilities;
 mod merge_assign;
+mod merge_constants;
+mod one_hot_selectors;
 mod papercut;
+mod prune_unreachable_states;
 mod reset_insertion;
+mod self_comparison;
+mod share_comparisons;
+mod simplify_dont_care;
 mod simplify_static_guards;
+mod specialize_branches;
 mod static_fsm_opts;
 mod static_inference;
 mod static_inliner;
 mod static_promotion;
+mod strip_activation_guards;
 // mod simplify_guards;
 mod add_guard;
 mod data_path_infer;
 mod default_assigns;
 mod dump_ports;
 mod remove_ids;
+mod remove_implied_guards;
+mod remove_retention_writes;
 mod simplify_with_control;
 mod synthesis_papercut;
 mod top_down_compile_control;
@@ -40,6 +63,8 @@ mod well_formed;
 mod wire_inliner;
 mod wrap_main;
 
+pub use balance_guards::BalanceGuards;
+pub use bypass_guard_wires::BypassGuardWires;
 pub use canonical::Canonicalize;
 pub use cell_share::CellShare;
 pub use clk_insertion::ClkInsertion;
@@ -53,24 +78,45 @@ pub use data_path_infer::DataPathInfer;
 pub use dead_assignment_removal::DeadAssignmentRemoval;
 pub use dead_cell_removal::DeadCellRemoval;
 pub use dead_group_removal::DeadGroupRemoval;
+pub use dead_writes::DeadWrites;
+pub use decode_guards::DecodeGuards;
 pub use dump_ports::DumpResults;
 pub use externalize::Externalize;
+pub use feedback_to_enable::FeedbackToEnable;
+pub use fold_degenerate_guards::FoldDegenerateGuards;
+pub use fold_neq_ranges::FoldNeqRanges;
+pub use fold_range_comparisons::FoldRangeComparisons;
+pub use fold_sub_comparisons::FoldSubComparisons;
+pub use fuse_eq_chains::FuseEqChains;
 pub use go_insertion::GoInsertion;
+pub use guard_nnf::GuardNnf;
 pub use group_to_invoke::GroupToInvoke;
 pub use group_to_seq::GroupToSeq;
+pub use hoist_invariant_comparisons::HoistInvariantComparisons;
 pub use infer_share::InferShare;
+pub use lift_par_guards::LiftParGuards;
 pub use lower_guards::LowerGuards;
 pub use math_utilities::get_bit_width_from;
 pub use merge_assign::MergeAssign;
+pub use merge_constants::MergeConstants;
+pub use one_hot_selectors::OneHotSelectors;
 pub use papercut::Papercut;
+pub use prune_unreachable_states::PruneUnreachableStates;
 pub use remove_ids::RemoveIds;
+pub use remove_implied_guards::RemoveImpliedGuards;
+pub use remove_retention_writes::RemoveRetentionWrites;
 pub use reset_insertion::ResetInsertion;
+pub use self_comparison::SelfComparison;
+pub use share_comparisons::ShareComparisons;
+pub use simplify_dont_care::SimplifyDontCare;
 pub use simplify_static_guards::SimplifyStaticGuards;
+pub use specialize_branches::SpecializeBranches;
 pub use simplify_with_control::SimplifyWithControl;
 pub use static_fsm_opts::StaticFSMOpts;
 pub use static_inference::StaticInference;
 pub use static_inliner::StaticInliner;
 pub use static_promotion::StaticPromotion;
+pub use strip_activation_guards::StripActivationGuards;
 pub use unroll_bound::UnrollBounded;
 // pub use simplify_guards::SimplifyGuards;
 pub use add_guard::AddGuard;
@@ -0,0 +1,107 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir::{self as ir, RRC};
+use itertools::Itertools;
+use linked_hash_map::LinkedHashMap;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Computes the guards of assignments to a port into one-hot selector bits
+/// when the guards are mutually exclusive.
+///
+/// The assignments to a port form a multiplexer whose select logic is
+/// evaluated in priority order. When the guards are proven pairwise
+/// exclusive with [ir::Guard::is_mutually_exclusive], each guard is
+/// computed into a 1-bit wire and the assignments switch on the wires
+/// instead, so the multiplexer is selected by bits that are known to be
+/// one-hot:
+/// ```
+/// x.in = s.out == 2'd0 ? 8'd1;
+/// x.in = s.out == 2'd1 & a.out ? 8'd2;
+/// ```
+/// becomes:
+/// ```
+/// x.in = sel.out ? 8'd1;
+/// x.in = sel0.out ? 8'd2;
+/// sel.in = s.out == 2'd0 ? 1'd1;
+/// sel0.in = s.out == 2'd1 & a.out ? 1'd1;
+/// ```
+/// Ports whose guards cannot be proven exclusive keep their assignments.
+/// Guards that are already a single port need no selector, and equal
+/// guards share one. Each group and the continuous assignments are
+/// rewritten separately.
+#[derive(Default)]
+pub struct OneHotSelectors;
+
+impl Named for OneHotSelectors {
+    fn name() -> &'static str {
+        "one-hot-selectors"
+    }
+
+    fn description() -> &'static str {
+        "select between exclusive assignments with one-hot selector wires"
+    }
+}
+
+fn rewrite(
+    assigns: &mut Vec<ir::Assignment<ir::Nothing>>,
+    builder: &mut ir::Builder,
+) {
+    let mut drivers: LinkedHashMap<ir::Canonical, Vec<usize>> =
+        LinkedHashMap::new();
+    for (idx, assign) in assigns.iter().enumerate() {
+        let dst = assign.dst.borrow();
+        if !dst.is_hole() {
+            drivers.entry(dst.canonical()).or_default().push(idx);
+        }
+    }
+
+    let mut selectors: HashMap<String, RRC<ir::Port>> = HashMap::new();
+    let mut added = vec![];
+    for idxs in drivers.values() {
+        let exclusive = idxs.len() > 1
+            && idxs.iter().tuple_combinations().all(|(l, r)| {
+                assigns[*l].guard.is_mutually_exclusive(&assigns[*r].guard)
+            });
+        if !exclusive {
+            continue;
+        }
+        for idx in idxs {
+            let guard = (*assigns[*idx].guard).clone();
+            if matches!(guard, ir::Guard::Port(_)) {
+                continue;
+            }
+            let key = guard.canonical_key();
+            let sel = selectors.entry(key).or_insert_with(|| {
+                let wire = builder.add_primitive("sel", "std_wire", &[1]);
+                let one = builder.add_constant(1, 1);
+                let wire_in = wire.borrow().get("in");
+                let one_out = one.borrow().get("out");
+                added.push(builder.build_assignment(wire_in, one_out, guard));
+                wire.borrow().get("out")
+            });
+            assigns[*idx].guard = Box::new(ir::Guard::port(Rc::clone(sel)));
+        }
+    }
+    assigns.extend(added);
+}
+
+impl Visitor for OneHotSelectors {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let groups = comp.get_groups().iter().cloned().collect_vec();
+        let mut continuous = std::mem::take(&mut comp.continuous_assignments);
+        let mut builder = ir::Builder::new(comp, sigs);
+        for group in groups {
+            rewrite(&mut group.borrow_mut().assignments, &mut builder);
+        }
+        rewrite(&mut continuous, &mut builder);
+        comp.continuous_assignments = continuous;
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
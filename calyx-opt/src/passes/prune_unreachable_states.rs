@@ -0,0 +1,287 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+use std::collections::{HashMap, HashSet};
+
+/// Removes the logic for FSM states that can never be reached.
+///
+/// A `std_reg` is treated as an FSM if every assignment to its `in` port
+/// writes a constant, so the values it can hold are known: the reset value
+/// `0`, and the value of every write whose guard can be true in a reachable
+/// state. A write can only happen in a state `c` if its guard has a
+/// top-level conjunct `fsm.out == c`. The reachable states are computed as a
+/// fixed point starting from `0`.
+///
+/// Comparisons `fsm.out == c` against unreachable states are replaced with
+/// `false`, `fsm.out != c` with `true`, and assignments whose guard becomes
+/// `false` are removed. For example, if `fsm` is never written `2'd2`:
+/// ```
+/// r.in = fsm.out == 2'd2 ? 8'd20;
+/// done = fsm.out == 2'd1 | fsm.out == 2'd2 ? 1'd1;
+/// ```
+/// becomes:
+/// ```
+/// done = fsm.out == 2'd1 ? 1'd1;
+/// ```
+/// Registers that are external, `ref` cells, invoked, or passed to an invoke
+/// as a `ref` cell may be written elsewhere and are never treated as FSMs.
+#[derive(Default)]
+pub struct PruneUnreachableStates;
+
+impl Named for PruneUnreachableStates {
+    fn name() -> &'static str {
+        "prune-unreachable-states"
+    }
+
+    fn description() -> &'static str {
+        "remove assignments that are only enabled in unreachable FSM states"
+    }
+}
+
+/// A write of a constant to an FSM, along with the FSM states that the guard
+/// of the write requires.
+struct Write {
+    value: u64,
+    requires: Vec<(ir::Id, u64)>,
+}
+
+/// If the port is the output of one of the `fsms`, return the FSM's name.
+fn fsm_out(port: &ir::Port, fsms: &HashSet<ir::Id>) -> Option<ir::Id> {
+    if port.name != "out" {
+        return None;
+    }
+    match &port.parent {
+        ir::PortParent::Cell(cell) => {
+            let name = cell.upgrade().borrow().name();
+            fsms.contains(&name).then_some(name)
+        }
+        ir::PortParent::Group(_) | ir::PortParent::StaticGroup(_) => None,
+    }
+}
+
+/// If the guard compares the output of one of the `fsms` against a constant,
+/// return the comparison, the FSM's name, and the constant.
+fn state_comparison<T>(
+    guard: &ir::Guard<T>,
+    fsms: &HashSet<ir::Id>,
+) -> Option<(ir::PortComp, ir::Id, u64)> {
+    let ir::Guard::CompOp(op, l, r) = guard else {
+        return None;
+    };
+    if !matches!(op, ir::PortComp::Eq | ir::PortComp::Neq) {
+        return None;
+    }
+    let (l, r) = (l.borrow(), r.borrow());
+    let (fsm, value) = match (fsm_out(&l, fsms), fsm_out(&r, fsms)) {
+        (Some(fsm), None) => (fsm, r.constant_value()?),
+        (None, Some(fsm)) => (fsm, l.constant_value()?),
+        _ => return None,
+    };
+    Some((op.clone(), fsm, value))
+}
+
+/// Collect the FSM states required by the top-level conjuncts of the guard.
+fn requirements<T>(
+    guard: &ir::Guard<T>,
+    fsms: &HashSet<ir::Id>,
+    acc: &mut Vec<(ir::Id, u64)>,
+) {
+    match guard {
+        ir::Guard::And(l, r) => {
+            requirements(l, fsms, acc);
+            requirements(r, fsms, acc);
+        }
+        g => {
+            if let Some((ir::PortComp::Eq, fsm, value)) =
+                state_comparison(g, fsms)
+            {
+                acc.push((fsm, value));
+            }
+        }
+    }
+}
+
+/// Record the assignment if it writes to one of the candidate FSMs. An FSM
+/// that is written a non-constant value is no longer a candidate.
+fn record_write<T>(
+    assign: &ir::Assignment<T>,
+    fsms: &HashSet<ir::Id>,
+    writes: &mut HashMap<ir::Id, Option<Vec<Write>>>,
+) {
+    let dst = assign.dst.borrow();
+    if dst.name != "in" {
+        return;
+    }
+    let ir::PortParent::Cell(cell) = &dst.parent else {
+        return;
+    };
+    let Some(entry) = writes.get_mut(&cell.upgrade().borrow().name()) else {
+        return;
+    };
+    match (entry.as_mut(), assign.src.borrow().constant_value()) {
+        (Some(fsm_writes), Some(value)) => {
+            let mut requires = vec![];
+            requirements(&assign.guard, fsms, &mut requires);
+            fsm_writes.push(Write { value, requires });
+        }
+        _ => *entry = None,
+    }
+}
+
+/// Collect the cells that are invoked or passed to an invoke as `ref` cells.
+fn invoked_cells(con: &ir::Control, acc: &mut HashSet<ir::Id>) {
+    match con {
+        ir::Control::Seq(ir::Seq { stmts, .. })
+        | ir::Control::Par(ir::Par { stmts, .. }) => {
+            stmts.iter().for_each(|stmt| invoked_cells(stmt, acc))
+        }
+        ir::Control::If(ir::If {
+            tbranch, fbranch, ..
+        }) => {
+            invoked_cells(tbranch, acc);
+            invoked_cells(fbranch, acc);
+        }
+        ir::Control::While(ir::While { body, .. })
+        | ir::Control::Repeat(ir::Repeat { body, .. }) => {
+            invoked_cells(body, acc)
+        }
+        ir::Control::Invoke(ir::Invoke {
+            comp, ref_cells, ..
+        }) => {
+            acc.insert(comp.borrow().name());
+            acc.extend(ref_cells.iter().map(|(_, cell)| cell.borrow().name()));
+        }
+        ir::Control::Enable(_) | ir::Control::Empty(_) => {}
+        ir::Control::Static(sc) => static_invoked_cells(sc, acc),
+    }
+}
+
+fn static_invoked_cells(con: &ir::StaticControl, acc: &mut HashSet<ir::Id>) {
+    match con {
+        ir::StaticControl::Seq(ir::StaticSeq { stmts, .. })
+        | ir::StaticControl::Par(ir::StaticPar { stmts, .. }) => stmts
+            .iter()
+            .for_each(|stmt| static_invoked_cells(stmt, acc)),
+        ir::StaticControl::If(ir::StaticIf {
+            tbranch, fbranch, ..
+        }) => {
+            static_invoked_cells(tbranch, acc);
+            static_invoked_cells(fbranch, acc);
+        }
+        ir::StaticControl::Repeat(ir::StaticRepeat { body, .. }) => {
+            static_invoked_cells(body, acc)
+        }
+        ir::StaticControl::Invoke(ir::StaticInvoke {
+            comp, ref_cells, ..
+        }) => {
+            acc.insert(comp.borrow().name());
+            acc.extend(ref_cells.iter().map(|(_, cell)| cell.borrow().name()));
+        }
+        ir::StaticControl::Enable(_) | ir::StaticControl::Empty(_) => {}
+    }
+}
+
+/// The reachable states of each FSM in the component.
+fn reachable_states(comp: &ir::Component) -> HashMap<ir::Id, HashSet<u64>> {
+    let mut invoked = HashSet::new();
+    invoked_cells(&comp.control.borrow(), &mut invoked);
+    let fsms: HashSet<ir::Id> = comp
+        .cells
+        .iter()
+        .filter(|cell_ref| {
+            let cell = cell_ref.borrow();
+            cell.is_primitive(Some("std_reg"))
+                && !cell.is_reference()
+                && !cell.attributes.has(ir::BoolAttr::External)
+                && !invoked.contains(&cell.name())
+        })
+        .map(|cell| cell.borrow().name())
+        .collect();
+
+    let mut writes: HashMap<ir::Id, Option<Vec<Write>>> =
+        fsms.iter().map(|fsm| (*fsm, Some(vec![]))).collect();
+    comp.iter_assignments(|assign| record_write(assign, &fsms, &mut writes));
+    comp.iter_static_assignments(|assign| {
+        record_write(assign, &fsms, &mut writes)
+    });
+    let writes: HashMap<ir::Id, Vec<Write>> = writes
+        .into_iter()
+        .filter_map(|(fsm, writes)| Some((fsm, writes?)))
+        .collect();
+
+    // Every FSM starts in its reset state. A write is enabled once all the
+    // states its guard requires are reachable.
+    let mut reachable: HashMap<ir::Id, HashSet<u64>> = writes
+        .keys()
+        .map(|fsm| (*fsm, HashSet::from([0])))
+        .collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (fsm, fsm_writes) in &writes {
+            for write in fsm_writes {
+                let enabled = write.requires.iter().all(|(req, v)| {
+                    reachable.get(req).map_or(true, |s| s.contains(v))
+                });
+                if enabled {
+                    let states = reachable.get_mut(fsm).unwrap();
+                    changed |= states.insert(write.value);
+                }
+            }
+        }
+    }
+    reachable
+}
+
+/// Replace comparisons against unreachable states with constants and
+/// simplify the guard.
+fn fold<T: Clone>(
+    guard: ir::Guard<T>,
+    reachable: &HashMap<ir::Id, HashSet<u64>>,
+) -> ir::Guard<T> {
+    reachable.iter().fold(guard, |guard, (fsm, states)| {
+        let out = ir::Canonical::new(*fsm, ir::Id::from("out"));
+        guard.prune_unreachable(states, &out)
+    })
+}
+
+/// Removes assignments with a `false` guard. Assignments to group holes are
+/// kept so that groups keep their `done` condition.
+fn prune<T>(assigns: &mut Vec<ir::Assignment<T>>) {
+    assigns.retain(|assign| {
+        !assign.guard.is_false() || assign.dst.borrow().is_hole()
+    })
+}
+
+impl Visitor for PruneUnreachableStates {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let reachable = reachable_states(comp);
+        if reachable.is_empty() {
+            return Ok(Action::Stop);
+        }
+        comp.for_each_assignment(|assign| {
+            assign.guard.update(|g| fold(g, &reachable))
+        });
+        comp.for_each_static_assignment(|assign| {
+            assign.guard.update(|g| fold(g, &reachable))
+        });
+
+        for group in comp.get_groups() {
+            prune(&mut group.borrow_mut().assignments);
+        }
+        for group in comp.get_static_groups() {
+            prune(&mut group.borrow_mut().assignments);
+        }
+        for group in comp.comb_groups.iter() {
+            prune(&mut group.borrow_mut().assignments);
+        }
+        prune(&mut comp.continuous_assignments);
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
@@ -0,0 +1,103 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir::{self as ir, Nothing};
+use std::collections::{HashMap, HashSet};
+
+/// Removes conjuncts from the guards of a group's assignments that are
+/// implied by the guards that activate the group.
+///
+/// After control compilation, groups are activated by assignments to their
+/// `go` holes such as:
+/// ```
+/// A[go] = !A[done] & fsm.out == 2'd1 ? 1'd1;
+/// ```
+/// The assignments in `A` can only be active when `fsm.out == 2'd1` holds, so
+/// the following guard in `A` can be simplified:
+/// ```
+/// r.in = fsm.out == 2'd1 & x.out ? 32'd1; => r.in = x.out ? 32'd1;
+/// ```
+/// Groups that are enabled by the control program, or whose `go` hole is
+/// driven by anything other than `1'd1`, are not modified.
+#[derive(Default)]
+pub struct RemoveImpliedGuards {
+    /// Groups enabled by the control program.
+    enabled: HashSet<ir::Id>,
+}
+
+impl Named for RemoveImpliedGuards {
+    fn name() -> &'static str {
+        "remove-implied-guards"
+    }
+
+    fn description() -> &'static str {
+        "remove guard conjuncts implied by the activation of the group"
+    }
+}
+
+/// Remove the conjuncts of the guard implied by all the activations.
+fn remove_implied(
+    guard: ir::Guard<Nothing>,
+    activations: &[ir::Guard<Nothing>],
+) -> ir::Guard<Nothing> {
+    match guard {
+        ir::Guard::And(l, r) => {
+            remove_implied(*l, activations) & remove_implied(*r, activations)
+        }
+        g if activations.iter().all(|act| act.implies(&g)) => ir::Guard::True,
+        g => g,
+    }
+}
+
+impl Visitor for RemoveImpliedGuards {
+    fn enable(
+        &mut self,
+        s: &mut ir::Enable,
+        _comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        self.enabled.insert(s.group.borrow().name());
+        Ok(Action::Continue)
+    }
+
+    fn finish(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        // The guards under which each group's `go` hole is driven high. Groups
+        // whose `go` hole is driven by a non-constant are mapped to `None`.
+        let mut activations: HashMap<ir::Id, Option<Vec<ir::Guard<Nothing>>>> =
+            HashMap::new();
+        comp.iter_assignments(|assign| {
+            let dst = assign.dst.borrow();
+            if dst.is_hole() && dst.name == "go" {
+                let acts = activations
+                    .entry(dst.get_parent_name())
+                    .or_insert_with(|| Some(vec![]));
+                if assign.src.borrow().is_constant(1, 1) {
+                    if let Some(acts) = acts {
+                        acts.push((*assign.guard).clone());
+                    }
+                } else {
+                    *acts = None;
+                }
+            }
+        });
+
+        for group in comp.get_groups().iter() {
+            let name = group.borrow().name();
+            if self.enabled.contains(&name) {
+                continue;
+            }
+            let Some(Some(acts)) = activations.get(&name) else {
+                continue;
+            };
+            for assign in group.borrow_mut().assignments.iter_mut() {
+                assign.guard.update(|g| remove_implied(g, acts));
+            }
+        }
+
+        Ok(Action::Continue)
+    }
+}
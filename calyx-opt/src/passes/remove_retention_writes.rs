@@ -0,0 +1,88 @@
+use crate::analysis::RegWrites;
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+use std::collections::HashSet;
+
+/// Removes writes of a register's own value that only retain it.
+///
+/// A register that is only written its own output, and is only enabled when
+/// it is, keeps its value whether or not it is enabled:
+/// ```
+/// r.in = g ? r.out;
+/// r.write_en = g ? 1'd1;
+/// ```
+/// Both assignments are removed, since a disabled register already holds
+/// its value. The only other effect of enabling a register is its `done`
+/// signal, so registers whose `done` port is read anywhere in the
+/// component, including by the control program, are left alone. The write
+/// enable must be the only one of the register in the assignment list and
+/// its guard must be the disjunction of the guards of the feedback
+/// assignments. Each group and the continuous assignments are rewritten
+/// separately.
+#[derive(Default)]
+pub struct RemoveRetentionWrites;
+
+impl Named for RemoveRetentionWrites {
+    fn name() -> &'static str {
+        "remove-retention-writes"
+    }
+
+    fn description() -> &'static str {
+        "remove register writes that only retain the register's value"
+    }
+}
+
+fn rewrite<T>(
+    assigns: &mut Vec<ir::Assignment<T>>,
+    done_read: &HashSet<ir::Id>,
+) where
+    T: Clone + Eq + ToString,
+{
+    let regs = RegWrites::collect(assigns, done_read);
+    let mut removed = HashSet::new();
+    for writes in regs.into_values() {
+        let [en] = writes.write_en[..] else {
+            continue;
+        };
+        if writes.feedback.is_empty()
+            || !writes.data.is_empty()
+            || !assigns[en].src.borrow().is_constant(1, 1)
+        {
+            continue;
+        }
+        let held = ir::Guard::or_all(
+            writes
+                .feedback
+                .iter()
+                .map(|idx| Some(*assigns[*idx].guard.clone())),
+        );
+        if assigns[en].guard.canonical_key() != held.canonical_key() {
+            continue;
+        }
+        removed.insert(en);
+        removed.extend(writes.feedback);
+    }
+
+    RegWrites::remove(assigns, &removed);
+}
+
+impl Visitor for RemoveRetentionWrites {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let done_read = RegWrites::done_read(comp);
+        for group in comp.get_groups() {
+            rewrite(&mut group.borrow_mut().assignments, &done_read);
+        }
+        for group in comp.get_static_groups() {
+            rewrite(&mut group.borrow_mut().assignments, &done_read);
+        }
+        rewrite(&mut comp.continuous_assignments, &done_read);
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
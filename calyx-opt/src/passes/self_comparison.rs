@@ -0,0 +1,85 @@
+use crate::traversal::{
+    Action, DiagnosticContext, DiagnosticPass, Named, VisResult, Visitor,
+};
+use calyx_ir::{self as ir, LibrarySignatures};
+use calyx_utils::{Error, WithPos};
+use std::rc::Rc;
+
+/// Warns about guards that compare a port against itself, which is almost
+/// always a bug:
+/// ```
+/// r.in = x.out < x.out ? 32'd1; // always false
+/// ```
+/// Ports are compared by identity so comparisons between ports with the same
+/// name on different cells are not reported.
+#[derive(Default)]
+pub struct SelfComparison {
+    /// Diagnostic context for reporting warnings
+    diag: DiagnosticContext,
+}
+
+impl Named for SelfComparison {
+    fn name() -> &'static str {
+        "self-comparison"
+    }
+
+    fn description() -> &'static str {
+        "warn about guards that compare a port against itself"
+    }
+}
+
+impl DiagnosticPass for SelfComparison {
+    fn diagnostics(&self) -> &DiagnosticContext {
+        &self.diag
+    }
+}
+
+impl SelfComparison {
+    fn check_guard<T>(&mut self, guard: &ir::Guard<T>, pos: &ir::Attributes) {
+        match guard {
+            ir::Guard::CompOp(op, l, r) if Rc::ptr_eq(l, r) => {
+                let result = matches!(
+                    op,
+                    ir::PortComp::Eq | ir::PortComp::Geq | ir::PortComp::Leq
+                );
+                self.diag.warning(
+                    Error::misc(format!(
+                        "Guard compares `{}' against itself and is always {result}",
+                        l.borrow().canonical(),
+                    ))
+                    .with_pos(pos),
+                );
+            }
+            ir::Guard::And(l, r)
+            | ir::Guard::Or(l, r)
+            | ir::Guard::Implies(l, r) => {
+                self.check_guard(l, pos);
+                self.check_guard(r, pos);
+            }
+            ir::Guard::Not(g) => self.check_guard(g, pos),
+            ir::Guard::CompOp(..)
+            | ir::Guard::Port(_)
+            | ir::Guard::True
+            | ir::Guard::Info(_) => (),
+        }
+    }
+}
+
+impl Visitor for SelfComparison {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        comp.iter_assignments(|assign| {
+            self.check_guard(&assign.guard, &assign.attributes)
+        });
+        comp.iter_static_assignments(|assign| {
+            self.check_guard(&assign.guard, &assign.attributes)
+        });
+
+        // This pass doesn't need to traverse the control program.
+        Ok(Action::Stop)
+    }
+}
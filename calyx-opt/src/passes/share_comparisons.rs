@@ -0,0 +1,294 @@
+use crate::traversal::{
+    Action, ConstructVisitor, Named, ParseVal, PassOpt, VisResult, Visitor,
+};
+use calyx_ir::{self as ir, RRC};
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Materializes every distinct comparison used in a guard as a comparator
+/// primitive driving a 1-bit wire, and replaces the comparisons in guards
+/// with the output of the wire. Comparisons are identified up to flipping
+/// `>` and `>=` into `<` and `<=` and reordering the operands of `==` and
+/// `!=`, so that, for example:
+/// ```
+/// group one { r.in = x.out < 4'd8 ? 1'd1; ... }
+/// group two { s.in = 4'd8 > x.out ? 1'd1; ... }
+/// ```
+/// share a single comparator:
+/// ```
+/// group one { r.in = cmp.out ? 1'd1; ... }
+/// group two { s.in = cmp.out ? 1'd1; ... }
+/// lt.left = x.out;
+/// lt.right = 4'd8;
+/// cmp.in = lt.out;
+/// ```
+/// The comparators are driven by continuous assignments, so comparisons that
+/// read group holes are left in place. For the same reason there is no need
+/// to check that the operands are stable: the wire always carries the value
+/// the inline comparison would have had. The comparators are not placed in a
+/// comb group, since a comb group is only active while the `if` or `while`
+/// using it evaluates its condition.
+///
+/// With the `min-uses` option, only comparisons used by at least that many
+/// guards in the component are materialized, and the others stay inline. A
+/// comparison that occurs several times in one guard counts as one use.
+pub struct ShareComparisons {
+    /// The number of uses a comparison needs to be materialized.
+    min_uses: u64,
+}
+
+impl ConstructVisitor for ShareComparisons {
+    fn from(ctx: &ir::Context) -> calyx_utils::CalyxResult<Self>
+    where
+        Self: Sized,
+    {
+        let opts = Self::get_opts(ctx);
+        Ok(ShareComparisons {
+            min_uses: opts[&"min-uses"].pos_num().unwrap_or(1),
+        })
+    }
+
+    fn clear_data(&mut self) {
+        /* do nothing */
+    }
+}
+
+impl Named for ShareComparisons {
+    fn name() -> &'static str {
+        "share-comparisons"
+    }
+
+    fn description() -> &'static str {
+        "materialize each distinct guard comparison once and share its output"
+    }
+
+    fn opts() -> Vec<PassOpt> {
+        vec![PassOpt::new(
+            "min-uses",
+            "the number of guards that must use a comparison to materialize it",
+            ParseVal::Num(1),
+            PassOpt::parse_num,
+        )]
+    }
+}
+
+/// A comparison written with `<`, `<=`, `==`, or `!=`, identified by the
+/// canonical names of its operands.
+type Key = (ir::PortComp, ir::Canonical, ir::Canonical);
+
+/// Wires driven by materialized comparators.
+type Comparators = HashMap<Key, RRC<ir::Port>>;
+
+/// A normalized comparison and its operands.
+type Normalized<'a> = (Key, &'a RRC<ir::Port>, &'a RRC<ir::Port>);
+
+/// Normalizes the comparison. Returns `None` if it reads a group hole.
+fn normalize<'a>(
+    op: &ir::PortComp,
+    l: &'a RRC<ir::Port>,
+    r: &'a RRC<ir::Port>,
+) -> Option<Normalized<'a>> {
+    let is_cell_port = |p: &RRC<ir::Port>| {
+        matches!(p.borrow().parent, ir::PortParent::Cell(_))
+    };
+    if !is_cell_port(l) || !is_cell_port(r) {
+        return None;
+    }
+    let name = |p: &RRC<ir::Port>| p.borrow().canonical();
+    let (op, l, r) = match op {
+        ir::PortComp::Gt => (ir::PortComp::Lt, r, l),
+        ir::PortComp::Geq => (ir::PortComp::Leq, r, l),
+        ir::PortComp::Eq | ir::PortComp::Neq if name(l) > name(r) => {
+            (op.clone(), r, l)
+        }
+        _ => (op.clone(), l, r),
+    };
+    let key = (op, name(l), name(r));
+    Some((key, l, r))
+}
+
+/// The name of the primitive of a normalized comparison without `std_`.
+fn prefix(op: &ir::PortComp) -> &'static str {
+    match op {
+        ir::PortComp::Eq => "eq",
+        ir::PortComp::Neq => "neq",
+        ir::PortComp::Lt => "lt",
+        ir::PortComp::Leq => "le",
+        ir::PortComp::Gt | ir::PortComp::Geq => unreachable!(),
+    }
+}
+
+/// Returns the output of the wire driven by the comparison, materializing it
+/// if it does not exist yet.
+fn comparator(
+    (key, l, r): Normalized,
+    comparators: &mut Comparators,
+    builder: &mut ir::Builder,
+) -> RRC<ir::Port> {
+    if let Some(out) = comparators.get(&key) {
+        return Rc::clone(out);
+    }
+
+    let prefix = prefix(&key.0);
+    let prim = format!("std_{prefix}");
+
+    let cmp_cell = builder.add_primitive(prefix, prim, &[l.borrow().width]);
+    let wire_cell = builder.add_primitive("cmp", "std_wire", &[1]);
+    let (cmp, wire) = (cmp_cell.borrow(), wire_cell.borrow());
+    let assigns = vec![
+        builder.build_assignment(
+            cmp.get("left"),
+            Rc::clone(l),
+            ir::Guard::True,
+        ),
+        builder.build_assignment(
+            cmp.get("right"),
+            Rc::clone(r),
+            ir::Guard::True,
+        ),
+        builder.build_assignment(
+            wire.get("in"),
+            cmp.get("out"),
+            ir::Guard::True,
+        ),
+    ];
+    builder.add_continuous_assignments(assigns);
+    let out = wire.get("out");
+    comparators.insert(key, Rc::clone(&out));
+    out
+}
+
+/// Collects the normalized comparisons in the guard.
+fn comparisons<T>(guard: &ir::Guard<T>, acc: &mut HashSet<Key>) {
+    match guard {
+        ir::Guard::And(l, r)
+        | ir::Guard::Or(l, r)
+        | ir::Guard::Implies(l, r) => {
+            comparisons(l, acc);
+            comparisons(r, acc);
+        }
+        ir::Guard::Not(g) => comparisons(g, acc),
+        ir::Guard::CompOp(op, l, r) => {
+            if let Some((key, ..)) = normalize(op, l, r) {
+                acc.insert(key);
+            }
+        }
+        ir::Guard::True | ir::Guard::Port(_) | ir::Guard::Info(_) => {}
+    }
+}
+
+/// Counts the guards that use each normalized comparison. A comparison that
+/// occurs several times in one guard is only counted once.
+fn count<T>(guard: &ir::Guard<T>, uses: &mut HashMap<Key, u64>) {
+    let mut acc = HashSet::new();
+    comparisons(guard, &mut acc);
+    for key in acc {
+        *uses.entry(key).or_default() += 1;
+    }
+}
+
+fn share<T>(
+    guard: &mut ir::Guard<T>,
+    shared: &HashSet<Key>,
+    comparators: &mut Comparators,
+    builder: &mut ir::Builder,
+) {
+    match guard {
+        ir::Guard::And(l, r)
+        | ir::Guard::Or(l, r)
+        | ir::Guard::Implies(l, r) => {
+            share(l, shared, comparators, builder);
+            share(r, shared, comparators, builder);
+        }
+        ir::Guard::Not(g) => share(g, shared, comparators, builder),
+        ir::Guard::CompOp(op, l, r) => match normalize(op, l, r) {
+            Some(cmp) if shared.contains(&cmp.0) => {
+                *guard = ir::Guard::port(comparator(cmp, comparators, builder));
+            }
+            _ => {}
+        },
+        ir::Guard::True | ir::Guard::Port(_) | ir::Guard::Info(_) => {}
+    }
+}
+
+fn share_assigns<T>(
+    assigns: &mut [ir::Assignment<T>],
+    shared: &HashSet<Key>,
+    comparators: &mut Comparators,
+    builder: &mut ir::Builder,
+) {
+    for assign in assigns {
+        share(&mut assign.guard, shared, comparators, builder);
+    }
+}
+
+impl Visitor for ShareComparisons {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        let mut uses = HashMap::new();
+        comp.iter_assignments(|assign| count(&assign.guard, &mut uses));
+        comp.iter_static_assignments(|assign| count(&assign.guard, &mut uses));
+        let shared: HashSet<Key> = uses
+            .into_iter()
+            .filter(|(_, n)| *n >= self.min_uses)
+            .map(|(key, _)| key)
+            .collect();
+        if shared.is_empty() {
+            return Ok(Action::Stop);
+        }
+
+        let mut builder = ir::Builder::new(comp, sigs);
+        let mut comparators = Comparators::new();
+
+        // The comparators are added to the continuous assignments while they
+        // are detached, so keep the new assignments after the existing ones.
+        let mut conts =
+            std::mem::take(&mut builder.component.continuous_assignments);
+        share_assigns(&mut conts, &shared, &mut comparators, &mut builder);
+        conts.append(&mut builder.component.continuous_assignments);
+        builder.component.continuous_assignments = conts;
+
+        let groups =
+            builder.component.get_groups().iter().cloned().collect_vec();
+        for group in groups {
+            share_assigns(
+                &mut group.borrow_mut().assignments,
+                &shared,
+                &mut comparators,
+                &mut builder,
+            );
+        }
+        let groups = builder
+            .component
+            .get_static_groups()
+            .iter()
+            .cloned()
+            .collect_vec();
+        for group in groups {
+            share_assigns(
+                &mut group.borrow_mut().assignments,
+                &shared,
+                &mut comparators,
+                &mut builder,
+            );
+        }
+        let groups =
+            builder.component.comb_groups.iter().cloned().collect_vec();
+        for group in groups {
+            share_assigns(
+                &mut group.borrow_mut().assignments,
+                &shared,
+                &mut comparators,
+                &mut builder,
+            );
+        }
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
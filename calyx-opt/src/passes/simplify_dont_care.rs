@@ -0,0 +1,199 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+use std::collections::HashMap;
+
+/// Guards with more distinct atoms than this are not simplified.
+const MAX_ATOMS: usize = 12;
+
+/// Simplifies guards using don't-care conditions.
+///
+/// An assignment with the `@dont_care` attribute does not write a value.
+/// Instead, its guard describes when the value of its destination is
+/// irrelevant:
+/// ```
+/// r.in = a.out & b.out ? 32'd1;
+/// @dont_care r.in = !b.out ? 32'd0;
+/// ```
+/// The pass removes these annotations and replaces the guard of the
+/// assignment to the destination with the smallest guard, obtained by
+/// dropping operands, that agrees with the original whenever the don't-care
+/// condition is false. In the example, `b.out` can be dropped:
+/// ```
+/// r.in = a.out ? 32'd1;
+/// ```
+///
+/// Destinations written by more than one assignment in the same group are
+/// not simplified since a relaxed guard could overlap with the guards of
+/// the other assignments. Ports and comparisons are treated as independent
+/// atoms, and guards with more than [MAX_ATOMS] atoms are left unchanged.
+///
+/// This is the only pass that removes `@dont_care` assignments, so it runs
+/// at the start of the `validate` alias, before any other pass sees them.
+#[derive(Default)]
+pub struct SimplifyDontCare;
+
+impl Named for SimplifyDontCare {
+    fn name() -> &'static str {
+        "simplify-dont-care"
+    }
+
+    fn description() -> &'static str {
+        "simplify guards using @dont_care conditions on their destinations"
+    }
+}
+
+/// Add the leaves of the guard to `atoms` if not already present.
+fn collect_atoms<T: Eq + Clone>(
+    guard: &ir::Guard<T>,
+    atoms: &mut Vec<ir::Guard<T>>,
+) {
+    match guard {
+        ir::Guard::And(l, r)
+        | ir::Guard::Or(l, r)
+        | ir::Guard::Implies(l, r) => {
+            collect_atoms(l, atoms);
+            collect_atoms(r, atoms);
+        }
+        ir::Guard::Not(g) => collect_atoms(g, atoms),
+        ir::Guard::True => (),
+        leaf => {
+            if !atoms.contains(leaf) {
+                atoms.push(leaf.clone())
+            }
+        }
+    }
+}
+
+/// Evaluate the guard when the atoms whose bits are set in `mask` are true.
+fn eval<T: Eq>(
+    guard: &ir::Guard<T>,
+    atoms: &[ir::Guard<T>],
+    mask: u64,
+) -> bool {
+    match guard {
+        ir::Guard::And(l, r) => eval(l, atoms, mask) && eval(r, atoms, mask),
+        ir::Guard::Or(l, r) => eval(l, atoms, mask) || eval(r, atoms, mask),
+        ir::Guard::Implies(l, r) => {
+            !eval(l, atoms, mask) || eval(r, atoms, mask)
+        }
+        ir::Guard::Not(g) => !eval(g, atoms, mask),
+        ir::Guard::True => true,
+        leaf => {
+            let idx = atoms.iter().position(|a| a == leaf).unwrap();
+            mask & (1 << idx) != 0
+        }
+    }
+}
+
+/// All guards obtained by dropping exactly one operand of an `&` or `|`
+/// in the guard.
+fn drop_operand<T: Clone>(guard: &ir::Guard<T>) -> Vec<ir::Guard<T>> {
+    match guard {
+        ir::Guard::And(l, r) | ir::Guard::Or(l, r) => {
+            let rebuild = |l, r| match guard {
+                ir::Guard::And(..) => ir::Guard::And(Box::new(l), Box::new(r)),
+                _ => ir::Guard::Or(Box::new(l), Box::new(r)),
+            };
+            let mut out = vec![(**l).clone(), (**r).clone()];
+            for nl in drop_operand(l) {
+                out.push(rebuild(nl, (**r).clone()));
+            }
+            for nr in drop_operand(r) {
+                out.push(rebuild((**l).clone(), nr));
+            }
+            out
+        }
+        ir::Guard::Not(g) => drop_operand(g)
+            .into_iter()
+            .map(|g| ir::Guard::Not(Box::new(g)))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns the smallest guard that agrees with `guard` whenever
+/// `dont_care` is false.
+fn simplify_guard<T: Eq + Clone>(
+    guard: ir::Guard<T>,
+    dont_care: &ir::Guard<T>,
+) -> ir::Guard<T> {
+    let mut atoms = vec![];
+    collect_atoms(&guard, &mut atoms);
+    collect_atoms(dont_care, &mut atoms);
+    if atoms.len() > MAX_ATOMS {
+        return guard;
+    }
+    let care_equiv = |cand: &ir::Guard<T>| {
+        (0..1u64 << atoms.len()).all(|mask| {
+            eval(dont_care, &atoms, mask)
+                || eval(&guard, &atoms, mask) == eval(cand, &atoms, mask)
+        })
+    };
+
+    let mut best = guard.clone();
+    // Every candidate is smaller than the current one so this terminates.
+    while let Some(next) = drop_operand(&best)
+        .into_iter()
+        .filter(&care_equiv)
+        .min_by_key(|cand| cand.size())
+    {
+        best = next;
+    }
+    best
+}
+
+/// Simplify the guards of the assignments using the don't-care conditions
+/// defined by the `@dont_care` assignments in the same list.
+fn simplify_assigns<T: Eq + Clone>(assigns: &mut Vec<ir::Assignment<T>>) {
+    let (annotations, mut rest): (Vec<_>, Vec<_>) = std::mem::take(assigns)
+        .into_iter()
+        .partition(|a| a.attributes.has(ir::BoolAttr::DontCare));
+
+    let mut dont_care: HashMap<ir::Canonical, ir::Guard<T>> = HashMap::new();
+    for assign in annotations {
+        let key = assign.dst.borrow().canonical();
+        let guard = match dont_care.remove(&key) {
+            Some(g) => g.or(*assign.guard),
+            None => *assign.guard,
+        };
+        dont_care.insert(key, guard);
+    }
+
+    let mut writers: HashMap<ir::Canonical, usize> = HashMap::new();
+    for assign in &rest {
+        *writers.entry(assign.dst.borrow().canonical()).or_default() += 1;
+    }
+
+    for assign in &mut rest {
+        let key = assign.dst.borrow().canonical();
+        if let Some(dc) = dont_care.get(&key) {
+            if writers[&key] == 1 {
+                assign.guard.update(|g| simplify_guard(g, dc));
+            }
+        }
+    }
+    *assigns = rest;
+}
+
+impl Visitor for SimplifyDontCare {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        for group in comp.get_groups().iter() {
+            simplify_assigns(&mut group.borrow_mut().assignments);
+        }
+        for group in comp.get_static_groups().iter() {
+            simplify_assigns(&mut group.borrow_mut().assignments);
+        }
+        for group in comp.comb_groups.iter() {
+            simplify_assigns(&mut group.borrow_mut().assignments);
+        }
+        simplify_assigns(&mut comp.continuous_assignments);
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
@@ -27,6 +27,10 @@ impl From<GuardWrap> for Expr<ir::Guard> {
             ir::Guard::Or(l, r) => {
                 Expr::or(GuardWrap::from(*l).into(), GuardWrap::from(*r).into())
             }
+            ir::Guard::Implies(l, r) => Expr::or(
+                Expr::not(GuardWrap::from(*l).into()),
+                GuardWrap::from(*r).into(),
+            ),
             ir::Guard::Not(e) => Expr::not((*e).into()),
             ir::Guard::True => Expr::Const(true),
             ir::Guard::CompOp(
@@ -64,6 +64,7 @@ impl SimplifyStaticGuards {
             | ir::Guard::CompOp(_, _, _)
             | ir::Guard::Not(_)
             | ir::Guard::Or(_, _)
+            | ir::Guard::Implies(_, _)
             | ir::Guard::Port(_) => Some(g),
         }
     }
@@ -134,6 +135,10 @@ impl SimplifyStaticGuards {
                 Box::new(Self::simplify_guard(*g1, group_latency)),
                 Box::new(Self::simplify_guard(*g2, group_latency)),
             ),
+            ir::Guard::Implies(g1, g2) => ir::Guard::Implies(
+                Box::new(Self::simplify_guard(*g1, group_latency)),
+                Box::new(Self::simplify_guard(*g2, group_latency)),
+            ),
             ir::Guard::And(_, _) => {
                 Self::simplify_anded_guards(guard, group_latency)
             }
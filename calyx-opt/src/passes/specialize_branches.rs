@@ -0,0 +1,245 @@
+use crate::analysis::ReadWriteSet;
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir as ir;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Simplifies the guards of the groups in the branches of an `if` using its
+/// condition.
+///
+/// Within the true branch of `if c`, `c` is true, and within the false
+/// branch it is false. Guards of groups enabled in a branch are simplified
+/// with [ir::Guard::assume], and assignments whose guard becomes `false` are
+/// removed. For example:
+/// ```
+/// group t { x.in = c.out & f.out ? 8'd1; x.in = !c.out ? 8'd2; ... }
+/// if c.out { t; }
+/// ```
+/// becomes:
+/// ```
+/// group t { x.in = f.out ? 8'd1; ... }
+/// if c.out { t; }
+/// ```
+/// The condition is only known for the whole branch if the values it reads
+/// cannot change while the branch runs, so it must either be the output of
+/// a `std_reg`, or the output of a comparison cell such as `std_lt` whose
+/// inputs are driven by the `with` group with outputs of `std_reg`s or
+/// constants. In the second case, the known fact is the comparison of
+/// those ports, like `x.out < 8'd5`. The registers must not be written by
+/// the branch, by threads running in parallel with it, or by continuous
+/// assignments. Groups that are enabled more than once, or whose `go` hole
+/// is driven by an assignment, are left alone.
+#[derive(Default)]
+pub struct SpecializeBranches;
+
+impl Named for SpecializeBranches {
+    fn name() -> &'static str {
+        "specialize-branches"
+    }
+
+    fn description() -> &'static str {
+        "simplify guards in the branches of an if using its condition"
+    }
+}
+
+/// Guards with known values.
+type Facts = Vec<(ir::Guard<ir::Nothing>, bool)>;
+
+/// If the port is the output of a `std_reg` or a constant, return the
+/// register it reads, if any.
+fn stable_port(port: &ir::Port) -> Option<Option<ir::Id>> {
+    if port.constant_value().is_some() {
+        return Some(None);
+    }
+    let ir::PortParent::Cell(cell) = &port.parent else {
+        return None;
+    };
+    let cell = cell.upgrade();
+    let cell = cell.borrow();
+    (port.name == "out"
+        && cell.is_primitive(Some("std_reg"))
+        && !cell.is_reference())
+    .then(|| Some(cell.name()))
+}
+
+/// If `port` is the output of a register, the guard reading it and the
+/// register.
+fn register(
+    port: &ir::RRC<ir::Port>,
+) -> Option<(ir::Guard<ir::Nothing>, Vec<ir::Id>)> {
+    let reg = stable_port(&port.borrow())??;
+    Some((ir::Guard::port(Rc::clone(port)), vec![reg]))
+}
+
+/// If `port` is the output of a comparison cell whose inputs are driven by
+/// `cond` with stable ports, the comparison of those ports and the registers
+/// it reads.
+fn comparison(
+    port: &ir::RRC<ir::Port>,
+    cond: &ir::CombGroup,
+) -> Option<(ir::Guard<ir::Nothing>, Vec<ir::Id>)> {
+    let port = port.borrow();
+    let ir::PortParent::Cell(cell) = &port.parent else {
+        return None;
+    };
+    let cell = cell.upgrade();
+    let cell = cell.borrow();
+    let comparisons = [
+        ("std_eq", ir::PortComp::Eq),
+        ("std_neq", ir::PortComp::Neq),
+        ("std_lt", ir::PortComp::Lt),
+        ("std_gt", ir::PortComp::Gt),
+        ("std_le", ir::PortComp::Leq),
+        ("std_ge", ir::PortComp::Geq),
+    ];
+    let (_, op) = comparisons
+        .into_iter()
+        .find(|(prim, _)| cell.is_primitive(Some(*prim)))?;
+    if port.name != "out" || cell.is_reference() {
+        return None;
+    }
+    let driver = |name: &str| {
+        let input = cell.get(name);
+        let mut drivers = cond
+            .assignments
+            .iter()
+            .filter(|assign| Rc::ptr_eq(&assign.dst, &input));
+        match (drivers.next(), drivers.next()) {
+            (Some(assign), None) if assign.guard.is_true() => {
+                let reg = stable_port(&assign.src.borrow())?;
+                Some((Rc::clone(&assign.src), reg))
+            }
+            _ => None,
+        }
+    };
+    let (left, left_reg) = driver("left")?;
+    let (right, right_reg) = driver("right")?;
+    let regs = left_reg.into_iter().chain(right_reg).collect();
+    Some((ir::Guard::CompOp(op, left, right), regs))
+}
+
+/// The names of the cells written by the control program.
+fn writes(con: &ir::Control) -> HashSet<ir::Id> {
+    ReadWriteSet::control_read_write_set::<false>(con)
+        .1
+        .into_iter()
+        .map(|cell| cell.borrow().name())
+        .collect()
+}
+
+/// Collect the facts that hold while each group is enabled, counting the
+/// number of times every group is enabled.
+fn collect(
+    con: &ir::Control,
+    facts: &mut Facts,
+    concurrent: &HashSet<ir::Id>,
+    known: &mut HashMap<ir::Id, (usize, Facts)>,
+) {
+    match con {
+        ir::Control::Enable(ir::Enable { group, .. }) => {
+            let entry = known.entry(group.borrow().name()).or_default();
+            entry.0 += 1;
+            entry.1.extend(facts.iter().cloned());
+        }
+        ir::Control::Seq(ir::Seq { stmts, .. }) => {
+            for stmt in stmts {
+                collect(stmt, facts, concurrent, known);
+            }
+        }
+        ir::Control::Par(ir::Par { stmts, .. }) => {
+            let thread_writes: Vec<_> = stmts.iter().map(writes).collect();
+            for (idx, stmt) in stmts.iter().enumerate() {
+                let mut others = concurrent.clone();
+                for (other, w) in thread_writes.iter().enumerate() {
+                    if other != idx {
+                        others.extend(w.iter().cloned());
+                    }
+                }
+                collect(stmt, facts, &others, known);
+            }
+        }
+        ir::Control::If(ir::If {
+            port,
+            cond,
+            tbranch,
+            fbranch,
+            ..
+        }) => {
+            let fact = register(port).or_else(|| {
+                comparison(port, &cond.as_ref()?.borrow())
+            });
+            for (branch, value) in [(tbranch, true), (fbranch, false)] {
+                let branch_writes = writes(branch);
+                let holds = fact.as_ref().map_or(false, |(_, regs)| {
+                    regs.iter().all(|r| {
+                        !branch_writes.contains(r) && !concurrent.contains(r)
+                    })
+                });
+                if holds {
+                    let (atom, _) = fact.clone().unwrap();
+                    facts.push((atom, value));
+                }
+                collect(branch, facts, concurrent, known);
+                if holds {
+                    facts.pop();
+                }
+            }
+        }
+        ir::Control::While(ir::While { body, .. })
+        | ir::Control::Repeat(ir::Repeat { body, .. }) => {
+            collect(body, facts, concurrent, known)
+        }
+        ir::Control::Invoke(_)
+        | ir::Control::Empty(_)
+        | ir::Control::Static(_) => {}
+    }
+}
+
+impl Visitor for SpecializeBranches {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        // Registers written by continuous assignments can change at any time.
+        let continuous: HashSet<ir::Id> = comp
+            .continuous_assignments
+            .iter()
+            .filter(|assign| !assign.dst.borrow().is_hole())
+            .map(|assign| assign.dst.borrow().get_parent_name())
+            .collect();
+        let mut known = HashMap::new();
+        collect(&comp.control.borrow(), &mut vec![], &continuous, &mut known);
+
+        // Groups whose `go` hole is driven can run outside of the control.
+        let mut driven = HashSet::new();
+        comp.iter_assignments(|assign| {
+            let dst = assign.dst.borrow();
+            if dst.is_hole() && dst.name == "go" {
+                driven.insert(dst.get_parent_name());
+            }
+        });
+
+        for group in comp.get_groups().iter() {
+            let mut group = group.borrow_mut();
+            let Some((1, facts)) = known.get(&group.name()) else {
+                continue;
+            };
+            if facts.is_empty() || driven.contains(&group.name()) {
+                continue;
+            }
+            for assign in group.assignments.iter_mut() {
+                for (atom, value) in facts {
+                    assign.guard.update(|g| g.assume(atom, *value));
+                }
+            }
+            group.assignments.retain(|assign| {
+                !assign.guard.is_false() || assign.dst.borrow().is_hole()
+            });
+        }
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
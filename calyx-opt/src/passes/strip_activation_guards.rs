@@ -0,0 +1,123 @@
+use crate::traversal::{Action, Named, VisResult, Visitor};
+use calyx_ir::{self as ir, RRC};
+use std::rc::Rc;
+
+/// Removes the activation conjuncts from the guards of groups marked with
+/// the `@always` attribute, which are known to be active in every cycle.
+///
+/// Activation conjuncts come from control lowering. They are the reads of
+/// the group's own `go` hole that [super::GoInsertion] adds, and the
+/// conditions under which [super::TopDownCompileControl] enables the
+/// group: the conjuncts shared by every guard that sets its `go` hole,
+/// such as the FSM state test `fsm.out == 2'd0`. This transforms:
+/// ```
+/// group tick<"always"=1> {
+///     x.in = c.out & fsm.out == 2'd0 & tick[go] ? a.out;
+///     x.write_en = tick[go] ? 1'd1;
+///     tick[done] = x.done;
+/// }
+/// group tdcc {
+///     tick[go] = !tick[done] & fsm.out == 2'd0 ? 1'd1;
+///     ...
+/// }
+/// ```
+/// into:
+/// ```
+/// group tick<"always"=1> {
+///     x.in = c.out ? a.out;
+///     x.write_en = 1'd1;
+///     tick[done] = x.done;
+/// }
+/// ```
+/// Other conjuncts are kept, as are activation conditions below `|` or `!`.
+/// If the `go` hole is ever set to something other than `1'd1`, only the
+/// reads of the hole are removed.
+#[derive(Default)]
+pub struct StripActivationGuards;
+
+impl Named for StripActivationGuards {
+    fn name() -> &'static str {
+        "strip-activation-guards"
+    }
+
+    fn description() -> &'static str {
+        "remove activation conjuncts from the guards of @always groups"
+    }
+}
+
+/// The conjuncts of the chain of `&` nodes rooted at the guard.
+fn conjuncts<T>(guard: &ir::Guard<T>) -> Vec<&ir::Guard<T>> {
+    match guard {
+        ir::Guard::And(l, r) => {
+            let mut out = conjuncts(l);
+            out.extend(conjuncts(r));
+            out
+        }
+        g => vec![g],
+    }
+}
+
+/// The conjuncts shared by the guards of every assignment that sets `go`.
+fn activation_conjuncts(
+    comp: &ir::Component,
+    go: &RRC<ir::Port>,
+) -> Vec<ir::Guard<ir::Nothing>> {
+    let mut common: Option<Vec<ir::Guard<ir::Nothing>>> = None;
+    let mut only_sets = true;
+    comp.iter_assignments(|assign| {
+        if !Rc::ptr_eq(&assign.dst, go) {
+            return;
+        }
+        only_sets &= assign.src.borrow().is_constant(1, 1);
+        let conjs = conjuncts(&assign.guard);
+        match &mut common {
+            Some(common) => common.retain(|c| conjs.contains(&c)),
+            None => common = Some(conjs.into_iter().cloned().collect()),
+        }
+    });
+    match common {
+        Some(common) if only_sets => {
+            common.into_iter().filter(|c| !c.is_true()).collect()
+        }
+        _ => vec![],
+    }
+}
+
+/// Remove the conjuncts of the guard that read `go` or activate the group.
+fn strip(
+    guard: ir::Guard<ir::Nothing>,
+    go: &RRC<ir::Port>,
+    activation: &[ir::Guard<ir::Nothing>],
+) -> ir::Guard<ir::Nothing> {
+    match guard {
+        ir::Guard::And(l, r) => {
+            strip(*l, go, activation) & strip(*r, go, activation)
+        }
+        ir::Guard::Port(p) if Rc::ptr_eq(&p, go) => ir::Guard::True,
+        g if activation.contains(&g) => ir::Guard::True,
+        g => g,
+    }
+}
+
+impl Visitor for StripActivationGuards {
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        _sigs: &ir::LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        for group in comp.get_groups().iter() {
+            if !group.borrow().attributes.has(ir::BoolAttr::Always) {
+                continue;
+            }
+            let go = group.borrow().get("go");
+            let activation = activation_conjuncts(comp, &go);
+            for assign in group.borrow_mut().assignments.iter_mut() {
+                assign.guard.update(|g| strip(g, &go, &activation));
+            }
+        }
+
+        // This pass doesn't modify any control.
+        Ok(Action::Stop)
+    }
+}
@@ -1,3 +1,4 @@
+use super::fold_degenerate_guards::degenerate_operand;
 use crate::traversal::{Action, ConstructVisitor, Named, VisResult, Visitor};
 use crate::traversal::{DiagnosticContext, DiagnosticPass, DiagnosticResult};
 use calyx_ir::{
@@ -262,6 +263,77 @@ where
     Ok(())
 }
 
+/// Returns an error if the guard of the assignment compares against a
+/// zero-width port or an undefined value.
+fn degenerate_comparison<T>(assign: &ir::Assignment<T>) -> CalyxResult<()> {
+    fn find<T>(guard: &ir::Guard<T>) -> Option<(String, &'static str)> {
+        match guard {
+            ir::Guard::CompOp(_, l, r) => [l, r].into_iter().find_map(|p| {
+                let p = p.borrow();
+                degenerate_operand(&p)
+                    .map(|reason| (ir::Printer::port_to_str(&p), reason))
+            }),
+            ir::Guard::And(l, r)
+            | ir::Guard::Or(l, r)
+            | ir::Guard::Implies(l, r) => find(l).or_else(|| find(r)),
+            ir::Guard::Not(g) => find(g),
+            ir::Guard::Port(_) | ir::Guard::True | ir::Guard::Info(_) => None,
+        }
+    }
+    if let Some((port, reason)) = find(&assign.guard) {
+        return Err(Error::malformed_structure(format!(
+            "Guard compares against {reason} `{port}'. Use `-p fold-degenerate-guards` to replace the comparison with false."
+        ))
+        .with_pos(&assign.attributes));
+    }
+    Ok(())
+}
+
+/// Returns the reason the port's value is not known statically in each cycle,
+/// if it is a group hole or the `@done` port of a cell without a fixed
+/// latency.
+fn dynamic_operand(port: &ir::Port) -> Option<&'static str> {
+    match &port.parent {
+        ir::PortParent::Group(_) | ir::PortParent::StaticGroup(_) => {
+            Some("the hole")
+        }
+        ir::PortParent::Cell(cell) => {
+            if !port.attributes.has(ir::NumAttr::Done) {
+                return None;
+            }
+            let cell = cell.upgrade();
+            let cell = cell.borrow();
+            if matches!(cell.prototype, ir::CellType::ThisComponent) {
+                return None;
+            }
+            let is_static = cell.find_all_with_attr(ir::NumAttr::Go).any(|go| {
+                go.borrow().attributes.get(ir::NumAttr::Interval).is_some()
+            });
+            (!is_static).then_some("the done signal of the dynamic cell")
+        }
+    }
+}
+
+/// Returns an error if the guard of an assignment in the static group reads
+/// a port whose value is not known statically in each cycle.
+fn dynamic_guard(
+    group: ir::Id,
+    assign: &ir::Assignment<StaticTiming>,
+) -> CalyxResult<()> {
+    let dynamic = assign.guard.all_ports().into_iter().find_map(|p| {
+        let p = p.borrow();
+        dynamic_operand(&p).map(|reason| (ir::Printer::port_to_str(&p), reason))
+    });
+    if let Some((port, reason)) = dynamic {
+        return Err(Error::malformed_structure(format!(
+            "Guard `{}' in static group `{group}' reads {reason} `{port}', whose value is not known statically in each cycle",
+            ir::Printer::guard_str(&assign.guard),
+        ))
+        .with_pos(&assign.attributes));
+    }
+    Ok(())
+}
+
 /// Returns true if `cell_in` is a subtype of `cell_out`.
 /// Currenly this only checks for [`type_equivalence`](#method.calyx_ir::structure::Port::type_equivalent)
 /// between ports. It does not fully examine the cells
@@ -556,9 +628,24 @@ impl Visitor for WellFormed {
                         }
                     },
                 )?;
+                if let Err(err) = dynamic_guard(group.name(), assign) {
+                    self.diag.err(err);
+                }
             }
         }
 
+        // Check that guards don't compare against zero-width or undefined ports
+        comp.iter_assignments(|assign| {
+            if let Err(err) = degenerate_comparison(assign) {
+                self.diag.err(err);
+            }
+        });
+        comp.iter_static_assignments(|assign| {
+            if let Err(err) = degenerate_comparison(assign) {
+                self.diag.err(err);
+            }
+        });
+
         // Check for obvious conflicting assignments in the continuous assignments
         obvious_conflicts(
             comp.continuous_assignments.iter(),
@@ -5,6 +5,7 @@ mod namegenerator;
 mod out_file;
 mod pos_string;
 mod position;
+mod union_find;
 mod weight_graph;
 
 mod math;
@@ -19,4 +20,5 @@ pub use pos_string::PosString;
 pub use position::{
     FileIdx, GPosIdx, GlobalPositionTable, PosIdx, PositionTable, WithPos,
 };
+pub use union_find::UnionFind;
 pub use weight_graph::{BoolIdx, Idx, WeightGraph};
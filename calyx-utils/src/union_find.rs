@@ -0,0 +1,41 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A disjoint-set forest over values of type `T`. Values that have never
+/// been [unioned](UnionFind::union) are in a class of their own.
+pub struct UnionFind<T> {
+    /// Mapping from a value to its parent in the forest. Representatives
+    /// of a class are not in the map.
+    parent: HashMap<T, T>,
+}
+
+impl<T> Default for UnionFind<T> {
+    fn default() -> Self {
+        UnionFind {
+            parent: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone> UnionFind<T> {
+    /// The representative of the class containing `value`.
+    pub fn find(&self, value: &T) -> T {
+        let mut cur = value;
+        while let Some(next) = self.parent.get(cur) {
+            cur = next;
+        }
+        cur.clone()
+    }
+
+    /// Merge the classes containing `a` and `b`.
+    pub fn union(&mut self, a: &T, b: &T) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent.insert(a, b);
+        }
+    }
+
+    /// Whether `a` and `b` are in the same class.
+    pub fn equiv(&self, a: &T, b: &T) -> bool {
+        self.find(a) == self.find(b)
+    }
+}
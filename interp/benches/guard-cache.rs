@@ -0,0 +1,51 @@
+use calyx_frontend as frontend;
+use calyx_ir as ir;
+use calyx_opt::pass_manager::PassManager;
+use criterion::{criterion_group, criterion_main, Criterion};
+use interp::{
+    flatten::{
+        flat_ir,
+        structures::{context::Context, environment::Simulator},
+    },
+    serialization::DataDump,
+};
+use std::path::Path;
+
+/// The TCAM benchmark compiled down to guarded assignments, so that most of
+/// the simulation is spent evaluating guards.
+fn compiled_tcam() -> Context {
+    let file = Path::new("tests/benchmarks/tcam-32.futil");
+    let ws =
+        frontend::Workspace::construct(&Some(file.into()), Path::new(".."))
+            .unwrap();
+    let mut ctx = ir::from_ast::ast_to_ir(ws).unwrap();
+    let pm = PassManager::default_passes().unwrap();
+    pm.execute_plan(&mut ctx, &["all".to_string()], &[], &[], false)
+        .unwrap();
+    flat_ir::translate(&ctx)
+}
+
+fn simulate(ctx: &Context, cache_guards: bool) -> DataDump {
+    let mut sim = Simulator::build_simulator(ctx, &None).unwrap();
+    sim.cache_guards(cache_guards);
+    sim.run_program().unwrap();
+    sim.dump_memories(true, true)
+}
+
+fn guard_cache_bench(c: &mut Criterion) {
+    let ctx = compiled_tcam();
+    assert_eq!(
+        simulate(&ctx, false),
+        simulate(&ctx, true),
+        "caching guards changed the result of the simulation"
+    );
+
+    let mut group = c.benchmark_group("guard-cache");
+    group.sample_size(10);
+    group.bench_function("uncached", |b| b.iter(|| simulate(&ctx, false)));
+    group.bench_function("cached", |b| b.iter(|| simulate(&ctx, true)));
+    group.finish();
+}
+
+criterion_group!(benches, guard_cache_bench);
+criterion_main!(benches);
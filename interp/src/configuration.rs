@@ -15,6 +15,14 @@ pub struct Config {
     pub dump_registers: bool,
     /// dumps all memories rather than just external ones
     pub dump_all_memories: bool,
+    /// warns when a guard compares against an uninitialized register
+    pub check_uninitialized_reads: bool,
+    /// reuses guard values until a port they read changes
+    pub cache_guards: bool,
+    /// reports which guards were ever true and ever false
+    pub guard_coverage: bool,
+    /// records the value of every guard in every cycle
+    pub guard_timeline: bool,
 }
 
 #[derive(Default)]
@@ -39,6 +47,10 @@ pub struct ConfigBuilder {
     quiet: Option<bool>,
     dump_registers: Option<bool>,
     dump_all_memories: Option<bool>,
+    check_uninitialized_reads: Option<bool>,
+    cache_guards: Option<bool>,
+    guard_coverage: Option<bool>,
+    guard_timeline: Option<bool>,
 }
 
 impl ConfigBuilder {
@@ -84,6 +96,30 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets the `check_uninitialized_reads` flag to the given value.
+    pub fn check_uninitialized_reads(mut self, value: bool) -> Self {
+        self.check_uninitialized_reads = Some(value);
+        self
+    }
+
+    /// Sets the `cache_guards` flag to the given value.
+    pub fn cache_guards(mut self, value: bool) -> Self {
+        self.cache_guards = Some(value);
+        self
+    }
+
+    /// Sets the `guard_coverage` flag to the given value.
+    pub fn guard_coverage(mut self, value: bool) -> Self {
+        self.guard_coverage = Some(value);
+        self
+    }
+
+    /// Sets the `guard_timeline` flag to the given value.
+    pub fn guard_timeline(mut self, value: bool) -> Self {
+        self.guard_timeline = Some(value);
+        self
+    }
+
     /// Builds a [`Config`] from the current state of the [`ConfigBuilder`]. For
     /// any unset options, the default value will be used.
     pub fn build(self) -> Config {
@@ -96,6 +132,12 @@ impl ConfigBuilder {
                 .unwrap_or_default(),
             dump_registers: self.dump_registers.unwrap_or_default(),
             dump_all_memories: self.dump_all_memories.unwrap_or_default(),
+            check_uninitialized_reads: self
+                .check_uninitialized_reads
+                .unwrap_or_default(),
+            cache_guards: self.cache_guards.unwrap_or_default(),
+            guard_coverage: self.guard_coverage.unwrap_or_default(),
+            guard_timeline: self.guard_timeline.unwrap_or_default(),
         }
     }
 }
@@ -116,7 +116,7 @@ impl_index!(LocalRefCellOffset);
 /// Enum used in assignments to encapsulate the different types of port
 /// references these are always relative to a component's base-point and must be
 /// converted to global references when used.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum PortRef {
     /// A port belonging to a non-ref cell/group in the current component or the
     /// component itself
@@ -666,6 +666,9 @@ where
     pub parent: ComponentIdx,
     /// The prototype of the cell
     pub prototype: CellPrototype,
+    /// True if the cell is marked `@zero_init`, i.e. its initial zero value
+    /// is intentional
+    pub zero_init: bool,
 }
 
 impl<C> CellDefinitionInfo<C>
@@ -678,12 +681,14 @@ where
         ports: IndexRange<C>,
         parent: ComponentIdx,
         prototype: CellPrototype,
+        zero_init: bool,
     ) -> Self {
         Self {
             name,
             ports,
             parent,
             prototype,
+            zero_init,
         }
     }
 }
@@ -305,6 +305,7 @@ fn insert_cell(
 ) {
     let cell_ref = cell.borrow();
     let id = secondary_ctx.string_table.insert(cell_ref.name());
+    let zero_init = cell_ref.attributes.has(cir::BoolAttr::ZeroInit);
 
     if !cell_ref.is_reference() {
         let base = aux.port_offset_map.peek_next_index();
@@ -321,6 +322,7 @@ fn insert_cell(
             range,
             comp_id,
             create_cell_prototype(cell, comp_id_map),
+            zero_init,
         );
         let cell_offset = aux.cell_offset_map.insert(cell_def);
         layout.cell_map.insert(cell.as_raw(), cell_offset.into());
@@ -342,6 +344,7 @@ fn insert_cell(
             range,
             comp_id,
             create_cell_prototype(cell, comp_id_map),
+            zero_init,
         );
         let cell_offset = aux.ref_cell_offset_map.insert(ref_cell_def);
         layout.cell_map.insert(cell.as_raw(), cell_offset.into());
@@ -506,6 +509,24 @@ fn is_primitive(cell_ref: &std::cell::Ref<cir::Cell>) -> bool {
         || matches!(&cell_ref.prototype, cir::CellType::Constant { .. })
 }
 
+/// Whether the port belongs to a cell whose outputs are floating-point values.
+/// Panics if such a port is not 32 or 64 bits wide.
+fn is_float_port(port: &RRC<cir::Port>) -> bool {
+    let port = port.borrow();
+    let is_float = match &port.parent {
+        cir::PortParent::Cell(cell) => {
+            cell.upgrade().borrow().attributes.has(cir::BoolAttr::Float)
+        }
+        cir::PortParent::Group(_) | cir::PortParent::StaticGroup(_) => false,
+    };
+    assert!(
+        !is_float || port.width == 32 || port.width == 64,
+        "floating-point port `{}' must be 32 or 64 bits wide",
+        port.canonical()
+    );
+    is_float
+}
+
 impl FlattenTree for cir::Guard<cir::Nothing> {
     type Output = Guard;
     type IdxType = GuardIdx;
@@ -523,13 +544,22 @@ impl FlattenTree for cir::Guard<cir::Nothing> {
             cir::Guard::And(a, b) => {
                 Guard::And(handle.enqueue(a), handle.enqueue(b))
             }
+            cir::Guard::Implies(a, b) => {
+                Guard::Implies(handle.enqueue(a), handle.enqueue(b))
+            }
             cir::Guard::Not(n) => Guard::Not(handle.enqueue(n)),
             cir::Guard::True => Guard::True,
-            cir::Guard::CompOp(op, a, b) => Guard::Comp(
-                op.clone(),
-                *aux.get(&a.as_raw()).unwrap(),
-                *aux.get(&b.as_raw()).unwrap(),
-            ),
+            cir::Guard::CompOp(op, a, b) => {
+                let (a_ref, b_ref) = (
+                    *aux.get(&a.as_raw()).unwrap(),
+                    *aux.get(&b.as_raw()).unwrap(),
+                );
+                if is_float_port(a) || is_float_port(b) {
+                    Guard::FloatComp(op.clone(), a_ref, b_ref)
+                } else {
+                    Guard::Comp(op.clone(), a_ref, b_ref)
+                }
+            }
             cir::Guard::Port(p) => Guard::Port(*aux.get(&p.as_raw()).unwrap()),
             cir::Guard::Info(_) => panic!("Guard::Info(_) not handled yet"),
         }
@@ -10,7 +10,7 @@ pub type GuardMap = IndexedMap<GuardIdx, Guard>;
 
 /// A boolean expression that determines whether an assignment fires. Analogue
 /// of [calyx_ir::Guard]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Guard {
     /// A guard that always fires. This is the default value for a guard and
     /// applies when a guard is not explicitly defined.
@@ -19,10 +19,15 @@ pub enum Guard {
     Or(GuardIdx, GuardIdx),
     /// A conjunction of two guards
     And(GuardIdx, GuardIdx),
+    /// An implication between two guards
+    Implies(GuardIdx, GuardIdx),
     /// A negation of a guard
     Not(GuardIdx),
     /// A guard that applies a comparison operator to two ports
     Comp(PortComp, PortRef, PortRef),
+    /// A guard that compares two ports holding IEEE-754 floating-point
+    /// values. Used when either port belongs to a cell marked `@float`.
+    FloatComp(PortComp, PortRef, PortRef),
     /// A guard that evaluates a given port as a boolean. In such cases, the
     /// port must be a single bit.
     Port(PortRef),
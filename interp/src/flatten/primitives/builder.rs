@@ -42,9 +42,12 @@ pub fn build_primitive(
             "Build primitive erroneously called on a calyx component"
         ),
         CellPrototype::SingleWidth { op, width } => match op {
-            SingleWidthType::Reg => {
-                Box::new(StdReg::new(base_port, cell_idx, *width))
-            }
+            SingleWidthType::Reg => Box::new(StdReg::new(
+                base_port,
+                cell_idx,
+                *width,
+                prim.zero_init,
+            )),
             SingleWidthType::Not => Box::new(StdNot::new(base_port)),
             SingleWidthType::And => Box::new(StdAnd::new(base_port)),
             SingleWidthType::Or => Box::new(StdOr::new(base_port)),
@@ -129,6 +129,12 @@ pub trait Primitive {
     fn dump_memory_state(&self) -> Option<Vec<u8>> {
         None
     }
+
+    /// Returns true if the given port of this primitive outputs state that
+    /// has never been written.
+    fn is_uninitialized(&self, _port: GlobalPortIdx) -> bool {
+        false
+    }
 }
 
 /// An empty primitive implementation used for testing. It does not do anything
@@ -24,6 +24,9 @@ pub struct StdReg {
     internal_state: BitVecValue,
     global_idx: GlobalCellIdx,
     done_is_high: bool,
+    /// False until the register is written or reset, unless its initial
+    /// zero value is intentional.
+    initialized: bool,
 }
 
 impl StdReg {
@@ -33,6 +36,7 @@ impl StdReg {
         base_port: GlobalPortIdx,
         global_idx: GlobalCellIdx,
         width: u32,
+        zero_init: bool,
     ) -> Self {
         let internal_state = BitVecValue::zero(width);
         Self {
@@ -40,6 +44,7 @@ impl StdReg {
             global_idx,
             internal_state,
             done_is_high: false,
+            initialized: zero_init,
         }
     }
 }
@@ -57,6 +62,7 @@ impl Primitive for StdReg {
         let done_port = if port_map[reset].as_bool().unwrap_or_default() {
             self.internal_state =
                 BitVecValue::zero(self.internal_state.width());
+            self.initialized = true;
             port_map.insert_val(
                 done,
                 AssignedValue::cell_value(BitVecValue::fals()),
@@ -69,6 +75,7 @@ impl Primitive for StdReg {
                 .clone();
 
             self.done_is_high = true;
+            self.initialized = true;
 
             port_map.insert_val(
                 done,
@@ -126,6 +133,11 @@ impl Primitive for StdReg {
     fn dump_memory_state(&self) -> Option<Vec<u8>> {
         Some(self.internal_state.clone().to_bytes_le())
     }
+
+    fn is_uninitialized(&self, port: GlobalPortIdx) -> bool {
+        ports![&self.base_port; out_idx: Self::OUT];
+        !self.initialized && port == out_idx
+    }
 }
 
 pub struct MemDx<const SEQ: bool> {
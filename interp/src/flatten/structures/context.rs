@@ -191,9 +191,10 @@ impl SecondaryContext {
         ports: IndexRange<LocalPortOffset>,
         parent: ComponentIdx,
         prototype: CellPrototype,
+        zero_init: bool,
     ) -> CellDefinitionIdx {
         self.local_cell_defs
-            .push(CellInfo::new(name, ports, parent, prototype))
+            .push(CellInfo::new(name, ports, parent, prototype, zero_init))
     }
 
     /// Insert a new reference cell definition into the context and return its index
@@ -203,9 +204,10 @@ impl SecondaryContext {
         ports: IndexRange<LocalRefPortOffset>,
         parent: ComponentIdx,
         prototype: CellPrototype,
+        zero_init: bool,
     ) -> RefCellDefinitionIdx {
         self.ref_cell_defs
-            .push(RefCellInfo::new(name, ports, parent, prototype))
+            .push(RefCellInfo::new(name, ports, parent, prototype, zero_init))
     }
 }
 
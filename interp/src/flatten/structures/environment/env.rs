@@ -3,6 +3,7 @@ use super::{
         context::Context, index_trait::IndexRange, indexed_map::IndexedMap,
     },
     assignments::{GroupInterfacePorts, ScheduledAssignments},
+    guard_cache::GuardCache,
     program_counter::{PcMaps, ProgramCounter, WithEntry},
     traverser::{Path, TraversalError},
 };
@@ -25,6 +26,7 @@ use crate::{
             },
             wires::guards::Guard,
         },
+        flat_ir::component::AssignmentDefinitionLocation,
         primitives::{self, prim_trait::UpdateStatus, Primitive},
         structures::{
             context::{LookupName, PortDefinitionInfo},
@@ -44,6 +46,8 @@ use baa::{BitVecOps, BitVecValue};
 use itertools::Itertools;
 use owo_colors::OwoColorize;
 use slog::warn;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
 use std::fmt::Write;
 
@@ -1179,15 +1183,176 @@ impl<C: AsRef<Context> + Clone> Environment<C> {
 /// the environment to avoid confusion
 pub struct Simulator<C: AsRef<Context> + Clone> {
     env: Environment<C>,
+    /// Warn when a guard compares against an uninitialized register.
+    check_uninitialized: bool,
+    /// The uninitialized ports and the assignments reading them that have
+    /// already been reported.
+    reported_uninitialized: HashSet<(GlobalPortIdx, AssignmentIdx)>,
+    /// Guard values reused until a port they read changes. Only present when
+    /// guard caching is enabled.
+    guard_cache: Option<RefCell<GuardCache>>,
+    /// Record the values taken by the guards of active assignments.
+    track_coverage: bool,
+    /// For each assignment whose guard has been evaluated while it was
+    /// active, whether the guard was ever true and whether it was ever false.
+    guard_coverage: HashMap<AssignmentIdx, (bool, bool)>,
+    /// Record the value of every guard in every cycle.
+    record_guards: bool,
+    /// The settled value of every guard in each cycle so far, keyed by the
+    /// component instance and the assignment.
+    guard_timeline: Vec<HashMap<(GlobalCellIdx, AssignmentIdx), Option<bool>>>,
 }
 
 impl<C: AsRef<Context> + Clone> Simulator<C> {
     pub fn new(env: Environment<C>) -> Self {
-        let mut output = Self { env };
+        let mut output = Self {
+            env,
+            check_uninitialized: false,
+            reported_uninitialized: HashSet::new(),
+            guard_cache: None,
+            track_coverage: false,
+            guard_coverage: HashMap::new(),
+            record_guards: false,
+            guard_timeline: vec![],
+        };
         output.set_root_go_high();
         output
     }
 
+    /// Enables warnings for guards that compare against a register that has
+    /// never been written or reset. Registers marked `@zero_init` are
+    /// considered to be initialized.
+    pub fn check_uninitialized_reads(&mut self, enable: bool) {
+        self.check_uninitialized = enable;
+    }
+
+    /// Enables reusing the value of a guard until one of the ports it reads
+    /// changes. Structurally identical guards share their cached value, so
+    /// this avoids evaluating them once per assignment.
+    pub fn cache_guards(&mut self, enable: bool) {
+        self.guard_cache = enable
+            .then(|| RefCell::new(GuardCache::new(&self.ctx().primary.guards)));
+    }
+
+    /// Enables recording, for every assignment, whether its guard was ever
+    /// true and whether it was ever false while the assignment was active.
+    /// See [`Simulator::guard_coverage_report`].
+    pub fn track_guard_coverage(&mut self, enable: bool) {
+        self.track_coverage = enable;
+        self.guard_coverage.clear();
+    }
+
+    /// A report with a line for every assignment in the program that is not
+    /// guarded by `true`, saying whether its guard has been true and whether
+    /// it has been false while the assignment was active. Guards that have
+    /// never been true are candidates for dead code. Instances of the same
+    /// component share their coverage.
+    pub fn guard_coverage_report(&self) -> String {
+        let ctx = self.ctx();
+        let mut out = String::new();
+        for (assign_idx, assign) in ctx.primary.assignments.iter() {
+            if matches!(ctx.primary[assign.guard], Guard::True) {
+                continue;
+            }
+            let (reached_true, reached_false) = self
+                .guard_coverage
+                .get(&assign_idx)
+                .copied()
+                .unwrap_or_default();
+            let yes_no = |b: bool| if b { "yes" } else { "no" };
+            let (comp_idx, loc) = self.assignment_location(assign_idx);
+            writeln!(
+                out,
+                "{}: {} (reached-true: {}, reached-false: {})",
+                loc,
+                ctx.printer().print_assignment(comp_idx, assign_idx).trim(),
+                yes_no(reached_true),
+                yes_no(reached_false),
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// Enables recording the value of the guard of every assignment in
+    /// every cycle. See [`Simulator::guard_timeline_vcd`].
+    pub fn record_guard_timeline(&mut self, enable: bool) {
+        self.record_guards = enable;
+        self.guard_timeline.clear();
+    }
+
+    /// The guard values recorded since [`Simulator::record_guard_timeline`]
+    /// was enabled as a VCD waveform, with one 1-bit signal for every
+    /// assignment that is not guarded by `true` and one time step per
+    /// cycle. The signal of the assignment at index `idx` of a group is
+    /// named `guard_<group>_<idx>` and is declared in a scope named after
+    /// the component instance. Continuous assignments use `continuous` as
+    /// the group, and assignments implied by invokes are not included. A
+    /// guard that is undefined in a cycle has the value `x`.
+    pub fn guard_timeline_vcd(&self) -> String {
+        let ctx = self.ctx();
+        let recorded: BTreeSet<(GlobalCellIdx, AssignmentIdx)> = self
+            .guard_timeline
+            .iter()
+            .flat_map(|cycle| cycle.keys().copied())
+            .collect();
+        let mut scopes: BTreeMap<GlobalCellIdx, Vec<(String, String)>> =
+            BTreeMap::new();
+        let mut signals = vec![];
+        for (cell, assign) in recorded {
+            let (comp_idx, loc) = ctx.find_assignment_definition(assign);
+            let (group, assigns) = match loc {
+                AssignmentDefinitionLocation::Group(g) => {
+                    (ctx.lookup_name(g).clone(), &ctx.primary[g].assignments)
+                }
+                AssignmentDefinitionLocation::CombGroup(g) => {
+                    (ctx.lookup_name(g).clone(), &ctx.primary[g].assignments)
+                }
+                AssignmentDefinitionLocation::ContinuousAssignment => (
+                    "continuous".to_string(),
+                    &ctx.primary[comp_idx].continuous_assignments,
+                ),
+                AssignmentDefinitionLocation::Invoke(_) => continue,
+            };
+            let idx = assign.index() - assigns.start().index();
+            let code = vcd_identifier(signals.len());
+            scopes
+                .entry(cell)
+                .or_default()
+                .push((format!("guard_{group}_{idx}"), code.clone()));
+            signals.push(((cell, assign), code));
+        }
+
+        let mut out = String::new();
+        writeln!(out, "$timescale 1ns $end").unwrap();
+        for (cell, vars) in &scopes {
+            writeln!(out, "$scope module {} $end", self.get_full_name(cell))
+                .unwrap();
+            for (name, code) in vars {
+                writeln!(out, "$var wire 1 {code} {name} $end").unwrap();
+            }
+            writeln!(out, "$upscope $end").unwrap();
+        }
+        writeln!(out, "$enddefinitions $end").unwrap();
+        // Only changes are written after the first cycle.
+        let mut last: HashMap<(GlobalCellIdx, AssignmentIdx), char> =
+            HashMap::new();
+        for (time, cycle) in self.guard_timeline.iter().enumerate() {
+            writeln!(out, "#{time}").unwrap();
+            for (key, code) in &signals {
+                let val = match cycle.get(key) {
+                    Some(Some(true)) => '1',
+                    Some(Some(false)) => '0',
+                    Some(None) | None => 'x',
+                };
+                if last.insert(*key, val) != Some(val) {
+                    writeln!(out, "{val}{code}").unwrap();
+                }
+            }
+        }
+        out
+    }
+
     pub(crate) fn env(&self) -> &Environment<C> {
         &self.env
     }
@@ -1610,6 +1775,9 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
     }
 
     pub fn step(&mut self) -> InterpreterResult<()> {
+        if self.record_guards {
+            self.guard_timeline.push(HashMap::new());
+        }
         self.converge()?;
 
         let out: Result<(), BoxedInterpreterError> = {
@@ -1879,10 +2047,56 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
         Ok(())
     }
 
+    /// Interpret the bits of a 32 or 64-bit value as an IEEE-754 number.
+    fn float_value(val: &BitVecValue) -> f64 {
+        let bits = val.to_u64().unwrap();
+        match val.width() {
+            32 => f32::from_bits(bits as u32) as f64,
+            64 => f64::from_bits(bits),
+            w => unreachable!("floating-point value with width {w}"),
+        }
+    }
+
     fn evaluate_guard(
         &self,
         guard: GuardIdx,
         comp: GlobalCellIdx,
+    ) -> Option<bool> {
+        let Some(cache) = &self.guard_cache else {
+            return self.compute_guard(guard, comp);
+        };
+        if let Some(val) = cache.borrow_mut().lookup(guard, comp) {
+            return val;
+        }
+        let mark = cache.borrow_mut().start();
+        let val = self.compute_guard(guard, comp);
+        cache
+            .borrow_mut()
+            .finish(guard, comp, mark, val, &self.env.ports);
+        val
+    }
+
+    /// The value of a port read by a guard leaf. Records the read so the
+    /// cached guards reading the port can be evicted when it changes.
+    fn read_guard_port(&self, port: GlobalPortIdx) -> &PortValue {
+        if let Some(cache) = &self.guard_cache {
+            cache.borrow_mut().read(port);
+        }
+        &self.env.ports[port]
+    }
+
+    /// Evicts the cached guards that read the port. Must be called whenever
+    /// the port changes.
+    fn invalidate_guards(&self, port: GlobalPortIdx) {
+        if let Some(cache) = &self.guard_cache {
+            cache.borrow_mut().invalidate(port);
+        }
+    }
+
+    fn compute_guard(
+        &self,
+        guard: GuardIdx,
+        comp: GlobalCellIdx,
     ) -> Option<bool> {
         let guard = &self.ctx().primary[guard];
         match guard {
@@ -1897,6 +2111,11 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                 let g2 = self.evaluate_guard(*b, comp)?;
                 Some(g1 && g2)
             }
+            Guard::Implies(a, b) => {
+                let g1 = self.evaluate_guard(*a, comp)?;
+                let g2 = self.evaluate_guard(*b, comp)?;
+                Some(!g1 || g2)
+            }
             Guard::Not(n) => Some(!self.evaluate_guard(*n, comp)?),
             Guard::Comp(c, a, b) => {
                 let comp_v = self.env.cells[comp].unwrap_comp();
@@ -1906,8 +2125,8 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                 let b = self
                     .lookup_global_port_id(comp_v.convert_to_global_port(b));
 
-                let a_val = self.env.ports[a].val()?;
-                let b_val = self.env.ports[b].val()?;
+                let a_val = self.read_guard_port(a).val()?;
+                let b_val = self.read_guard_port(b).val()?;
                 match c {
                     calyx_ir::PortComp::Eq => a_val == b_val,
                     calyx_ir::PortComp::Neq => a_val != b_val,
@@ -1918,11 +2137,118 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                 }
                 .into()
             }
+            Guard::FloatComp(c, a, b) => {
+                let comp_v = self.env.cells[comp].unwrap_comp();
+
+                let a = self
+                    .lookup_global_port_id(comp_v.convert_to_global_port(a));
+                let b = self
+                    .lookup_global_port_id(comp_v.convert_to_global_port(b));
+
+                let a_val = Self::float_value(self.read_guard_port(a).val()?);
+                let b_val = Self::float_value(self.read_guard_port(b).val()?);
+                match c {
+                    calyx_ir::PortComp::Eq => a_val == b_val,
+                    calyx_ir::PortComp::Neq => a_val != b_val,
+                    calyx_ir::PortComp::Gt => a_val > b_val,
+                    calyx_ir::PortComp::Lt => a_val < b_val,
+                    calyx_ir::PortComp::Geq => a_val >= b_val,
+                    calyx_ir::PortComp::Leq => a_val <= b_val,
+                }
+                .into()
+            }
             Guard::Port(p) => {
                 let comp_v = self.env.cells[comp].unwrap_comp();
                 let p_idx = self
                     .lookup_global_port_id(comp_v.convert_to_global_port(p));
-                self.env.ports[p_idx].as_bool()
+                self.read_guard_port(p_idx).as_bool()
+            }
+        }
+    }
+
+    /// Collect the ports read by comparisons in the guard that belong to
+    /// primitives with uninitialized state.
+    fn uninitialized_guard_reads(
+        &self,
+        guard: GuardIdx,
+        comp: GlobalCellIdx,
+        acc: &mut Vec<GlobalPortIdx>,
+    ) {
+        match &self.ctx().primary[guard] {
+            Guard::True | Guard::Port(_) => {}
+            Guard::Or(a, b) | Guard::And(a, b) | Guard::Implies(a, b) => {
+                self.uninitialized_guard_reads(*a, comp, acc);
+                self.uninitialized_guard_reads(*b, comp, acc);
+            }
+            Guard::Not(n) => self.uninitialized_guard_reads(*n, comp, acc),
+            Guard::Comp(_, a, b) | Guard::FloatComp(_, a, b) => {
+                let comp_v = self.env.cells[comp].unwrap_comp();
+                for port in [a, b] {
+                    let port = self.lookup_global_port_id(
+                        comp_v.convert_to_global_port(port),
+                    );
+                    let uninit = self
+                        .env
+                        .get_parent_path_from_port(port)
+                        .and_then(|(path, _)| path.last().copied())
+                        .and_then(|cell| self.env.cells[cell].as_primitive())
+                        .is_some_and(|prim| prim.is_uninitialized(port));
+                    if uninit {
+                        acc.push(port);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The component defining the assignment and a description of where in
+    /// the component it is defined.
+    fn assignment_location(
+        &self,
+        assign: AssignmentIdx,
+    ) -> (ComponentIdx, String) {
+        let ctx = self.ctx();
+        let (comp_idx, loc) = ctx.find_assignment_definition(assign);
+        let comp_name = ctx.lookup_name(comp_idx);
+        let loc = match loc {
+            AssignmentDefinitionLocation::Group(g) => {
+                format!("group {comp_name}::{}", ctx.lookup_name(g))
+            }
+            AssignmentDefinitionLocation::CombGroup(g) => {
+                format!("comb group {comp_name}::{}", ctx.lookup_name(g))
+            }
+            AssignmentDefinitionLocation::ContinuousAssignment => {
+                format!("{comp_name}'s continuous assignments")
+            }
+            AssignmentDefinitionLocation::Invoke(_) => {
+                format!("an invoke in {comp_name}")
+            }
+        };
+        (comp_idx, loc)
+    }
+
+    /// Warn about guards of active assignments that compare against
+    /// uninitialized registers. Each port is reported once per assignment.
+    fn report_uninitialized_reads(
+        &mut self,
+        guards: Vec<(GuardIdx, GlobalCellIdx, AssignmentIdx)>,
+    ) {
+        for (guard, comp, assign) in guards {
+            let mut ports = vec![];
+            self.uninitialized_guard_reads(guard, comp, &mut ports);
+            for port in ports {
+                if !self.reported_uninitialized.insert((port, assign)) {
+                    continue;
+                }
+                let ctx = self.ctx();
+                let (comp_idx, loc) = self.assignment_location(assign);
+                warn!(
+                    logging::root(),
+                    "Guard reads uninitialized register `{}' in {}: {}",
+                    self.get_full_name(port),
+                    loc,
+                    ctx.printer().print_assignment(comp_idx, assign).trim()
+                );
             }
         }
     }
@@ -1938,6 +2264,11 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
         assigns_bundle: &[ScheduledAssignments],
     ) -> InterpreterResult<()> {
         let mut has_changed = true;
+        // The control and the stateful primitives may have changed any port
+        // since the guards were last evaluated.
+        if let Some(cache) = &self.guard_cache {
+            cache.borrow_mut().clear();
+        }
 
         // TODO griffin: rewrite this so that someone can actually read it
         let done_ports: Vec<_> = assigns_bundle
@@ -1955,6 +2286,7 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
 
         while has_changed {
             has_changed = false;
+            let mut guards_to_check = vec![];
 
             // evaluate all the assignments and make updates
             for ScheduledAssignments {
@@ -1976,22 +2308,58 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                 for assign_idx in assignments {
                     let assign = &self.env.ctx.as_ref().primary[assign_idx];
 
-                    // TODO griffin: Come back to this unwrap default later
-                    // since we may want to do something different if the guard
-                    // does not have a defined value
-                    if self
-                        .evaluate_guard(assign.guard, *active_cell)
-                        .unwrap_or_default()
-                    // the go for the group is high
-                    && go
+                    let active = go
                         .as_ref()
                         // the group must have its go signal high and the go
                         // signal of the component must also be high
-                        .map(|g| self.env.ports[*g].as_bool().unwrap_or_default() && self.env.ports[comp_go].as_bool().unwrap_or_default())
+                        .map(|g| {
+                            self.env.ports[*g].as_bool().unwrap_or_default()
+                                && self.env.ports[comp_go]
+                                    .as_bool()
+                                    .unwrap_or_default()
+                        })
                         // if there is no go signal, then we want to run the
                         // assignment
-                        .unwrap_or(true)
+                        .unwrap_or(true);
+
+                    if self.check_uninitialized && active {
+                        guards_to_check.push((
+                            assign.guard,
+                            *active_cell,
+                            assign_idx,
+                        ));
+                    }
+
+                    let guard_val =
+                        self.evaluate_guard(assign.guard, *active_cell);
+                    if self.track_coverage && active {
+                        if let Some(val) = guard_val {
+                            let reached = self
+                                .guard_coverage
+                                .entry(assign_idx)
+                                .or_default();
+                            reached.0 |= val;
+                            reached.1 |= !val;
+                        }
+                    }
+
+                    if self.record_guards
+                        && !matches!(
+                            self.env.ctx.as_ref().primary[assign.guard],
+                            Guard::True
+                        )
                     {
+                        // Later evaluations in the cycle overwrite earlier
+                        // ones, so the last value is the settled one.
+                        if let Some(cycle) = self.guard_timeline.last_mut() {
+                            cycle.insert((*active_cell, assign_idx), guard_val);
+                        }
+                    }
+
+                    // TODO griffin: Come back to this unwrap default later
+                    // since we may want to do something different if the guard
+                    // does not have a defined value
+                    if guard_val.unwrap_or_default() && active {
                         let val = self.get_value(&assign.src, *active_cell);
                         let dest =
                             self.get_global_port_idx(&assign.dst, *active_cell);
@@ -2014,7 +2382,10 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                                 AssignedValue::new(v.val().clone(), assign_idx),
                             )?;
 
-                            has_changed |= changed.as_bool();
+                            if changed.as_bool() {
+                                self.invalidate_guards(dest);
+                                has_changed = true;
+                            }
                         } else if self.env.ports[dest].is_def() {
                             todo!("Raise an error here since this assignment is undefining things: {}. Port currently has value: {}", self.env.ctx.as_ref().printer().print_assignment(ledger.comp_id, assign_idx), &self.env.ports[dest])
                         }
@@ -2022,6 +2393,8 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                 }
             }
 
+            self.report_uninitialized_reads(guards_to_check);
+
             // Run all the primitives
             let changed: bool = self
                 .env
@@ -2039,7 +2412,13 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                 })?
                 .as_bool();
 
-            has_changed |= changed;
+            if changed {
+                // The primitives do not report which ports they wrote
+                if let Some(cache) = &self.guard_cache {
+                    cache.borrow_mut().revalidate(&self.env.ports);
+                }
+                has_changed = true;
+            }
 
             // check for undefined done ports. If any remain after we've
             // converged then they should be set to zero and we should continue
@@ -2049,6 +2428,7 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                     if self.env.ports[done_port].is_undef() {
                         self.env.ports[done_port] =
                             PortValue::new_implicit(BitVecValue::fals());
+                        self.invalidate_guards(done_port);
                         has_changed = true;
                     }
                 }
@@ -2216,6 +2596,19 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
     }
 }
 
+/// The VCD identifier code of the `n`th signal, written in base 94 with the
+/// printable ASCII characters as digits.
+fn vcd_identifier(mut n: usize) -> String {
+    let mut id = String::new();
+    loop {
+        id.push((b'!' + (n % 94) as u8) as char);
+        n /= 94;
+        if n == 0 {
+            return id;
+        }
+    }
+}
+
 pub trait GetFullName<C: AsRef<Context> + Clone> {
     fn get_full_name(&self, env: &Environment<C>) -> String;
 }
@@ -0,0 +1,179 @@
+use crate::flatten::{
+    flat_ir::{
+        prelude::{GlobalCellIdx, GlobalPortIdx, GuardIdx},
+        wires::guards::{Guard, GuardMap},
+    },
+    structures::{index_trait::IndexRef, indexed_map::AuxillaryMap},
+};
+use ahash::{HashMap, HashMapExt};
+use baa::BitVecValue;
+
+use super::env::PortMap;
+
+/// A guard evaluated in a component instance.
+type GuardKey = (GuardIdx, GlobalCellIdx);
+
+/// A cached guard value and the ports that were read to compute it.
+#[derive(Debug)]
+struct CachedGuard {
+    value: Option<bool>,
+    reads: Box<[GlobalPortIdx]>,
+}
+
+/// The cached guards that read a port, along with the value the port had when
+/// the first of them was evaluated.
+#[derive(Debug)]
+struct Readers {
+    value: Option<BitVecValue>,
+    guards: Vec<GuardKey>,
+}
+
+/// Memoizes guard values until one of the ports they read changes.
+///
+/// Guards are keyed on their structure rather than their [GuardIdx], so
+/// identical guards in different assignments share one entry. Evaluating a
+/// guard records the ports its leaves read; a write to one of those ports
+/// evicts exactly the guards that read it, including the guards containing
+/// them.
+#[derive(Debug)]
+pub(super) struct GuardCache {
+    /// The first guard that is structurally identical to each guard.
+    representative: AuxillaryMap<GuardIdx, GuardIdx>,
+    values: HashMap<GuardKey, CachedGuard>,
+    readers: HashMap<GlobalPortIdx, Readers>,
+    /// The ports read by the guards currently being evaluated, innermost
+    /// last.
+    reads: Vec<GlobalPortIdx>,
+    /// The number of guards currently being evaluated.
+    depth: usize,
+}
+
+impl GuardCache {
+    pub fn new(guards: &GuardMap) -> Self {
+        let mut representative =
+            AuxillaryMap::capacity_with_default(GuardIdx::new(0), guards.len());
+        let mut seen: HashMap<Guard, GuardIdx> = HashMap::new();
+        // Flattening places children after their parent, so visiting the
+        // guards in reverse resolves the children first.
+        let all: Vec<_> = guards.iter().collect();
+        for (idx, guard) in all.into_iter().rev() {
+            let rep = |g: &GuardIdx| *representative.get(*g);
+            let shape = match guard {
+                Guard::Or(a, b) => Guard::Or(rep(a), rep(b)),
+                Guard::And(a, b) => Guard::And(rep(a), rep(b)),
+                Guard::Implies(a, b) => Guard::Implies(rep(a), rep(b)),
+                Guard::Not(a) => Guard::Not(rep(a)),
+                leaf => leaf.clone(),
+            };
+            let first = *seen.entry(shape).or_insert(idx);
+            representative.insert(idx, first);
+        }
+
+        Self {
+            representative,
+            values: HashMap::new(),
+            readers: HashMap::new(),
+            reads: vec![],
+            depth: 0,
+        }
+    }
+
+    fn key(&self, guard: GuardIdx, comp: GlobalCellIdx) -> GuardKey {
+        (*self.representative.get(guard), comp)
+    }
+
+    /// Returns the cached value of the guard, if there is one, and counts its
+    /// ports as read by the enclosing guard.
+    pub fn lookup(
+        &mut self,
+        guard: GuardIdx,
+        comp: GlobalCellIdx,
+    ) -> Option<Option<bool>> {
+        let cached = self.values.get(&self.key(guard, comp))?;
+        if self.depth > 0 {
+            self.reads.extend_from_slice(&cached.reads);
+        }
+        Some(cached.value)
+    }
+
+    /// Starts evaluating a guard that is not cached. Returns the mark to pass
+    /// to [GuardCache::finish].
+    pub fn start(&mut self) -> usize {
+        self.depth += 1;
+        self.reads.len()
+    }
+
+    /// Records that the guard being evaluated read the given port.
+    pub fn read(&mut self, port: GlobalPortIdx) {
+        self.reads.push(port);
+    }
+
+    /// Caches the value of a guard once it has been evaluated.
+    pub fn finish(
+        &mut self,
+        guard: GuardIdx,
+        comp: GlobalCellIdx,
+        mark: usize,
+        value: Option<bool>,
+        ports: &PortMap,
+    ) {
+        let key = self.key(guard, comp);
+        let mut reads = self.reads[mark..].to_vec();
+        reads.sort();
+        reads.dedup();
+        for &port in &reads {
+            self.readers
+                .entry(port)
+                .or_insert_with(|| Readers {
+                    value: ports[port].val().cloned(),
+                    guards: vec![],
+                })
+                .guards
+                .push(key);
+        }
+        self.values.insert(
+            key,
+            CachedGuard {
+                value,
+                reads: reads.into_boxed_slice(),
+            },
+        );
+
+        self.depth -= 1;
+        if self.depth == 0 {
+            self.reads.clear();
+        }
+    }
+
+    /// Evicts the guards that read the port.
+    pub fn invalidate(&mut self, port: GlobalPortIdx) {
+        if let Some(readers) = self.readers.remove(&port) {
+            for key in readers.guards {
+                self.values.remove(&key);
+            }
+        }
+    }
+
+    /// Evicts the guards that read a port whose value has changed. Used when
+    /// the ports that were written are not known, such as after running the
+    /// primitives.
+    pub fn revalidate(&mut self, ports: &PortMap) {
+        let changed: Vec<_> = self
+            .readers
+            .iter()
+            .filter(|(port, readers)| {
+                ports[**port].val() != readers.value.as_ref()
+            })
+            .map(|(port, _)| *port)
+            .collect();
+        for port in changed {
+            self.invalidate(port);
+        }
+    }
+
+    /// Evicts every guard.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.readers.clear();
+    }
+}
@@ -1,5 +1,6 @@
 mod assignments;
 mod env;
+mod guard_cache;
 mod program_counter;
 mod traverser;
 
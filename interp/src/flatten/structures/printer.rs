@@ -378,11 +378,16 @@ impl<'a> Printer<'a> {
                 let r = self.format_guard(parent, *r);
                 format!("({} & {})", l, r)
             }
+            Guard::Implies(l, r) => {
+                let l = self.format_guard(parent, *l);
+                let r = self.format_guard(parent, *r);
+                format!("({} -> {})", l, r)
+            }
             Guard::Not(n) => {
                 let n = self.format_guard(parent, *n);
                 format!("!{}", n)
             }
-            Guard::Comp(op, l, r) => {
+            Guard::Comp(op, l, r) | Guard::FloatComp(op, l, r) => {
                 let l = self.lookup_id_from_port(parent, *l);
                 let r = self.lookup_id_from_port(parent, *r);
                 format!(
@@ -12,7 +12,7 @@ use interp::{
 
 use slog::warn;
 use std::{
-    io::stdout,
+    io::{stdout, Write},
     path::{Path, PathBuf},
 };
 
@@ -67,6 +67,25 @@ pub struct Opts {
     /// dumps all memories rather than just external ones
     #[argh(switch, long = "all-memories")]
     dump_all_memories: bool,
+    /// warn when a guard compares against a register that has never been
+    /// written. Registers marked `@zero_init` are exempt
+    #[argh(switch, long = "check-uninitialized")]
+    check_uninitialized_reads: bool,
+
+    /// reuse the value of a guard until a port it reads changes instead of
+    /// evaluating it for every assignment
+    #[argh(switch, long = "cache-guards")]
+    cache_guards: bool,
+
+    /// print, for every guarded assignment, whether its guard was ever true
+    /// and ever false while the assignment was active
+    #[argh(switch, long = "guard-coverage")]
+    guard_coverage: bool,
+
+    /// write the value of every guard in every cycle to the given file as a
+    /// VCD waveform
+    #[argh(option, long = "guard-vcd")]
+    guard_vcd: Option<OutputFile>,
 
     #[argh(subcommand)]
     mode: Option<Command>,
@@ -100,6 +119,10 @@ fn main() -> InterpreterResult<()> {
         .allow_par_conflicts(opts.allow_par_conflicts)
         .dump_registers(opts.dump_registers)
         .dump_all_memories(opts.dump_all_memories)
+        .check_uninitialized_reads(opts.check_uninitialized_reads)
+        .cache_guards(opts.cache_guards)
+        .guard_coverage(opts.guard_coverage)
+        .guard_timeline(opts.guard_vcd.is_some())
         .build();
 
     interp::logging::initialize_logger(config.quiet);
@@ -120,9 +143,22 @@ fn main() -> InterpreterResult<()> {
     match &command {
         Command::Interpret(_) => {
             let mut sim = Simulator::build_simulator(&i_ctx, &opts.data_file)?;
+            sim.check_uninitialized_reads(config.check_uninitialized_reads);
+            sim.cache_guards(config.cache_guards);
+            sim.track_guard_coverage(config.guard_coverage);
+            sim.record_guard_timeline(config.guard_timeline);
 
             sim.run_program()?;
 
+            if config.guard_coverage {
+                eprint!("{}", sim.guard_coverage_report());
+            }
+
+            if let Some(file) = &opts.guard_vcd {
+                let vcd = sim.guard_timeline_vcd();
+                file.get_write().write_all(vcd.as_bytes())?;
+            }
+
             let output = sim
                 .dump_memories(config.dump_registers, config.dump_all_memories);
 
@@ -68,6 +68,21 @@ pub struct Opts {
     #[argh(switch, long = "nested")]
     pub nested_assign: bool,
 
+    /// value driven onto a port when none of its guards are active:
+    /// `zero`, `x`, or `const:<n>`, where `<n>` is truncated to the width of
+    /// each port (only relevant to the Verilog backend)
+    #[argh(
+        option,
+        long = "guard-default",
+        default = "ir::GuardDefault::default()"
+    )]
+    pub guard_default: ir::GuardDefault,
+
+    /// emit guard comparisons as instances of comparison primitives such as
+    /// `std_lt` (only relevant to the Verilog backend)
+    #[argh(switch, long = "comparison-primitives")]
+    pub comparison_primitives: bool,
+
     /// emit extmodules to use with SystemVerilog implementations
     /// of primitives (only relevant to the FIRRTL backend)
     #[argh(switch, long = "emit-primitive-extmodules")]
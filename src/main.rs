@@ -67,6 +67,8 @@ fn main() -> PassResult<()> {
         synthesis_mode: opts.enable_synthesis,
         enable_verification: !opts.disable_verify,
         flat_assign: !opts.nested_assign,
+        guard_default: opts.guard_default,
+        comparison_primitives: opts.comparison_primitives,
         emit_primitive_extmodules: opts.emit_primitive_extmodules,
     };
     // Extra options for the passes